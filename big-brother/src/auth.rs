@@ -0,0 +1,105 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    big_brother::{BigBrotherError, BigBrotherMetapacket, BigBrotherPacket, Broadcastable},
+    serdes::{deserialize_packet, verify_packet, AuthKey, AuthSessions, PacketMetadata},
+    BigBrother,
+};
+
+pub const MAX_AUTHENTICATED_ADDRESSES: usize = 8;
+
+impl<'a, const NETWORK_MAP_SIZE: usize, P, A> BigBrother<'a, NETWORK_MAP_SIZE, P, A>
+where
+    P: Serialize + for<'de> Deserialize<'de>,
+    A: Copy
+        + PartialEq
+        + Eq
+        + Broadcastable
+        + Serialize
+        + for<'de> Deserialize<'de>
+        + core::fmt::Debug,
+{
+    /// Sets the pre-shared key used to sign every outgoing packet (including heartbeats and
+    /// acks) and to verify incoming ones. `None` goes back to sending unsigned packets.
+    pub fn set_authentication_key(&mut self, key: Option<AuthKey>) {
+        self.auth_key = key;
+    }
+
+    /// Drops any packet from `address` that isn't signed with our key. Passing the broadcast
+    /// address requires authentication from every peer. Signed packets are bound to the
+    /// sender's session, and to ours unless they're broadcast, so they can only be replayed
+    /// within the sessions they were sent in, where the dedupe counters catch them. Heartbeats
+    /// can't move a peer back to a session it has already left, see update_session_id.
+    pub fn require_authentication(&mut self, address: A) -> Result<(), BigBrotherError> {
+        if self.authenticated_addresses.contains(&Some(address)) {
+            return Ok(());
+        }
+
+        let entry = self
+            .authenticated_addresses
+            .iter_mut()
+            .find(|entry| entry.is_none())
+            .ok_or(BigBrotherError::AuthenticationListFull)?;
+        *entry = Some(address);
+
+        Ok(())
+    }
+
+    pub fn requires_authentication(&self, address: A) -> bool {
+        self.authenticated_addresses
+            .iter()
+            .flatten()
+            .any(|entry| *entry == address || entry.is_broadcast())
+    }
+
+    /// How many received packets were dropped for failing authentication
+    pub fn get_rejected_packets(&self) -> u32 {
+        self.rejected_packets
+    }
+
+    pub(crate) fn is_authenticated(&mut self, metadata: &PacketMetadata<A>, size: usize) -> bool {
+        let key = match self.auth_key {
+            Some(key) => key,
+            None => return false,
+        };
+
+        let sessions = self.auth_sessions(metadata);
+
+        verify_packet(&key, &sessions, &self.working_buffer, size)
+    }
+
+    /// The sessions the sender would have signed the packet in the working buffer with. Our
+    /// view of every session comes from the same heartbeats the sender's does, so this holds
+    /// for packets we're forwarding too
+    fn auth_sessions(&mut self, metadata: &PacketMetadata<A>) -> AuthSessions {
+        let packet: Option<BigBrotherPacket<P>> = deserialize_packet(&self.working_buffer).ok();
+
+        // A heartbeat is signed in the session it announces, which we may not know yet
+        let from_session_id = match packet {
+            Some(BigBrotherPacket::MetaPacket(BigBrotherMetapacket::Heartbeat { session_id })) => {
+                session_id
+            }
+            _ => self.known_session_id(metadata.from_addr),
+        };
+
+        let to_session_id = if metadata.to_addr.is_broadcast() {
+            0
+        } else if metadata.to_addr == self.host_addr {
+            self.session_id
+        } else {
+            self.known_session_id(metadata.to_addr)
+        };
+
+        AuthSessions {
+            from_session_id,
+            to_session_id,
+        }
+    }
+
+    fn known_session_id(&mut self, address: A) -> u32 {
+        match self.network_map.get_address_mapping(address) {
+            Ok(mapping) => mapping.from_session_id,
+            Err(_) => 0,
+        }
+    }
+}
@@ -1,10 +1,17 @@
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    dedupe::{self, is_duplicate},
+    auth::MAX_AUTHENTICATED_ADDRESSES,
+    dedupe::{self, is_duplicate, ReliableDedupe},
+    fragmentation::{FragmentHeader, FragmentIdType, ReassemblyBuffer, MAX_REASSEMBLY_BUFFERS},
     interface::BigBrotherInterface,
+    link_health::{LinkEvent, LinkEventQueue, DEFAULT_PEER_TIMEOUT_MS},
     network_map::NetworkMap,
-    serdes::{deserialize_metadata, deserialize_packet, serialize_packet, SerdesError},
+    reliable::{ReliableSlot, SequenceType, MAX_PENDING_ACKS, MAX_RELIABLE_IN_FLIGHT},
+    serdes::{
+        deserialize_metadata, deserialize_packet, deserialize_postcard, serialize_packet,
+        sign_packet, AuthKey, AuthSessions, SerdesError,
+    },
 };
 
 pub const UDP_PORT: u16 = 25560;
@@ -24,6 +31,10 @@ pub enum BigBrotherError {
     SocketBindFailure,
     SocketConfigFailure,
     SendFailure,
+    ReliableWindowFull,
+    ReliableBroadcast,
+    ReliableDeliveryFailed(SequenceType),
+    AuthenticationListFull,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,11 +47,16 @@ pub struct BigBrotherEndpoint {
 pub enum BigBrotherPacket<T> {
     MetaPacket(BigBrotherMetapacket),
     UserPacket(T),
+    ReliableUserPacket { sequence: SequenceType, packet: T },
+    Fragment(FragmentHeader),
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum BigBrotherMetapacket {
     Heartbeat { session_id: u32 },
+    Ack { sequence: SequenceType },
+    Ping { timestamp: u32 },
+    Pong { timestamp: u32 },
 }
 
 pub trait Broadcastable {
@@ -52,17 +68,29 @@ pub struct BigBrother<'a, const NETWORK_MAP_SIZE: usize, P, A> {
     pub(crate) host_addr: A,
     pub(crate) working_buffer: [u8; WORKING_BUFFER_SIZE],
     pub interfaces: [Option<&'a mut dyn BigBrotherInterface>; MAX_INTERFACE_COUNT],
-    broadcast_address: A,
+    pub(crate) broadcast_address: A,
     broadcast_counter: dedupe::CounterType,
-    session_id: u32,
+    pub(crate) session_id: u32,
     use_dedupe: bool,
     missed_packets: u32,
     last_heartbeat_timestamp: u32,
     last_bitrate_measurement_timestamp: u32,
     recv_byte_counter: usize,
-    send_byte_counter: usize,
+    pub(crate) send_byte_counter: usize,
     recv_bitrate: usize,
     send_bitrate: usize,
+    pub(crate) reliable_slots: [Option<ReliableSlot<A>>; MAX_RELIABLE_IN_FLIGHT],
+    pub(crate) reliable_sequence: SequenceType,
+    pub(crate) pending_acks: [Option<(A, SequenceType)>; MAX_PENDING_ACKS],
+    pub(crate) last_poll_timestamp: u32,
+    pub(crate) auth_key: Option<AuthKey>,
+    pub(crate) authenticated_addresses: [Option<A>; MAX_AUTHENTICATED_ADDRESSES],
+    pub(crate) rejected_packets: u32,
+    pub(crate) fragment_id: FragmentIdType,
+    pub(crate) reassembly_buffers: [ReassemblyBuffer<A>; MAX_REASSEMBLY_BUFFERS],
+    pub(crate) link_events: LinkEventQueue<A>,
+    pub(crate) peer_timeout_ms: u32,
+    pub(crate) last_ping_timestamp: u32,
     _packet_type: core::marker::PhantomData<P>,
 }
 
@@ -99,6 +127,18 @@ where
             send_byte_counter: 0,
             recv_bitrate: 0,
             send_bitrate: 0,
+            reliable_slots: [None; MAX_RELIABLE_IN_FLIGHT],
+            reliable_sequence: 0,
+            pending_acks: [None; MAX_PENDING_ACKS],
+            last_poll_timestamp: 0,
+            auth_key: None,
+            authenticated_addresses: [None; MAX_AUTHENTICATED_ADDRESSES],
+            rejected_packets: 0,
+            fragment_id: 0,
+            reassembly_buffers: [ReassemblyBuffer::new(); MAX_REASSEMBLY_BUFFERS],
+            link_events: LinkEventQueue::new(),
+            peer_timeout_ms: DEFAULT_PEER_TIMEOUT_MS,
+            last_ping_timestamp: 0,
             _packet_type: core::marker::PhantomData,
         };
 
@@ -113,7 +153,12 @@ where
     }
 
     pub fn send_packet(&mut self, packet: &P, destination: A) -> Result<(), BigBrotherError> {
-        self.send_bb_packet(BigBrotherPacket::UserPacket(packet), destination)
+        match self.send_bb_packet(BigBrotherPacket::UserPacket(packet), destination) {
+            Err(BigBrotherError::SerializationError(SerdesError::PacketTooLong)) => {
+                self.send_fragmented_packet(packet, destination)
+            }
+            result => result,
+        }
     }
 
     pub fn recv_packet(&mut self) -> Result<Option<(P, A)>, BigBrotherError> {
@@ -121,13 +166,26 @@ where
             .map(|packet| packet.map(|(packet, addr, _)| (packet, addr)))
     }
 
+    /// Same as recv_packet, but also returns the raw bytes the packet was received as. For
+    /// packets that were reassembled from fragments, this is the reassembled packet data
     pub fn recv_packet_raw(&mut self) -> Result<Option<(P, A, &[u8])>, BigBrotherError> {
+        self.flush_pending_acks();
+
         loop {
             if let Some((size, source_interface_index, remote)) = self.recv_next_udp()? {
                 self.recv_byte_counter += size;
 
                 let metadata = deserialize_metadata(&self.working_buffer)?;
 
+                // Checked before anything else so forged packets can't remap addresses or
+                // advance the dedupe counters
+                if self.requires_authentication(metadata.from_addr)
+                    && !self.is_authenticated(&metadata, size)
+                {
+                    self.rejected_packets += 1;
+                    continue;
+                }
+
                 if metadata.from_addr != self.host_addr {
                     // println!("{:?} Received packet from {:?} to {:?} ({:?}:{} @i{})", self.host_addr, metadata.from_addr, metadata.to_addr, remote.ip, remote.port, source_interface_index);
                 }
@@ -162,6 +220,20 @@ where
                             source_interface_index,
                             true,
                         )?;
+
+                        // Loss is only tracked for our own counters, forwarded packets use
+                        // the counters of whoever they're addressed to
+                        let missed_packets = match dedupe {
+                            Ok(missed_packets)
+                                if metadata.to_addr == self.host_addr
+                                    || metadata.to_addr.is_broadcast() =>
+                            {
+                                missed_packets as u32
+                            }
+                            _ => 0,
+                        };
+
+                        self.update_link_health(metadata.from_addr, missed_packets)?;
                     }
                 }
 
@@ -183,13 +255,82 @@ where
                                     None
                                 };
 
-                                self.network_map.update_session_id(
+                                let rebooted = self.network_map.update_session_id(
                                     metadata.from_addr,
                                     session_id,
                                     broadcast_counter,
                                 )?;
+
+                                if rebooted {
+                                    self.link_events
+                                        .push(LinkEvent::PeerRebooted(metadata.from_addr));
+                                }
+                            }
+                            BigBrotherMetapacket::Ack { sequence } => {
+                                self.handle_reliable_ack(metadata.from_addr, sequence);
+                            }
+                            BigBrotherMetapacket::Ping { timestamp } => {
+                                // The ping has already been deserialized, so replying here
+                                // is free to reuse the working buffer
+                                if metadata.from_addr != self.host_addr {
+                                    let _ = self.send_bb_packet(
+                                        BigBrotherPacket::MetaPacket(BigBrotherMetapacket::Pong {
+                                            timestamp,
+                                        }),
+                                        metadata.from_addr,
+                                    );
+                                }
+                            }
+                            BigBrotherMetapacket::Pong { timestamp } => {
+                                if metadata.to_addr == self.host_addr {
+                                    self.handle_pong(metadata.from_addr, timestamp)?;
+                                }
                             }
                         },
+                        BigBrotherPacket::ReliableUserPacket { sequence, packet } => {
+                            if dedupe.is_err() {
+                                continue;
+                            }
+
+                            // Duplicates are acked again as the previous ack may have been
+                            // the packet that was lost. Stale packets aren't, as there's no
+                            // telling whether they were ever delivered
+                            match self.reliable_dedupe(metadata.from_addr, sequence)? {
+                                ReliableDedupe::New => {
+                                    self.queue_reliable_ack(metadata.from_addr, sequence);
+
+                                    return Ok(Some((
+                                        packet,
+                                        metadata.from_addr,
+                                        &self.working_buffer[..size],
+                                    )));
+                                }
+                                ReliableDedupe::Duplicate => {
+                                    self.queue_reliable_ack(metadata.from_addr, sequence);
+                                }
+                                ReliableDedupe::Stale => {}
+                            }
+                        }
+                        BigBrotherPacket::Fragment(_) => {
+                            // Fragments skip the counter dedupe so they can be reordered,
+                            // duplicates are caught by their fragment ID instead
+                            if let Some(buffer_index) =
+                                self.reassemble_fragment(metadata.from_addr, size)?
+                            {
+                                let packet: BigBrotherPacket<P> = deserialize_postcard(
+                                    self.reassembly_buffers[buffer_index].data(),
+                                )
+                                .map_err(BigBrotherError::SerializationError)?;
+
+                                if let BigBrotherPacket::UserPacket(packet) = packet {
+                                    return Ok(Some((
+                                        packet,
+                                        metadata.from_addr,
+                                        self.reassembly_buffers[buffer_index].data(),
+                                    )));
+                                }
+                            }
+                        }
                         BigBrotherPacket::UserPacket(packet) => {
                             if dedupe.is_ok() {
                                 return Ok(Some((
@@ -210,6 +351,8 @@ where
     }
 
     pub fn poll_1ms(&mut self, timestamp: u32) {
+        self.last_poll_timestamp = timestamp;
+
         if timestamp.wrapping_sub(self.last_heartbeat_timestamp) > 100 {
             self.last_heartbeat_timestamp = timestamp;
 
@@ -221,9 +364,10 @@ where
             );
         }
 
-        if timestamp.wrapping_sub(self.last_bitrate_measurement_timestamp)
-            > BITRATE_MEASUREMENT_DURATION_MS
-        {
+        let end_of_window = timestamp.wrapping_sub(self.last_bitrate_measurement_timestamp)
+            > BITRATE_MEASUREMENT_DURATION_MS;
+
+        if end_of_window {
             self.last_bitrate_measurement_timestamp = timestamp;
 
             self.recv_bitrate =
@@ -235,6 +379,11 @@ where
             self.send_byte_counter = 0;
         }
 
+        self.flush_pending_acks();
+        self.poll_reliable_retransmits(timestamp);
+        self.expire_reassembly_buffers(timestamp);
+        self.poll_link_health(timestamp, end_of_window);
+
         for interface in &mut self.interfaces {
             if let Some(interface) = interface {
                 interface.poll(timestamp);
@@ -262,19 +411,38 @@ where
         self.send_bitrate
     }
 
-    fn send_bb_packet(
+    pub(crate) fn send_bb_packet(
         &mut self,
         packet: BigBrotherPacket<&P>,
         destination: A,
     ) -> Result<(), BigBrotherError> {
+        let host_addr = self.host_addr;
+
+        self.send_frame(destination, |buffer, counter| {
+            serialize_packet(&packet, host_addr, destination, counter, buffer)
+        })
+    }
+
+    /// Serializes a frame into the working buffer with the right counter for the destination,
+    /// signs it if we have a key, and sends it out the mapped (or every, for broadcasts) interface
+    pub(crate) fn send_frame<F>(
+        &mut self,
+        destination: A,
+        serialize: F,
+    ) -> Result<(), BigBrotherError>
+    where
+        F: FnOnce(&mut [u8], dedupe::CounterType) -> Result<usize, BigBrotherError>,
+    {
         if destination.is_broadcast() {
-            let size = serialize_packet(
-                &packet,
-                self.host_addr,
-                destination,
-                self.broadcast_counter,
-                &mut self.working_buffer,
-            )?;
+            let size = serialize(&mut self.working_buffer, self.broadcast_counter)?;
+            let sessions = AuthSessions {
+                from_session_id: self.session_id,
+                to_session_id: 0,
+            };
+            let size = match &self.auth_key {
+                Some(key) => sign_packet(key, &sessions, &mut self.working_buffer, size)?,
+                None => size,
+            };
             self.broadcast_counter = self.broadcast_counter.wrapping_add(1);
             self.send_byte_counter += size;
 
@@ -293,13 +461,15 @@ where
             Ok(())
         } else {
             let mapping = self.network_map.get_address_mapping(destination)?;
-            let size = serialize_packet(
-                &packet,
-                self.host_addr,
-                destination,
-                mapping.to_counter,
-                &mut self.working_buffer,
-            )?;
+            let size = serialize(&mut self.working_buffer, mapping.to_counter)?;
+            let sessions = AuthSessions {
+                from_session_id: self.session_id,
+                to_session_id: mapping.from_session_id,
+            };
+            let size = match &self.auth_key {
+                Some(key) => sign_packet(key, &sessions, &mut self.working_buffer, size)?,
+                None => size,
+            };
             mapping.to_counter = mapping.to_counter.wrapping_add(1);
             self.send_byte_counter += size;
 
@@ -458,6 +628,12 @@ mod tests {
                 BigBrotherPacket::MetaPacket(_) => {
                     panic!("Received metapacket");
                 }
+                BigBrotherPacket::ReliableUserPacket { .. } => {
+                    panic!("Received reliable packet");
+                }
+                BigBrotherPacket::Fragment(_) => {
+                    panic!("Received fragment");
+                }
                 BigBrotherPacket::UserPacket(packet) => {
                     assert_eq!(packet, test_packet);
                 }
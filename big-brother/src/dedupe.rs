@@ -1,6 +1,10 @@
-use crate::{big_brother::Broadcastable, network_map::NetworkMapEntry, serdes::PacketMetadata};
+use crate::{
+    big_brother::Broadcastable, network_map::NetworkMapEntry, reliable::SequenceType,
+    serdes::PacketMetadata,
+};
 
 pub type CounterType = u32;
+pub type ReliableWindowType = u32;
 
 /// Returns Ok() if not a duplicate w/ how many missed packets, Err() if duplicate
 pub fn is_duplicate<A>(
@@ -39,12 +43,74 @@ where
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReliableDedupe {
+    /// Not received before, and now marked as received
+    New,
+    /// Already received, and still inside the window
+    Duplicate,
+    /// Too far behind the window to tell either way
+    Stale,
+}
+
+/// Checks a reliable sequence number against the window of those received from the mapping.
+/// Reliable packets are retransmitted with fresh counters, so they need their own sliding
+/// window on top of the counter based dedupe
+pub fn reliable_dedupe<A>(
+    sequence: SequenceType,
+    mapping: &mut NetworkMapEntry<A>,
+) -> ReliableDedupe {
+    let window_size = ReliableWindowType::BITS as SequenceType;
+
+    // The newest sequence always has its bit set, so an empty window means nothing has been
+    // received since the session started and the sender could be anywhere in the sequence
+    if mapping.reliable_window == 0 {
+        mapping.reliable_sequence = sequence;
+        mapping.reliable_window = 1;
+
+        return ReliableDedupe::New;
+    }
+
+    // Same wrapped sub trick as is_duplicate, newer sequences will be below MAX/2
+    let diff = sequence.wrapping_sub(mapping.reliable_sequence);
+
+    if diff != 0 && diff < SequenceType::MAX / 2 {
+        mapping.reliable_window = if diff >= window_size {
+            0
+        } else {
+            mapping.reliable_window << diff
+        };
+        mapping.reliable_window |= 1;
+        mapping.reliable_sequence = sequence;
+
+        ReliableDedupe::New
+    } else {
+        let age = mapping.reliable_sequence.wrapping_sub(sequence);
+
+        if age >= window_size {
+            return ReliableDedupe::Stale;
+        }
+
+        let bit = 1 << age;
+        let duplicate = mapping.reliable_window & bit != 0;
+        mapping.reliable_window |= bit;
+
+        if duplicate {
+            ReliableDedupe::Duplicate
+        } else {
+            ReliableDedupe::New
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{
         big_brother::{Broadcastable, UDP_PORT},
-        dedupe::{is_duplicate, CounterType},
-        network_map::NetworkMapEntry,
+        dedupe::{is_duplicate, reliable_dedupe, CounterType, ReliableDedupe},
+        link_health::LinkHealth,
+        network_map::{NetworkMapEntry, SESSION_HISTORY_SIZE},
+        reliable::SequenceType,
         serdes::PacketMetadata,
     };
 
@@ -66,6 +132,11 @@ mod tests {
             from_counter: 0,
             broadcast_counter: 0,
             from_session_id: 0,
+            previous_session_ids: [0; SESSION_HISTORY_SIZE],
+            reliable_sequence: 0,
+            reliable_window: 0,
+            last_fragment_id: None,
+            link_health: LinkHealth::new(),
         };
 
         let mut metadata = PacketMetadata {
@@ -110,6 +181,11 @@ mod tests {
             from_counter: 0,
             broadcast_counter: 0,
             from_session_id: 0,
+            previous_session_ids: [0; SESSION_HISTORY_SIZE],
+            reliable_sequence: 0,
+            reliable_window: 0,
+            last_fragment_id: None,
+            link_health: LinkHealth::new(),
         };
 
         let mut metadata = PacketMetadata {
@@ -150,6 +226,11 @@ mod tests {
     //         from_counter: 0,
     //         broadcast_counter: 0,
     //         from_session_id: 0,
+    //         previous_session_ids: [0; SESSION_HISTORY_SIZE],
+    //         reliable_sequence: 0,
+    //         reliable_window: 0,
+    //         last_fragment_id: None,
+    //         link_health: LinkHealth::new(),
     //     };
 
     //     let mut metadata = PacketMetadata {
@@ -172,6 +253,11 @@ mod tests {
             from_counter: 0,
             broadcast_counter: 0,
             from_session_id: 0,
+            previous_session_ids: [0; SESSION_HISTORY_SIZE],
+            reliable_sequence: 0,
+            reliable_window: 0,
+            last_fragment_id: None,
+            link_health: LinkHealth::new(),
         };
 
         let mut metadata = PacketMetadata {
@@ -207,6 +293,11 @@ mod tests {
             from_counter: 0,
             broadcast_counter: 0,
             from_session_id: 0,
+            previous_session_ids: [0; SESSION_HISTORY_SIZE],
+            reliable_sequence: 0,
+            reliable_window: 0,
+            last_fragment_id: None,
+            link_health: LinkHealth::new(),
         };
 
         let mut metadata = PacketMetadata {
@@ -242,6 +333,11 @@ mod tests {
             from_counter: 0,
             broadcast_counter: 0,
             from_session_id: 0,
+            previous_session_ids: [0; SESSION_HISTORY_SIZE],
+            reliable_sequence: 0,
+            reliable_window: 0,
+            last_fragment_id: None,
+            link_health: LinkHealth::new(),
         };
 
         let mut metadata = PacketMetadata {
@@ -275,6 +371,87 @@ mod tests {
         assert_eq!(mapping.from_counter, 0);
     }
 
+    #[test]
+    fn reliable_dedupe_window() {
+        let mut mapping = NetworkMapEntry {
+            network_address: TestNetworkAddress::A,
+            ip: [192, 168, 0, 1],
+            port: UDP_PORT,
+            interface_index: 0,
+            to_counter: 0,
+            from_counter: 0,
+            broadcast_counter: 0,
+            from_session_id: 0,
+            previous_session_ids: [0; SESSION_HISTORY_SIZE],
+            reliable_sequence: 0,
+            reliable_window: 0,
+            last_fragment_id: None,
+            link_health: LinkHealth::new(),
+        };
+
+        assert_eq!(reliable_dedupe(0, &mut mapping), ReliableDedupe::New);
+        assert_eq!(reliable_dedupe(0, &mut mapping), ReliableDedupe::Duplicate);
+
+        // Out of order packets within the window are accepted once
+        assert_eq!(reliable_dedupe(3, &mut mapping), ReliableDedupe::New);
+        assert_eq!(reliable_dedupe(1, &mut mapping), ReliableDedupe::New);
+        assert_eq!(reliable_dedupe(1, &mut mapping), ReliableDedupe::Duplicate);
+        assert_eq!(reliable_dedupe(2, &mut mapping), ReliableDedupe::New);
+        assert_eq!(reliable_dedupe(3, &mut mapping), ReliableDedupe::Duplicate);
+        assert_eq!(mapping.reliable_sequence, 3);
+
+        // Packets that have fallen out of the window can't be told apart
+        assert_eq!(reliable_dedupe(100, &mut mapping), ReliableDedupe::New);
+        assert_eq!(reliable_dedupe(4, &mut mapping), ReliableDedupe::Stale);
+
+        // Make sure the sequence can wrap around
+        mapping.reliable_sequence = SequenceType::MAX - 1;
+        mapping.reliable_window = 1;
+        assert_eq!(
+            reliable_dedupe(SequenceType::MAX, &mut mapping),
+            ReliableDedupe::New
+        );
+        assert_eq!(reliable_dedupe(1, &mut mapping), ReliableDedupe::New);
+        assert_eq!(reliable_dedupe(0, &mut mapping), ReliableDedupe::New);
+        assert_eq!(
+            reliable_dedupe(SequenceType::MAX, &mut mapping),
+            ReliableDedupe::Duplicate
+        );
+        assert_eq!(mapping.reliable_sequence, 1);
+    }
+
+    #[test]
+    fn reliable_dedupe_seeded_after_reset() {
+        let mut mapping = NetworkMapEntry {
+            network_address: TestNetworkAddress::A,
+            ip: [192, 168, 0, 1],
+            port: UDP_PORT,
+            interface_index: 0,
+            to_counter: 0,
+            from_counter: 0,
+            broadcast_counter: 0,
+            from_session_id: 0,
+            previous_session_ids: [0; SESSION_HISTORY_SIZE],
+            reliable_sequence: 0,
+            reliable_window: 0,
+            last_fragment_id: None,
+            link_health: LinkHealth::new(),
+        };
+
+        // A sender that's been up a while, more than half the sequence space from 0
+        let sequence = SequenceType::MAX / 2 + 100;
+        assert_eq!(reliable_dedupe(sequence, &mut mapping), ReliableDedupe::New);
+        assert_eq!(
+            reliable_dedupe(sequence, &mut mapping),
+            ReliableDedupe::Duplicate
+        );
+        assert_eq!(
+            reliable_dedupe(sequence + 1, &mut mapping),
+            ReliableDedupe::New
+        );
+        assert_eq!(mapping.reliable_sequence, sequence + 1);
+    }
+
     impl Broadcastable for TestNetworkAddress {
         fn is_broadcast(&self) -> bool {
             match self {
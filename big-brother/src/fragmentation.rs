@@ -0,0 +1,207 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    big_brother::{BigBrotherError, BigBrotherPacket, Broadcastable},
+    serdes::{deserialize_fragment, serialize_fragment, serialize_postcard},
+    BigBrother,
+};
+
+pub type FragmentIdType = u16;
+
+/// How much of the packet each fragment carries. Sized so a fragment with its metadata, header
+/// and authentication tag still fits in the working buffer
+pub const FRAGMENT_DATA_SIZE: usize = 192;
+pub const MAX_FRAGMENTED_PACKET_SIZE: usize = 1024;
+pub const MAX_FRAGMENT_COUNT: usize = MAX_FRAGMENTED_PACKET_SIZE.div_ceil(FRAGMENT_DATA_SIZE);
+pub const MAX_REASSEMBLY_BUFFERS: usize = 2;
+pub const REASSEMBLY_TIMEOUT_MS: u32 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FragmentHeader {
+    pub fragment_id: FragmentIdType,
+    pub index: u8,
+    pub count: u8,
+    pub total_size: u16,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ReassemblyBuffer<A> {
+    from_addr: Option<A>,
+    fragment_id: FragmentIdType,
+    count: u8,
+    total_size: u16,
+    received_mask: u32,
+    start_timestamp: u32,
+    data: [u8; MAX_FRAGMENTED_PACKET_SIZE],
+}
+
+impl<A> ReassemblyBuffer<A> {
+    pub(crate) const fn new() -> Self {
+        Self {
+            from_addr: None,
+            fragment_id: 0,
+            count: 0,
+            total_size: 0,
+            received_mask: 0,
+            start_timestamp: 0,
+            data: [0_u8; MAX_FRAGMENTED_PACKET_SIZE],
+        }
+    }
+
+    pub(crate) fn data(&self) -> &[u8] {
+        &self.data[..(self.total_size as usize)]
+    }
+}
+
+impl<'a, const NETWORK_MAP_SIZE: usize, P, A> BigBrother<'a, NETWORK_MAP_SIZE, P, A>
+where
+    P: Serialize + for<'de> Deserialize<'de>,
+    A: Copy
+        + PartialEq
+        + Eq
+        + Broadcastable
+        + Serialize
+        + for<'de> Deserialize<'de>
+        + core::fmt::Debug,
+{
+    pub(crate) fn send_fragmented_packet(
+        &mut self,
+        packet: &P,
+        destination: A,
+    ) -> Result<(), BigBrotherError> {
+        let mut packet_buffer = [0_u8; MAX_FRAGMENTED_PACKET_SIZE];
+        let packet_size =
+            serialize_postcard(&BigBrotherPacket::UserPacket(packet), &mut packet_buffer)
+                .map_err(BigBrotherError::SerializationError)?;
+
+        let host_addr = self.host_addr;
+        let fragment_id = self.fragment_id;
+        self.fragment_id = self.fragment_id.wrapping_add(1);
+
+        let count = packet_size.div_ceil(FRAGMENT_DATA_SIZE);
+
+        for (index, data) in packet_buffer[..packet_size]
+            .chunks(FRAGMENT_DATA_SIZE)
+            .enumerate()
+        {
+            let header = FragmentHeader {
+                fragment_id,
+                index: index as u8,
+                count: count as u8,
+                total_size: packet_size as u16,
+            };
+
+            self.send_frame(destination, |buffer, counter| {
+                serialize_fragment(&header, data, host_addr, destination, counter, buffer)
+            })?;
+        }
+
+        Ok(())
+    }
+
+    /// Copies the fragment in the working buffer into its reassembly buffer. Returns the index
+    /// of the reassembly buffer once every fragment of the packet has been received
+    pub(crate) fn reassemble_fragment(
+        &mut self,
+        from_addr: A,
+        size: usize,
+    ) -> Result<Option<usize>, BigBrotherError> {
+        let (header, data) = deserialize_fragment(&self.working_buffer[..size])?;
+
+        let count = header.count as usize;
+        let index = header.index as usize;
+        let total_size = header.total_size as usize;
+        let offset = index * FRAGMENT_DATA_SIZE;
+        let expected_size = FRAGMENT_DATA_SIZE.min(total_size.saturating_sub(offset));
+
+        // Silently drop anything malformed, the same as a lost fragment
+        if count == 0
+            || count > MAX_FRAGMENT_COUNT
+            || index >= count
+            || total_size > MAX_FRAGMENTED_PACKET_SIZE
+            || total_size.div_ceil(FRAGMENT_DATA_SIZE) != count
+            || data.len() != expected_size
+        {
+            return Ok(None);
+        }
+
+        // Fragments from packets we've already reassembled are duplicates
+        let mapping = self.network_map.get_address_mapping(from_addr)?;
+        if let Some(last_fragment_id) = mapping.last_fragment_id {
+            let diff = header.fragment_id.wrapping_sub(last_fragment_id);
+
+            if diff == 0 || diff >= FragmentIdType::MAX / 2 {
+                return Ok(None);
+            }
+        }
+
+        let buffer_index = match self.reassembly_buffers.iter().position(|buffer| {
+            buffer.from_addr == Some(from_addr) && buffer.fragment_id == header.fragment_id
+        }) {
+            Some(buffer_index) => buffer_index,
+            None => {
+                // Take a free buffer, or give up on the oldest packet if there aren't any
+                let buffer_index = self
+                    .reassembly_buffers
+                    .iter()
+                    .position(|buffer| buffer.from_addr.is_none())
+                    .unwrap_or_else(|| {
+                        let mut oldest_index = 0;
+
+                        for (i, buffer) in self.reassembly_buffers.iter().enumerate() {
+                            let age = self
+                                .last_poll_timestamp
+                                .wrapping_sub(buffer.start_timestamp);
+                            let oldest_age = self.last_poll_timestamp.wrapping_sub(
+                                self.reassembly_buffers[oldest_index].start_timestamp,
+                            );
+
+                            if age > oldest_age {
+                                oldest_index = i;
+                            }
+                        }
+
+                        oldest_index
+                    });
+
+                let buffer = &mut self.reassembly_buffers[buffer_index];
+                buffer.from_addr = Some(from_addr);
+                buffer.fragment_id = header.fragment_id;
+                buffer.count = header.count;
+                buffer.total_size = header.total_size;
+                buffer.received_mask = 0;
+                buffer.start_timestamp = self.last_poll_timestamp;
+
+                buffer_index
+            }
+        };
+
+        let buffer = &mut self.reassembly_buffers[buffer_index];
+        if buffer.count != header.count || buffer.total_size != header.total_size {
+            return Ok(None);
+        }
+
+        buffer.data[offset..(offset + data.len())].copy_from_slice(data);
+        buffer.received_mask |= 1 << index;
+
+        if buffer.received_mask == (1 << count) - 1 {
+            // Free the buffer, but the data stays put for the caller to deserialize
+            buffer.from_addr = None;
+            mapping.last_fragment_id = Some(header.fragment_id);
+
+            return Ok(Some(buffer_index));
+        }
+
+        Ok(None)
+    }
+
+    pub(crate) fn expire_reassembly_buffers(&mut self, timestamp: u32) {
+        for buffer in &mut self.reassembly_buffers {
+            if buffer.from_addr.is_some()
+                && timestamp.wrapping_sub(buffer.start_timestamp) > REASSEMBLY_TIMEOUT_MS
+            {
+                buffer.from_addr = None;
+            }
+        }
+    }
+}
@@ -14,6 +14,9 @@ pub struct MockPhysicalNet {
     subnet_mask: [bool; 4],
     broadcast_ip: [u8; 4],
     packet_log: Option<Vec<MockPayload>>,
+    drop_count: usize,
+    reorder_count: usize,
+    reorder_buffer: Vec<MockPayload>,
 }
 
 impl MockPhysicalNet {
@@ -25,6 +28,9 @@ impl MockPhysicalNet {
             subnet_mask,
             broadcast_ip,
             packet_log: None,
+            drop_count: 0,
+            reorder_count: 0,
+            reorder_buffer: Vec::new(),
         }
     }
 
@@ -35,6 +41,29 @@ impl MockPhysicalNet {
             log.push(payload.clone());
         }
 
+        if self.drop_count > 0 {
+            self.drop_count -= 1;
+            return;
+        }
+
+        if self.reorder_count > 0 {
+            self.reorder_buffer.push(payload);
+
+            if self.reorder_buffer.len() == self.reorder_count {
+                self.reorder_count = 0;
+
+                for payload in std::mem::take(&mut self.reorder_buffer).into_iter().rev() {
+                    self.deliver_udp(payload);
+                }
+            }
+
+            return;
+        }
+
+        self.deliver_udp(payload);
+    }
+
+    fn deliver_udp(&mut self, payload: MockPayload) {
         if payload.host.ip == self.broadcast_ip {
             // println!("port, {} broadcasted to {} interfaces", payload.host.port, self.interface_map.len());
 
@@ -89,6 +118,16 @@ impl MockPhysicalNet {
         self.broadcast_ip
     }
 
+    /// Silently drops the next `count` payloads sent on this network, to simulate packet loss
+    pub fn drop_next_payloads(&mut self, count: usize) {
+        self.drop_count = count;
+    }
+
+    /// Holds the next `count` payloads sent on this network, then delivers them in reverse order
+    pub fn reorder_next_payloads(&mut self, count: usize) {
+        self.reorder_count = count;
+    }
+
     pub fn enable_payload_logging(&mut self) {
         if self.packet_log.is_none() {
             self.packet_log = Some(Vec::new());
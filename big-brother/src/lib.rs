@@ -1,11 +1,15 @@
 #![cfg_attr(all(not(test), feature = "no_std"), no_std)]
 #![forbid(unsafe_code)]
 
+pub mod auth;
 pub mod big_brother;
 mod dedupe;
 pub(crate) mod forwarding;
+pub mod fragmentation;
 pub mod interface;
+pub mod link_health;
 mod network_map;
+pub mod reliable;
 pub mod serdes;
 
 pub use crate::big_brother::BigBrother;
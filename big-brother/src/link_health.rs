@@ -0,0 +1,206 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    big_brother::{BigBrotherError, BigBrotherMetapacket, BigBrotherPacket, Broadcastable},
+    BigBrother,
+};
+
+pub const MAX_LINK_EVENTS: usize = 8;
+pub const DEFAULT_PEER_TIMEOUT_MS: u32 = 500;
+pub const PING_INTERVAL_MS: u32 = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LinkHealth {
+    pub alive: bool,
+    pub last_seen_timestamp: u32,
+    /// Smoothed round trip time from ping/pong, None until the first pong is received
+    pub rtt_ms: Option<u32>,
+    /// Fraction of packets from the peer that were missed over the last measurement window
+    pub loss_rate: f32,
+    pub received_packets: u32,
+    pub missed_packets: u32,
+}
+
+impl LinkHealth {
+    pub const fn new() -> Self {
+        Self {
+            alive: false,
+            last_seen_timestamp: 0,
+            rtt_ms: None,
+            loss_rate: 0.0,
+            received_packets: 0,
+            missed_packets: 0,
+        }
+    }
+}
+
+impl Default for LinkHealth {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkEvent<A> {
+    PeerAppeared(A),
+    PeerLost(A),
+    /// The peer started sending heartbeats with a new session ID
+    PeerRebooted(A),
+}
+
+pub(crate) struct LinkEventQueue<A> {
+    events: [Option<LinkEvent<A>>; MAX_LINK_EVENTS],
+    head: usize,
+    count: usize,
+}
+
+impl<A: Copy> LinkEventQueue<A> {
+    pub(crate) const fn new() -> Self {
+        Self {
+            events: [None; MAX_LINK_EVENTS],
+            head: 0,
+            count: 0,
+        }
+    }
+
+    /// Drops the oldest event if the queue is full
+    pub(crate) fn push(&mut self, event: LinkEvent<A>) {
+        if self.count == MAX_LINK_EVENTS {
+            self.head = (self.head + 1) % MAX_LINK_EVENTS;
+            self.count -= 1;
+        }
+
+        self.events[(self.head + self.count) % MAX_LINK_EVENTS] = Some(event);
+        self.count += 1;
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<LinkEvent<A>> {
+        if self.count == 0 {
+            return None;
+        }
+
+        let event = self.events[self.head].take();
+        self.head = (self.head + 1) % MAX_LINK_EVENTS;
+        self.count -= 1;
+
+        event
+    }
+}
+
+impl<'a, const NETWORK_MAP_SIZE: usize, P, A> BigBrother<'a, NETWORK_MAP_SIZE, P, A>
+where
+    P: Serialize + for<'de> Deserialize<'de>,
+    A: Copy
+        + PartialEq
+        + Eq
+        + Broadcastable
+        + Serialize
+        + for<'de> Deserialize<'de>
+        + core::fmt::Debug,
+{
+    pub fn get_link_health(&mut self, address: A) -> Option<LinkHealth> {
+        self.network_map
+            .get_address_mapping(address)
+            .ok()
+            .map(|mapping| mapping.link_health)
+    }
+
+    /// How long a peer can go without sending anything before it's considered lost
+    pub fn set_peer_timeout(&mut self, timeout_ms: u32) {
+        self.peer_timeout_ms = timeout_ms;
+    }
+
+    /// Takes the oldest link event. If events aren't taken fast enough, the oldest ones are
+    /// dropped once MAX_LINK_EVENTS are queued
+    pub fn take_link_event(&mut self) -> Option<LinkEvent<A>> {
+        self.link_events.pop()
+    }
+
+    /// Called for every valid packet from a peer, with the number of packets the dedupe
+    /// counters say were missed before it
+    pub(crate) fn update_link_health(
+        &mut self,
+        from_addr: A,
+        missed_packets: u32,
+    ) -> Result<(), BigBrotherError> {
+        let timestamp = self.last_poll_timestamp;
+        let link_health = &mut self.network_map.get_address_mapping(from_addr)?.link_health;
+
+        link_health.last_seen_timestamp = timestamp;
+        link_health.received_packets += 1;
+
+        if link_health.alive {
+            link_health.missed_packets += missed_packets;
+        } else {
+            // The counters can't be trusted across an outage, so don't count it as loss
+            link_health.alive = true;
+            self.link_events.push(LinkEvent::PeerAppeared(from_addr));
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn handle_pong(
+        &mut self,
+        from_addr: A,
+        timestamp: u32,
+    ) -> Result<(), BigBrotherError> {
+        let sample = self.last_poll_timestamp.wrapping_sub(timestamp);
+
+        // The clock has stepped back since the ping, so the echo looks like it's from the future
+        if (sample as i32) < 0 {
+            return Ok(());
+        }
+
+        let link_health = &mut self.network_map.get_address_mapping(from_addr)?.link_health;
+
+        link_health.rtt_ms = Some(match link_health.rtt_ms {
+            Some(rtt_ms) => (rtt_ms * 7 + sample) / 8,
+            None => sample,
+        });
+
+        Ok(())
+    }
+
+    pub(crate) fn poll_link_health(&mut self, timestamp: u32, end_of_window: bool) {
+        if timestamp.wrapping_sub(self.last_ping_timestamp) >= PING_INTERVAL_MS {
+            self.last_ping_timestamp = timestamp;
+
+            let _ = self.send_bb_packet(
+                BigBrotherPacket::MetaPacket(BigBrotherMetapacket::Ping { timestamp }),
+                self.broadcast_address,
+            );
+        }
+
+        let host_addr = self.host_addr;
+        let peer_timeout_ms = self.peer_timeout_ms;
+
+        for mapping in self.network_map.iter_mut() {
+            if mapping.network_address == host_addr {
+                continue;
+            }
+
+            let link_health = &mut mapping.link_health;
+
+            if end_of_window {
+                let total_packets = link_health.received_packets + link_health.missed_packets;
+
+                if total_packets > 0 {
+                    link_health.loss_rate =
+                        link_health.missed_packets as f32 / total_packets as f32;
+                }
+
+                link_health.received_packets = 0;
+                link_health.missed_packets = 0;
+            }
+
+            if link_health.alive
+                && timestamp.wrapping_sub(link_health.last_seen_timestamp) > peer_timeout_ms
+            {
+                link_health.alive = false;
+                self.link_events
+                    .push(LinkEvent::PeerLost(mapping.network_address));
+            }
+        }
+    }
+}
@@ -1,8 +1,13 @@
 use serde::{Deserialize, Serialize};
 
-use crate::{big_brother::BigBrotherError, dedupe};
+use crate::{
+    big_brother::BigBrotherError, dedupe, fragmentation::FragmentIdType, link_health::LinkHealth,
+    reliable::SequenceType,
+};
 
 pub const MAX_UPSTREAM_LOCAL_PORTS: usize = 4;
+/// How many sessions a peer can reboot through before the oldest could be replayed again
+pub const SESSION_HISTORY_SIZE: usize = 8;
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize)]
 pub struct NetworkMapEntry<T> {
@@ -14,6 +19,11 @@ pub struct NetworkMapEntry<T> {
     pub from_counter: dedupe::CounterType,
     pub broadcast_counter: dedupe::CounterType,
     pub from_session_id: u32,
+    pub previous_session_ids: [u32; SESSION_HISTORY_SIZE],
+    pub reliable_sequence: SequenceType,
+    pub reliable_window: dedupe::ReliableWindowType,
+    pub last_fragment_id: Option<FragmentIdType>,
+    pub link_health: LinkHealth,
 }
 
 pub struct NetworkMap<T, const NETWORK_MAP_SIZE: usize> {
@@ -73,6 +83,11 @@ where
                                 from_counter: mapping.from_counter,
                                 broadcast_counter: mapping.broadcast_counter,
                                 from_session_id: mapping.from_session_id,
+                                previous_session_ids: mapping.previous_session_ids,
+                                reliable_sequence: mapping.reliable_sequence,
+                                reliable_window: mapping.reliable_window,
+                                last_fragment_id: mapping.last_fragment_id,
+                                link_health: mapping.link_health,
                             };
 
                             // print!("{} (i{})", mapping.broadcast_counter, interface_index);
@@ -93,6 +108,11 @@ where
                         from_counter: 0,
                         broadcast_counter: 0,
                         from_session_id: 0,
+                        previous_session_ids: [0; SESSION_HISTORY_SIZE],
+                        reliable_sequence: 0,
+                        reliable_window: 0,
+                        last_fragment_id: None,
+                        link_health: LinkHealth::new(),
                     });
 
                     if from_address == self.host_addr {
@@ -135,21 +155,42 @@ where
         Err(BigBrotherError::UnknownNetworkAddress)
     }
 
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut NetworkMapEntry<T>> {
+        self.network_map.iter_mut().flatten()
+    }
+
+    /// Returns true if the address rebooted, i.e. it already had a session that changed.
+    /// Sessions the address has already moved on from are ignored, otherwise a recorded
+    /// heartbeat could roll the counters back and let the rest of that session be replayed
     pub fn update_session_id(
         &mut self,
         address: T,
         session_id: u32,
         broadcast_counter: Option<dedupe::CounterType>,
-    ) -> Result<(), BigBrotherError> {
+    ) -> Result<bool, BigBrotherError> {
         let mapping = self.get_address_mapping(address)?;
 
-        if session_id != mapping.from_session_id {
+        if session_id != mapping.from_session_id
+            && !mapping.previous_session_ids.contains(&session_id)
+        {
+            let rebooted = mapping.from_session_id != 0;
+
+            if rebooted {
+                mapping.previous_session_ids.rotate_right(1);
+                mapping.previous_session_ids[0] = mapping.from_session_id;
+            }
+
             mapping.from_counter = 0;
             mapping.broadcast_counter = broadcast_counter.unwrap_or(0);
             mapping.from_session_id = session_id;
+            mapping.reliable_sequence = 0;
+            mapping.reliable_window = 0;
+            mapping.last_fragment_id = None;
+
+            return Ok(rebooted);
         }
 
-        Ok(())
+        Ok(false)
     }
 
     pub fn get_upstream_local_ports(&self) -> &[u16] {
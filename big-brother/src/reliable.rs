@@ -0,0 +1,218 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    big_brother::{
+        BigBrotherEndpoint, BigBrotherError, BigBrotherMetapacket, BigBrotherPacket, Broadcastable,
+        WORKING_BUFFER_SIZE,
+    },
+    dedupe::{reliable_dedupe, ReliableDedupe},
+    serdes::{serialize_packet_bytes, serialize_postcard, sign_packet, AuthSessions},
+    BigBrother,
+};
+
+pub type SequenceType = u16;
+
+/// How many reliable packets can be awaiting an ack at once. Each slot holds a full working
+/// buffer for retransmission, so keep this small for no_std targets
+pub const MAX_RELIABLE_IN_FLIGHT: usize = 4;
+pub const MAX_PENDING_ACKS: usize = 8;
+pub const RELIABLE_INITIAL_TIMEOUT_MS: u32 = 20;
+pub const RELIABLE_MAX_TIMEOUT_MS: u32 = 320;
+pub const RELIABLE_MAX_RETRIES: u8 = 5;
+
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct ReliableSlot<A> {
+    destination: A,
+    sequence: SequenceType,
+    last_send_timestamp: u32,
+    timeout_ms: u32,
+    retries: u8,
+    failed: bool,
+    packet_size: usize,
+    packet_data: [u8; WORKING_BUFFER_SIZE],
+}
+
+impl<'a, const NETWORK_MAP_SIZE: usize, P, A> BigBrother<'a, NETWORK_MAP_SIZE, P, A>
+where
+    P: Serialize + for<'de> Deserialize<'de>,
+    A: Copy
+        + PartialEq
+        + Eq
+        + Broadcastable
+        + Serialize
+        + for<'de> Deserialize<'de>
+        + core::fmt::Debug,
+{
+    /// Sends a packet that is retransmitted (with backoff) from poll_1ms until the destination
+    /// acknowledges it. Returns the sequence number of the packet, which stays in flight until
+    /// acked. If it runs out of retries, check_reliable_deliveries() will return a
+    /// ReliableDeliveryFailed error for it.
+    pub fn send_packet_reliable(
+        &mut self,
+        packet: &P,
+        destination: A,
+    ) -> Result<SequenceType, BigBrotherError> {
+        if destination.is_broadcast() {
+            return Err(BigBrotherError::ReliableBroadcast);
+        }
+
+        let slot_index = self
+            .reliable_slots
+            .iter()
+            .position(|slot| slot.is_none())
+            .ok_or(BigBrotherError::ReliableWindowFull)?;
+
+        let sequence = self.reliable_sequence;
+        let mut slot = ReliableSlot {
+            destination,
+            sequence,
+            last_send_timestamp: self.last_poll_timestamp,
+            timeout_ms: RELIABLE_INITIAL_TIMEOUT_MS,
+            retries: 0,
+            failed: false,
+            packet_size: 0,
+            packet_data: [0_u8; WORKING_BUFFER_SIZE],
+        };
+
+        slot.packet_size = serialize_postcard(
+            &BigBrotherPacket::ReliableUserPacket { sequence, packet },
+            &mut slot.packet_data,
+        )
+        .map_err(BigBrotherError::SerializationError)?;
+
+        self.reliable_sequence = self.reliable_sequence.wrapping_add(1);
+        self.reliable_slots[slot_index] = Some(slot);
+
+        if let Err(err) = self.transmit_reliable_slot(slot_index) {
+            self.reliable_slots[slot_index] = None;
+            return Err(err);
+        }
+
+        Ok(sequence)
+    }
+
+    pub fn is_reliable_in_flight(&self, sequence: SequenceType) -> bool {
+        self.reliable_slots
+            .iter()
+            .flatten()
+            .any(|slot| slot.sequence == sequence && !slot.failed)
+    }
+
+    /// Returns a ReliableDeliveryFailed error for a reliable packet that ran out of retries
+    /// and frees its slot. Failed packets hold onto their slot until they're checked here.
+    pub fn check_reliable_deliveries(&mut self) -> Result<(), BigBrotherError> {
+        for slot in &mut self.reliable_slots {
+            if let Some(failed_slot) = slot.filter(|slot| slot.failed) {
+                *slot = None;
+
+                return Err(BigBrotherError::ReliableDeliveryFailed(
+                    failed_slot.sequence,
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub(crate) fn poll_reliable_retransmits(&mut self, timestamp: u32) {
+        for slot_index in 0..MAX_RELIABLE_IN_FLIGHT {
+            if let Some(slot) = self.reliable_slots[slot_index].as_mut() {
+                if slot.failed || timestamp.wrapping_sub(slot.last_send_timestamp) < slot.timeout_ms
+                {
+                    continue;
+                }
+
+                if slot.retries >= RELIABLE_MAX_RETRIES {
+                    slot.failed = true;
+                    continue;
+                }
+
+                slot.retries += 1;
+                slot.timeout_ms = (slot.timeout_ms * 2).min(RELIABLE_MAX_TIMEOUT_MS);
+
+                // A failed send is treated the same as a lost packet
+                let _ = self.transmit_reliable_slot(slot_index);
+            }
+        }
+    }
+
+    pub(crate) fn handle_reliable_ack(&mut self, from_addr: A, sequence: SequenceType) {
+        for slot in &mut self.reliable_slots {
+            if let Some(in_flight) = slot {
+                if in_flight.destination == from_addr && in_flight.sequence == sequence {
+                    *slot = None;
+                }
+            }
+        }
+    }
+
+    pub(crate) fn queue_reliable_ack(&mut self, destination: A, sequence: SequenceType) {
+        // If the queue is full the ack is dropped and the sender will retransmit
+        if let Some(pending_ack) = self.pending_acks.iter_mut().find(|ack| ack.is_none()) {
+            *pending_ack = Some((destination, sequence));
+        }
+    }
+
+    /// Acks are deferred until the next recv or poll so that acking doesn't clobber the
+    /// working buffer of a packet that was just handed back to the user
+    pub(crate) fn flush_pending_acks(&mut self) {
+        for ack_index in 0..MAX_PENDING_ACKS {
+            if let Some((destination, sequence)) = self.pending_acks[ack_index].take() {
+                let _ = self.send_bb_packet(
+                    BigBrotherPacket::MetaPacket(BigBrotherMetapacket::Ack { sequence }),
+                    destination,
+                );
+            }
+        }
+    }
+
+    pub(crate) fn reliable_dedupe(
+        &mut self,
+        from_addr: A,
+        sequence: SequenceType,
+    ) -> Result<ReliableDedupe, BigBrotherError> {
+        let mapping = self.network_map.get_address_mapping(from_addr)?;
+
+        Ok(reliable_dedupe(sequence, mapping))
+    }
+
+    fn transmit_reliable_slot(&mut self, slot_index: usize) -> Result<(), BigBrotherError> {
+        let slot = match self.reliable_slots[slot_index].as_mut() {
+            Some(slot) => slot,
+            None => return Ok(()),
+        };
+
+        // Every transmission gets a fresh counter so the receiver's dedupe doesn't drop
+        // retransmissions, duplicates are caught by the reliable sequence window instead
+        let mapping = self.network_map.get_address_mapping(slot.destination)?;
+        let size = serialize_packet_bytes(
+            &slot.packet_data[..slot.packet_size],
+            self.host_addr,
+            slot.destination,
+            mapping.to_counter,
+            &mut self.working_buffer,
+        )?;
+        let sessions = AuthSessions {
+            from_session_id: self.session_id,
+            to_session_id: mapping.from_session_id,
+        };
+        let size = match &self.auth_key {
+            Some(key) => sign_packet(key, &sessions, &mut self.working_buffer, size)?,
+            None => size,
+        };
+        mapping.to_counter = mapping.to_counter.wrapping_add(1);
+        self.send_byte_counter += size;
+        slot.last_send_timestamp = self.last_poll_timestamp;
+
+        let destination_endpoint = BigBrotherEndpoint {
+            ip: mapping.ip,
+            port: mapping.port,
+        };
+
+        if let Some(interface) = self.interfaces[mapping.interface_index as usize].as_mut() {
+            interface.send_udp(destination_endpoint, &mut self.working_buffer[..size])
+        } else {
+            Err(BigBrotherError::SendUnnaddressable)
+        }
+    }
+}
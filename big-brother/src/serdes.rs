@@ -1,12 +1,34 @@
+use hmac::{Hmac, Mac};
 use serde::{Deserialize, Serialize};
+use sha2::Sha256;
 
-use crate::{big_brother::BigBrotherError, dedupe};
+use crate::{
+    big_brother::{BigBrotherError, BigBrotherPacket},
+    dedupe,
+    fragmentation::FragmentHeader,
+};
 
 // Serialization format:
 // u8: metadata size
 // u8: packet size
 // [u8; metadata size]: metadata
 // [u8; packet size]: packet
+// [u8; AUTH_TAG_SIZE]: (optional) truncated HMAC-SHA256 of the AuthSessions and everything
+//                     before it
+
+pub const AUTH_KEY_SIZE: usize = 32;
+pub const AUTH_TAG_SIZE: usize = 16;
+
+pub type AuthKey = [u8; AUTH_KEY_SIZE];
+
+/// Sessions a signed packet is bound to, so it can't be replayed into a later session of
+/// either end. They aren't sent, each end fills them in from what it knows of the other.
+/// Broadcasts have no single destination, so their `to_session_id` is always 0
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuthSessions {
+    pub from_session_id: u32,
+    pub to_session_id: u32,
+}
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PacketMetadata<T> {
@@ -61,6 +83,162 @@ where
     Ok(buf_ptr)
 }
 
+/// Same as serialize_packet, but for a packet that has already been serialized with
+/// serialize_postcard. Used to retransmit stored packets with a fresh counter
+pub fn serialize_packet_bytes<A>(
+    packet_data: &[u8],
+    host_addr: A,
+    destination: A,
+    counter: dedupe::CounterType,
+    buffer: &mut [u8],
+) -> Result<usize, BigBrotherError>
+where
+    A: Serialize,
+{
+    let metadata = PacketMetadata {
+        to_addr: destination,
+        from_addr: host_addr,
+        counter,
+    };
+
+    let mut buf_ptr = 2;
+
+    let metadata_size = serialize_postcard(&metadata, &mut buffer[buf_ptr..])
+        .map_err(BigBrotherError::SerializationError)?;
+    buf_ptr += metadata_size;
+
+    let packet_size = packet_data.len();
+    if buf_ptr + packet_size > buffer.len() {
+        return Err(BigBrotherError::SerializationError(
+            SerdesError::PacketTooLong,
+        ));
+    }
+
+    buffer[buf_ptr..(buf_ptr + packet_size)].copy_from_slice(packet_data);
+    buf_ptr += packet_size;
+
+    buffer[0] = u8::try_from(metadata_size)
+        .map_err(|_| BigBrotherError::SerializationError(SerdesError::PacketTooLong))?;
+    buffer[1] = u8::try_from(packet_size)
+        .map_err(|_| BigBrotherError::SerializationError(SerdesError::PacketTooLong))?;
+
+    Ok(buf_ptr)
+}
+
+/// Serializes one fragment of a larger packet. The packet section is the fragment header
+/// followed directly by the raw fragment data
+pub fn serialize_fragment<A>(
+    header: &FragmentHeader,
+    data: &[u8],
+    host_addr: A,
+    destination: A,
+    counter: dedupe::CounterType,
+    buffer: &mut [u8],
+) -> Result<usize, BigBrotherError>
+where
+    A: Serialize,
+{
+    let metadata = PacketMetadata {
+        to_addr: destination,
+        from_addr: host_addr,
+        counter,
+    };
+
+    let mut buf_ptr = 2;
+
+    let metadata_size = serialize_postcard(&metadata, &mut buffer[buf_ptr..])
+        .map_err(BigBrotherError::SerializationError)?;
+    buf_ptr += metadata_size;
+
+    let header_size = serialize_postcard(
+        &BigBrotherPacket::<()>::Fragment(*header),
+        &mut buffer[buf_ptr..],
+    )
+    .map_err(BigBrotherError::SerializationError)?;
+    buf_ptr += header_size;
+
+    if buf_ptr + data.len() > buffer.len() {
+        return Err(BigBrotherError::SerializationError(
+            SerdesError::PacketTooLong,
+        ));
+    }
+
+    buffer[buf_ptr..(buf_ptr + data.len())].copy_from_slice(data);
+    buf_ptr += data.len();
+
+    buffer[0] = u8::try_from(metadata_size)
+        .map_err(|_| BigBrotherError::SerializationError(SerdesError::PacketTooLong))?;
+    buffer[1] = u8::try_from(header_size + data.len())
+        .map_err(|_| BigBrotherError::SerializationError(SerdesError::PacketTooLong))?;
+
+    Ok(buf_ptr)
+}
+
+pub fn deserialize_fragment(buffer: &[u8]) -> Result<(FragmentHeader, &[u8]), BigBrotherError> {
+    let metadata_size = buffer[0] as usize;
+    let packet_size = buffer[1] as usize;
+
+    let buf_ptr = 2 + metadata_size;
+
+    match postcard::take_from_bytes(&buffer[buf_ptr..(buf_ptr + packet_size)]) {
+        Ok((BigBrotherPacket::<()>::Fragment(header), data)) => Ok((header, data)),
+        Ok(_) => Err(BigBrotherError::SerializationError(
+            SerdesError::BadEncoding,
+        )),
+        Err(err) => Err(BigBrotherError::SerializationError(
+            postcard_serialization_err_to_hal_err(err),
+        )),
+    }
+}
+
+/// Appends an authentication tag to a serialized packet of `size` bytes, returns the new size
+pub fn sign_packet(
+    key: &AuthKey,
+    sessions: &AuthSessions,
+    buffer: &mut [u8],
+    size: usize,
+) -> Result<usize, BigBrotherError> {
+    if size + AUTH_TAG_SIZE > buffer.len() {
+        return Err(BigBrotherError::SerializationError(
+            SerdesError::PacketTooLong,
+        ));
+    }
+
+    let mut mac = auth_mac(key, sessions);
+    mac.update(&buffer[..size]);
+    let tag = mac.finalize().into_bytes();
+
+    buffer[size..(size + AUTH_TAG_SIZE)].copy_from_slice(&tag[..AUTH_TAG_SIZE]);
+
+    Ok(size + AUTH_TAG_SIZE)
+}
+
+/// Returns true if the received packet of `size` bytes carries a valid authentication tag.
+/// Unsigned packets are identified by having no bytes after the packet data
+pub fn verify_packet(key: &AuthKey, sessions: &AuthSessions, buffer: &[u8], size: usize) -> bool {
+    let unsigned_size = 2 + buffer[0] as usize + buffer[1] as usize;
+
+    if size != unsigned_size + AUTH_TAG_SIZE || size > buffer.len() {
+        return false;
+    }
+
+    let mut mac = auth_mac(key, sessions);
+    mac.update(&buffer[..unsigned_size]);
+
+    // Constant time comparison of the truncated tag
+    mac.verify_truncated_left(&buffer[unsigned_size..size])
+        .is_ok()
+}
+
+fn auth_mac(key: &AuthKey, sessions: &AuthSessions) -> Hmac<Sha256> {
+    // HMAC accepts keys of any length, so this can't fail
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).unwrap();
+    mac.update(&sessions.from_session_id.to_le_bytes());
+    mac.update(&sessions.to_session_id.to_le_bytes());
+
+    mac
+}
+
 pub fn deserialize_metadata<'a, A>(buffer: &'a [u8]) -> Result<PacketMetadata<A>, BigBrotherError>
 where
     A: Deserialize<'a>,
@@ -137,6 +315,7 @@ pub mod tests {
 
     use crate::{
         big_brother::WORKING_BUFFER_SIZE,
+        fragmentation::FRAGMENT_DATA_SIZE,
         network_map::tests::{TestNetworkAddress, NETWORK_ADDRESS_TEST_DEFAULTS},
     };
     use serde::{Deserialize, Serialize};
@@ -244,4 +423,130 @@ pub mod tests {
             }
         }
     }
+
+    #[test]
+    fn test_fragment_reserialization() {
+        let host_addr = TestNetworkAddress::EngineController(250);
+        let mut buffer = [0_u8; WORKING_BUFFER_SIZE];
+        let mut data = [0_u8; FRAGMENT_DATA_SIZE];
+
+        for (i, byte) in data.iter_mut().enumerate() {
+            *byte = (i * 7) as u8;
+        }
+
+        for data_size in [0, 1, FRAGMENT_DATA_SIZE / 2, FRAGMENT_DATA_SIZE] {
+            let header = FragmentHeader {
+                fragment_id: 0xABCD,
+                index: 3,
+                count: 4,
+                total_size: 1000,
+            };
+
+            let size = serialize_fragment(
+                &header,
+                &data[..data_size],
+                host_addr,
+                TestNetworkAddress::Broadcast,
+                42,
+                &mut buffer,
+            )
+            .unwrap();
+            let metadata: PacketMetadata<TestNetworkAddress> =
+                deserialize_metadata(&buffer).unwrap();
+            let (recv_header, recv_data) = deserialize_fragment(&buffer[..size]).unwrap();
+
+            // Leave room for the authentication tag
+            assert!(size + AUTH_TAG_SIZE <= WORKING_BUFFER_SIZE);
+            assert_eq!(metadata.from_addr, host_addr);
+            assert_eq!(metadata.counter, 42);
+            assert_eq!(recv_header, header);
+            assert_eq!(recv_data, &data[..data_size]);
+        }
+    }
+
+    #[test]
+    fn test_packet_authentication() {
+        let key: AuthKey = [0x5A; AUTH_KEY_SIZE];
+        let mut wrong_key = key;
+        wrong_key[0] = 0x00;
+
+        let sessions = AuthSessions {
+            from_session_id: 0x12345678,
+            to_session_id: 0x9ABCDEF0,
+        };
+        let other_sessions = [
+            AuthSessions {
+                from_session_id: 0x12345679,
+                ..sessions
+            },
+            AuthSessions {
+                to_session_id: 0,
+                ..sessions
+            },
+        ];
+
+        let host_addr = TestNetworkAddress::EngineController(250);
+        let mut buffer = [0_u8; WORKING_BUFFER_SIZE];
+
+        for (counter, packet) in TEST_PACKET_DEFAULTS.iter().enumerate() {
+            let unsigned_size = serialize_packet(
+                packet,
+                host_addr,
+                TestNetworkAddress::FlightController,
+                counter as dedupe::CounterType,
+                &mut buffer,
+            )
+            .unwrap();
+            assert!(!verify_packet(&key, &sessions, &buffer, unsigned_size));
+
+            let size = sign_packet(&key, &sessions, &mut buffer, unsigned_size).unwrap();
+            assert_eq!(size, unsigned_size + AUTH_TAG_SIZE);
+            assert!(verify_packet(&key, &sessions, &buffer, size));
+            assert!(!verify_packet(&wrong_key, &sessions, &buffer, size));
+
+            // Valid in one session doesn't mean valid in any other
+            for other_sessions in &other_sessions {
+                assert!(!verify_packet(&key, other_sessions, &buffer, size));
+            }
+
+            // Signing shouldn't get in the way of normal deserialization
+            let recv_packet: TestPacket = deserialize_packet(&buffer).unwrap();
+            assert_eq!(recv_packet, *packet);
+
+            // Tampering with any byte should invalidate the tag
+            for i in 0..size {
+                buffer[i] ^= 0x01;
+                assert!(!verify_packet(&key, &sessions, &buffer, size));
+                buffer[i] ^= 0x01;
+            }
+        }
+    }
+
+    #[test]
+    fn test_packet_bytes_reserialization() {
+        let host_addr = TestNetworkAddress::EngineController(250);
+        let mut packet_buffer = [0_u8; WORKING_BUFFER_SIZE];
+        let mut buffer = [0_u8; WORKING_BUFFER_SIZE];
+
+        for (counter, packet) in TEST_PACKET_DEFAULTS.iter().enumerate() {
+            let packet_size = serialize_postcard(packet, &mut packet_buffer).unwrap();
+            let size = serialize_packet_bytes(
+                &packet_buffer[..packet_size],
+                host_addr,
+                TestNetworkAddress::FlightController,
+                counter as dedupe::CounterType,
+                &mut buffer,
+            )
+            .unwrap();
+            let metadata: PacketMetadata<TestNetworkAddress> =
+                deserialize_metadata(&buffer).unwrap();
+            let recv_packet: TestPacket = deserialize_packet(&buffer).unwrap();
+
+            assert!(size < WORKING_BUFFER_SIZE);
+            assert_eq!(recv_packet, *packet);
+            assert_eq!(metadata.from_addr, host_addr);
+            assert_eq!(metadata.to_addr, TestNetworkAddress::FlightController);
+            assert_eq!(metadata.counter, counter as dedupe::CounterType);
+        }
+    }
 }
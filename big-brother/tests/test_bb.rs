@@ -1,12 +1,19 @@
 use std::sync::{Arc, Mutex};
 
 use big_brother::{
-    big_brother::{BigBrotherError, Broadcastable, MAX_INTERFACE_COUNT},
+    big_brother::{
+        BigBrotherEndpoint, BigBrotherError, BigBrotherMetapacket, BigBrotherPacket, Broadcastable,
+        BITRATE_MEASUREMENT_DURATION_MS, MAX_INTERFACE_COUNT, WORKING_BUFFER_SIZE,
+    },
+    fragmentation::{FRAGMENT_DATA_SIZE, MAX_FRAGMENTED_PACKET_SIZE, REASSEMBLY_TIMEOUT_MS},
     interface::{
         mock_interface::MockInterface,
         mock_topology::{MockPhysicalInterface, MockPhysicalNet},
         BigBrotherInterface,
     },
+    link_health::{LinkEvent, DEFAULT_PEER_TIMEOUT_MS, PING_INTERVAL_MS},
+    reliable::{SequenceType, MAX_RELIABLE_IN_FLIGHT, RELIABLE_INITIAL_TIMEOUT_MS},
+    serdes::{deserialize_packet, serialize_packet, AuthKey, SerdesError, AUTH_KEY_SIZE},
     BigBrother,
 };
 use serde::{Deserialize, Serialize};
@@ -15,6 +22,7 @@ use serde::{Deserialize, Serialize};
 pub enum TestPacket {
     Heartbeat,
     SomeData { a: u32, b: u32, c: bool },
+    LargeData(Vec<u8>),
 }
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
@@ -384,6 +392,408 @@ fn simple_local_forwarding() {
     assert_empty_recv_slice(&mut [&mut bb_sep, &mut bb_host, &mut bb_chained]);
 }
 
+#[test]
+fn reliable_delivery() {
+    let network = fixture_network();
+    let mut iface0 = fixture_iface_singleton(network.clone());
+    let mut iface1 = fixture_iface_singleton(network.clone());
+
+    let mut bb0 = fixture_bb([Some(&mut iface0), None], TestNetworkAddress::A);
+    let mut bb1 = fixture_bb([Some(&mut iface1), None], TestNetworkAddress::B);
+
+    assert_empty_recv_slice(&mut [&mut bb0, &mut bb1]);
+
+    let packet = TestPacket::SomeData {
+        a: 0,
+        b: 1,
+        c: true,
+    };
+
+    let sequence = bb0
+        .send_packet_reliable(&packet, TestNetworkAddress::B)
+        .unwrap();
+    assert!(bb0.is_reliable_in_flight(sequence));
+
+    let (recv_packet, remote) = bb1.recv_packet().unwrap().unwrap();
+    assert_eq!(recv_packet, packet);
+    assert_eq!(remote, TestNetworkAddress::A);
+
+    // Sends the deferred ack from bb1, then processes it on bb0
+    assert_empty_recv_slice(&mut [&mut bb1, &mut bb0]);
+    assert!(!bb0.is_reliable_in_flight(sequence));
+    assert!(bb0.check_reliable_deliveries().is_ok());
+}
+
+#[test]
+fn reliable_retransmit_lost_packet() {
+    let network = fixture_network();
+    let mut iface0 = fixture_iface_singleton(network.clone());
+    let mut iface1 = fixture_iface_singleton(network.clone());
+
+    let mut bb0 = fixture_bb([Some(&mut iface0), None], TestNetworkAddress::A);
+    let mut bb1 = fixture_bb([Some(&mut iface1), None], TestNetworkAddress::B);
+
+    assert_empty_recv_slice(&mut [&mut bb0, &mut bb1]);
+
+    let packet = TestPacket::SomeData {
+        a: 2,
+        b: 3,
+        c: false,
+    };
+
+    network.lock().unwrap().drop_next_payloads(1);
+    let sequence = bb0
+        .send_packet_reliable(&packet, TestNetworkAddress::B)
+        .unwrap();
+    assert_empty_recv_slice(&mut [&mut bb1, &mut bb0]);
+    assert!(bb0.is_reliable_in_flight(sequence));
+
+    // Not timed out yet
+    bb0.poll_1ms(RELIABLE_INITIAL_TIMEOUT_MS - 1);
+    assert_empty_recv_slice(&mut [&mut bb1, &mut bb0]);
+
+    bb0.poll_1ms(RELIABLE_INITIAL_TIMEOUT_MS);
+    let (recv_packet, remote) = bb1.recv_packet().unwrap().unwrap();
+    assert_eq!(recv_packet, packet);
+    assert_eq!(remote, TestNetworkAddress::A);
+
+    assert_empty_recv_slice(&mut [&mut bb1, &mut bb0]);
+    assert!(!bb0.is_reliable_in_flight(sequence));
+}
+
+#[test]
+fn reliable_retransmit_lost_ack() {
+    let network = fixture_network();
+    let mut iface0 = fixture_iface_singleton(network.clone());
+    let mut iface1 = fixture_iface_singleton(network.clone());
+
+    let mut bb0 = fixture_bb([Some(&mut iface0), None], TestNetworkAddress::A);
+    let mut bb1 = fixture_bb([Some(&mut iface1), None], TestNetworkAddress::B);
+
+    assert_empty_recv_slice(&mut [&mut bb0, &mut bb1]);
+
+    let packet = TestPacket::SomeData {
+        a: 4,
+        b: 5,
+        c: true,
+    };
+
+    let sequence = bb0
+        .send_packet_reliable(&packet, TestNetworkAddress::B)
+        .unwrap();
+    let (recv_packet, _) = bb1.recv_packet().unwrap().unwrap();
+    assert_eq!(recv_packet, packet);
+
+    // Drop the ack
+    network.lock().unwrap().drop_next_payloads(1);
+    assert_empty_recv_slice(&mut [&mut bb1, &mut bb0]);
+    assert!(bb0.is_reliable_in_flight(sequence));
+
+    // The retransmission must be acked again but not delivered twice
+    bb0.poll_1ms(RELIABLE_INITIAL_TIMEOUT_MS);
+    assert!(bb1.recv_packet().unwrap().is_none());
+    assert_empty_recv_slice(&mut [&mut bb1, &mut bb0]);
+    assert!(!bb0.is_reliable_in_flight(sequence));
+    assert!(bb0.check_reliable_deliveries().is_ok());
+}
+
+#[test]
+fn reliable_delivery_failure() {
+    let network = fixture_network();
+    let mut iface0 = fixture_iface_singleton(network.clone());
+    let mut iface1 = fixture_iface_singleton(network.clone());
+
+    let mut bb0 = fixture_bb([Some(&mut iface0), None], TestNetworkAddress::A);
+    let mut bb1 = fixture_bb([Some(&mut iface1), None], TestNetworkAddress::B);
+
+    assert_empty_recv_slice(&mut [&mut bb0, &mut bb1]);
+
+    let packet = TestPacket::SomeData {
+        a: 6,
+        b: 7,
+        c: false,
+    };
+
+    let sequence = bb0
+        .send_packet_reliable(&packet, TestNetworkAddress::B)
+        .unwrap();
+
+    // bb1 never processes anything, so no acks are sent
+    for timestamp in 0..2000 {
+        bb0.poll_1ms(timestamp);
+        assert!(bb0.recv_packet().unwrap().is_none());
+    }
+
+    assert!(!bb0.is_reliable_in_flight(sequence));
+    assert!(matches!(
+        bb0.check_reliable_deliveries(),
+        Err(BigBrotherError::ReliableDeliveryFailed(failed)) if failed == sequence
+    ));
+    assert!(bb0.check_reliable_deliveries().is_ok());
+
+    // Every retransmission arrived, but it should only be delivered once
+    let (recv_packet, _) = bb1.recv_packet().unwrap().unwrap();
+    assert_eq!(recv_packet, packet);
+    assert_empty_recv_slice(&mut [&mut bb1]);
+}
+
+#[test]
+fn reliable_window_full() {
+    let network = fixture_network();
+    let mut iface0 = fixture_iface_singleton(network.clone());
+    let mut iface1 = fixture_iface_singleton(network.clone());
+
+    let mut bb0 = fixture_bb([Some(&mut iface0), None], TestNetworkAddress::A);
+    let mut bb1 = fixture_bb([Some(&mut iface1), None], TestNetworkAddress::B);
+
+    assert_empty_recv_slice(&mut [&mut bb0, &mut bb1]);
+
+    let packet = TestPacket::Heartbeat;
+
+    assert!(matches!(
+        bb0.send_packet_reliable(&packet, TestNetworkAddress::Broadcast),
+        Err(BigBrotherError::ReliableBroadcast)
+    ));
+
+    for _ in 0..MAX_RELIABLE_IN_FLIGHT {
+        bb0.send_packet_reliable(&packet, TestNetworkAddress::B)
+            .unwrap();
+    }
+
+    assert!(matches!(
+        bb0.send_packet_reliable(&packet, TestNetworkAddress::B),
+        Err(BigBrotherError::ReliableWindowFull)
+    ));
+
+    // Acking everything frees up the window
+    for _ in 0..MAX_RELIABLE_IN_FLIGHT {
+        assert!(bb1.recv_packet().unwrap().is_some());
+    }
+    assert_empty_recv_slice(&mut [&mut bb1, &mut bb0]);
+
+    bb0.send_packet_reliable(&packet, TestNetworkAddress::B)
+        .unwrap();
+}
+
+#[test]
+fn reliable_high_sequence_after_receiver_boot() {
+    let network = fixture_network();
+    let mut iface1 = fixture_iface_singleton(network.clone());
+    let mut iface_sender = fixture_iface_singleton(network.clone());
+    let bb1_endpoint = BigBrotherEndpoint {
+        ip: iface1.host_ip,
+        port: iface1.host_port,
+    };
+
+    let mut bb1 = fixture_bb([Some(&mut iface1), None], TestNetworkAddress::B);
+
+    let packet = TestPacket::SomeData {
+        a: 8,
+        b: 9,
+        c: true,
+    };
+
+    // A sender that's been up long enough to be more than half way round the sequence space,
+    // talking to a receiver that has just booted
+    let sequence = SequenceType::MAX / 2 + 100;
+    let mut send_reliable = |counter, sequence| {
+        send_forged_packet(
+            &mut iface_sender,
+            bb1_endpoint.clone(),
+            TestNetworkAddress::A,
+            TestNetworkAddress::B,
+            counter,
+            BigBrotherPacket::ReliableUserPacket {
+                sequence,
+                packet: &packet,
+            },
+        )
+        .unwrap();
+    };
+
+    send_reliable(0, sequence);
+    let (recv_packet, remote) = bb1.recv_packet().unwrap().unwrap();
+    assert_eq!(recv_packet, packet);
+    assert_eq!(remote, TestNetworkAddress::A);
+
+    // The retransmission is acked again, but not delivered twice
+    send_reliable(1, sequence);
+    assert_empty_recv_slice(&mut [&mut bb1]);
+
+    // Too far behind the window to know if it was delivered, so it isn't acked
+    send_reliable(2, sequence - 40);
+    assert_empty_recv_slice(&mut [&mut bb1]);
+
+    assert_eq!(take_acks(&mut iface_sender), vec![sequence, sequence]);
+}
+
+#[test]
+fn authenticated_packets() {
+    let key: AuthKey = [0xA5; AUTH_KEY_SIZE];
+    let network = fixture_network();
+    let mut iface0 = fixture_iface_singleton(network.clone());
+    let mut iface1 = fixture_iface_singleton(network.clone());
+
+    let mut bb0 = fixture_bb([Some(&mut iface0), None], TestNetworkAddress::A);
+    let mut bb1 = fixture_bb([Some(&mut iface1), None], TestNetworkAddress::B);
+
+    assert_empty_recv_slice(&mut [&mut bb0, &mut bb1]);
+
+    bb1.set_authentication_key(Some(key));
+    bb1.require_authentication(TestNetworkAddress::A).unwrap();
+    assert!(bb1.requires_authentication(TestNetworkAddress::A));
+    assert!(!bb1.requires_authentication(TestNetworkAddress::C));
+
+    let packet = TestPacket::SomeData {
+        a: 0,
+        b: 1,
+        c: true,
+    };
+
+    // Unsigned packets from A are dropped
+    bb0.send_packet(&packet, TestNetworkAddress::B).unwrap();
+    assert_empty_recv_slice(&mut [&mut bb1]);
+    assert_eq!(bb1.get_rejected_packets(), 1);
+
+    // Packets signed with the wrong key are dropped
+    bb0.set_authentication_key(Some([0x00; AUTH_KEY_SIZE]));
+    bb0.send_packet(&packet, TestNetworkAddress::B).unwrap();
+    assert_empty_recv_slice(&mut [&mut bb1]);
+    assert_eq!(bb1.get_rejected_packets(), 2);
+
+    bb0.set_authentication_key(Some(key));
+    bb0.send_packet(&packet, TestNetworkAddress::B).unwrap();
+    let (recv_packet, remote) = bb1.recv_packet().unwrap().unwrap();
+    assert_eq!(recv_packet, packet);
+    assert_eq!(remote, TestNetworkAddress::A);
+    assert_eq!(bb1.get_rejected_packets(), 2);
+
+    // Signed heartbeats and broadcasts are accepted too
+    bb0.poll_1ms(101);
+    bb0.send_packet(&packet, TestNetworkAddress::Broadcast)
+        .unwrap();
+    let (recv_packet, remote) = bb1.recv_packet().unwrap().unwrap();
+    assert_eq!(recv_packet, packet);
+    assert_eq!(remote, TestNetworkAddress::A);
+    assert_empty_recv_slice(&mut [&mut bb1]);
+    assert_eq!(bb1.get_rejected_packets(), 2);
+}
+
+#[test]
+fn authentication_rejects_forged_counters() {
+    let key: AuthKey = [0x3C; AUTH_KEY_SIZE];
+    let network = fixture_network();
+    let mut iface0 = fixture_iface_singleton(network.clone());
+    let mut iface1 = fixture_iface_singleton(network.clone());
+    let mut iface_attacker = fixture_iface_singleton(network.clone());
+    let bb1_endpoint = BigBrotherEndpoint {
+        ip: iface1.host_ip,
+        port: iface1.host_port,
+    };
+
+    let mut bb0 = fixture_bb([Some(&mut iface0), None], TestNetworkAddress::A);
+    let mut bb1 = fixture_bb([Some(&mut iface1), None], TestNetworkAddress::B);
+
+    bb0.set_authentication_key(Some(key));
+    bb1.set_authentication_key(Some(key));
+    bb1.require_authentication(TestNetworkAddress::Broadcast)
+        .unwrap();
+
+    // The initial heartbeats were unsigned, so get fresh signed ones out
+    bb0.poll_1ms(101);
+    bb1.poll_1ms(101);
+    assert_empty_recv_slice(&mut [&mut bb0, &mut bb1]);
+
+    let packet = TestPacket::SomeData {
+        a: 2,
+        b: 3,
+        c: false,
+    };
+
+    // Impersonate A with a huge counter, which would make B drop A's real packets as
+    // duplicates if it got through dedupe
+    let rejected_packets = bb1.get_rejected_packets();
+    send_forged_packet(
+        &mut iface_attacker,
+        bb1_endpoint,
+        TestNetworkAddress::A,
+        TestNetworkAddress::B,
+        u32::MAX / 4,
+        BigBrotherPacket::UserPacket(&packet),
+    )
+    .unwrap();
+    assert_empty_recv_slice(&mut [&mut bb1]);
+    assert_eq!(bb1.get_rejected_packets(), rejected_packets + 1);
+
+    bb0.send_packet(&packet, TestNetworkAddress::B).unwrap();
+    let (recv_packet, remote) = bb1.recv_packet().unwrap().unwrap();
+    assert_eq!(recv_packet, packet);
+    assert_eq!(remote, TestNetworkAddress::A);
+}
+
+#[test]
+fn authentication_rejects_old_session_replay() {
+    let key: AuthKey = [0x69; AUTH_KEY_SIZE];
+    let network = fixture_network();
+    let mut iface0 = fixture_iface_singleton(network.clone());
+    let mut iface1 = fixture_iface_singleton(network.clone());
+    let mut iface_attacker = fixture_iface_singleton(network.clone());
+    let bb1_endpoint = BigBrotherEndpoint {
+        ip: iface1.host_ip,
+        port: iface1.host_port,
+    };
+
+    let mut bb0 = fixture_bb([Some(&mut iface0), None], TestNetworkAddress::A);
+    let mut bb1 = fixture_bb([Some(&mut iface1), None], TestNetworkAddress::B);
+
+    bb0.set_authentication_key(Some(key));
+    bb1.set_authentication_key(Some(key));
+    bb1.require_authentication(TestNetworkAddress::A).unwrap();
+
+    // Checked apart, as sharing a slice would tie the first bb0 to bb1's lifetime
+    bb0.poll_1ms(101);
+    assert_empty_recv_slice(&mut [&mut bb0]);
+    assert_empty_recv_slice(&mut [&mut bb1]);
+
+    let packet = TestPacket::SomeData {
+        a: 10,
+        b: 11,
+        c: true,
+    };
+
+    // Record a signed heartbeat and packet from A's first session
+    network.lock().unwrap().enable_payload_logging();
+    bb0.poll_1ms(202);
+    bb0.send_packet(&packet, TestNetworkAddress::B).unwrap();
+    let recorded = network.lock().unwrap().take_payload_log();
+
+    assert_eq!(bb1.recv_packet().unwrap().unwrap().0, packet);
+    assert_empty_recv_slice(&mut [&mut bb1]);
+
+    // A reboots into a new session and B follows it
+    drop(bb0);
+    let mut bb0 = fixture_bb([Some(&mut iface0), None], TestNetworkAddress::A);
+    bb0.set_authentication_key(Some(key));
+    bb0.poll_1ms(101);
+    bb1.poll_1ms(303);
+    assert_empty_recv_slice(&mut [&mut bb0, &mut bb1]);
+
+    // Replaying the old session can't roll B's counters back to let the packet through
+    let rejected_packets = bb1.get_rejected_packets();
+    for mut payload in recorded {
+        iface_attacker
+            .send_udp(bb1_endpoint.clone(), &mut payload.data)
+            .unwrap();
+    }
+    assert_empty_recv_slice(&mut [&mut bb1]);
+    assert_eq!(bb1.get_rejected_packets(), rejected_packets + 1);
+
+    bb0.send_packet(&packet, TestNetworkAddress::B).unwrap();
+    let (recv_packet, remote) = bb1.recv_packet().unwrap().unwrap();
+    assert_eq!(recv_packet, packet);
+    assert_eq!(remote, TestNetworkAddress::A);
+}
+
 fn assert_empty_recv<'a, const N: usize>(
     bbs: &mut [BigBrother<'a, N, TestPacket, TestNetworkAddress>],
 ) {
@@ -430,6 +840,284 @@ fn fixture_network() -> Arc<Mutex<MockPhysicalNet>> {
     )))
 }
 
+#[test]
+fn fragmented_packets() {
+    let network = fixture_network();
+    let mut iface0 = fixture_iface_singleton(network.clone());
+    let mut iface1 = fixture_iface_singleton(network.clone());
+
+    let mut bb0 = fixture_bb([Some(&mut iface0), None], TestNetworkAddress::A);
+    let mut bb1 = fixture_bb([Some(&mut iface1), None], TestNetworkAddress::B);
+
+    assert_empty_recv_slice(&mut [&mut bb0, &mut bb1]);
+
+    for size in [WORKING_BUFFER_SIZE, 600, MAX_FRAGMENTED_PACKET_SIZE - 8] {
+        let packet = fixture_large_packet(size);
+
+        bb0.send_packet(&packet, TestNetworkAddress::B).unwrap();
+        let (recv_packet, remote) = bb1.recv_packet().unwrap().unwrap();
+        assert_eq!(recv_packet, packet);
+        assert_eq!(remote, TestNetworkAddress::A);
+        assert_empty_recv_slice(&mut [&mut bb0, &mut bb1]);
+
+        // Broadcasts are fragmented the same way, and loop back to the sender
+        bb1.send_packet(&packet, TestNetworkAddress::Broadcast)
+            .unwrap();
+        for bb in [&mut bb0, &mut bb1] {
+            let (recv_packet, remote) = bb.recv_packet().unwrap().unwrap();
+            assert_eq!(recv_packet, packet);
+            assert_eq!(remote, TestNetworkAddress::B);
+        }
+        assert_empty_recv_slice(&mut [&mut bb0, &mut bb1]);
+    }
+
+    assert!(matches!(
+        bb0.send_packet(
+            &fixture_large_packet(MAX_FRAGMENTED_PACKET_SIZE),
+            TestNetworkAddress::B
+        ),
+        Err(BigBrotherError::SerializationError(
+            SerdesError::PacketTooLong
+        ))
+    ));
+}
+
+#[test]
+fn fragmented_packets_reordered() {
+    let network = fixture_network();
+    let mut iface0 = fixture_iface_singleton(network.clone());
+    let mut iface1 = fixture_iface_singleton(network.clone());
+
+    let mut bb0 = fixture_bb([Some(&mut iface0), None], TestNetworkAddress::A);
+    let mut bb1 = fixture_bb([Some(&mut iface1), None], TestNetworkAddress::B);
+
+    assert_empty_recv_slice(&mut [&mut bb0, &mut bb1]);
+
+    let packet = fixture_large_packet(600);
+    let fragment_count = 600_usize.div_ceil(FRAGMENT_DATA_SIZE);
+
+    network
+        .lock()
+        .unwrap()
+        .reorder_next_payloads(fragment_count);
+    bb0.send_packet(&packet, TestNetworkAddress::B).unwrap();
+
+    let (recv_packet, remote) = bb1.recv_packet().unwrap().unwrap();
+    assert_eq!(recv_packet, packet);
+    assert_eq!(remote, TestNetworkAddress::A);
+    assert_empty_recv_slice(&mut [&mut bb0, &mut bb1]);
+
+    // Regular packets still make it through afterwards
+    let small_packet = TestPacket::Heartbeat;
+    bb0.send_packet(&small_packet, TestNetworkAddress::B)
+        .unwrap();
+    let (recv_packet, _) = bb1.recv_packet().unwrap().unwrap();
+    assert_eq!(recv_packet, small_packet);
+}
+
+#[test]
+fn fragmented_packets_lost() {
+    let network = fixture_network();
+    let mut iface0 = fixture_iface_singleton(network.clone());
+    let mut iface1 = fixture_iface_singleton(network.clone());
+
+    let mut bb0 = fixture_bb([Some(&mut iface0), None], TestNetworkAddress::A);
+    let mut bb1 = fixture_bb([Some(&mut iface1), None], TestNetworkAddress::B);
+
+    assert_empty_recv_slice(&mut [&mut bb0, &mut bb1]);
+
+    let packet = fixture_large_packet(600);
+
+    // Losing more packets than there are reassembly buffers shouldn't block new packets
+    for _ in 0..4 {
+        network.lock().unwrap().drop_next_payloads(1);
+        bb0.send_packet(&packet, TestNetworkAddress::B).unwrap();
+        assert_empty_recv_slice(&mut [&mut bb0, &mut bb1]);
+    }
+
+    bb0.send_packet(&packet, TestNetworkAddress::B).unwrap();
+    let (recv_packet, _) = bb1.recv_packet().unwrap().unwrap();
+    assert_eq!(recv_packet, packet);
+
+    // Partially received packets are thrown out after a timeout
+    network.lock().unwrap().drop_next_payloads(1);
+    bb0.send_packet(&packet, TestNetworkAddress::B).unwrap();
+    assert_empty_recv_slice(&mut [&mut bb0, &mut bb1]);
+    bb1.poll_1ms(REASSEMBLY_TIMEOUT_MS + 1);
+
+    bb0.send_packet(&packet, TestNetworkAddress::B).unwrap();
+    let (recv_packet, _) = bb1.recv_packet().unwrap().unwrap();
+    assert_eq!(recv_packet, packet);
+    assert_empty_recv_slice(&mut [&mut bb0, &mut bb1]);
+}
+
+#[test]
+fn link_health_events() {
+    let network = fixture_network();
+    let mut iface0 = fixture_iface_singleton(network.clone());
+    let mut iface1 = fixture_iface_singleton(network.clone());
+
+    let mut bb0 = fixture_bb([Some(&mut iface0), None], TestNetworkAddress::A);
+    let bb1 = fixture_bb([Some(&mut iface1), None], TestNetworkAddress::B);
+
+    assert!(bb0.recv_packet().unwrap().is_none());
+    assert_eq!(
+        bb0.take_link_event(),
+        Some(LinkEvent::PeerAppeared(TestNetworkAddress::B))
+    );
+    assert_eq!(bb0.take_link_event(), None);
+    assert!(bb0.get_link_health(TestNetworkAddress::B).unwrap().alive);
+
+    // bb1 goes silent
+    bb0.poll_1ms(DEFAULT_PEER_TIMEOUT_MS + 1);
+    assert_eq!(
+        bb0.take_link_event(),
+        Some(LinkEvent::PeerLost(TestNetworkAddress::B))
+    );
+    assert!(!bb0.get_link_health(TestNetworkAddress::B).unwrap().alive);
+
+    // And comes back with a new session. The first heartbeat only resets the counters, so
+    // it's seen again from the next packet
+    drop(bb1);
+    let mut bb1 = fixture_bb([Some(&mut iface1), None], TestNetworkAddress::B);
+
+    assert!(bb0.recv_packet().unwrap().is_none());
+    assert_eq!(
+        bb0.take_link_event(),
+        Some(LinkEvent::PeerRebooted(TestNetworkAddress::B))
+    );
+    assert_eq!(bb0.take_link_event(), None);
+
+    bb1.poll_1ms(DEFAULT_PEER_TIMEOUT_MS + 2);
+    assert!(bb0.recv_packet().unwrap().is_none());
+    assert_eq!(
+        bb0.take_link_event(),
+        Some(LinkEvent::PeerAppeared(TestNetworkAddress::B))
+    );
+
+    // Heartbeats from the same session don't generate any more events
+    bb1.poll_1ms(DEFAULT_PEER_TIMEOUT_MS + 200);
+    assert!(bb0.recv_packet().unwrap().is_none());
+    assert_eq!(bb0.take_link_event(), None);
+}
+
+#[test]
+fn link_health_ignores_pong_after_clock_step() {
+    let network = fixture_network();
+    let mut iface0 = fixture_iface_singleton(network.clone());
+    let mut iface1 = fixture_iface_singleton(network.clone());
+
+    let mut bb0 = fixture_bb([Some(&mut iface0), None], TestNetworkAddress::A);
+    let mut bb1 = fixture_bb([Some(&mut iface1), None], TestNetworkAddress::B);
+
+    assert_empty_recv_slice(&mut [&mut bb0, &mut bb1]);
+
+    // bb0's clock steps back before the pong comes in
+    bb0.poll_1ms(PING_INTERVAL_MS);
+    assert!(bb1.recv_packet().unwrap().is_none());
+    bb0.poll_1ms(PING_INTERVAL_MS - 2);
+    assert!(bb0.recv_packet().unwrap().is_none());
+
+    assert_eq!(
+        bb0.get_link_health(TestNetworkAddress::B).unwrap().rtt_ms,
+        None
+    );
+}
+
+#[test]
+fn link_health_rtt_and_loss() {
+    let network = fixture_network();
+    let mut iface0 = fixture_iface_singleton(network.clone());
+    let mut iface1 = fixture_iface_singleton(network.clone());
+
+    let mut bb0 = fixture_bb([Some(&mut iface0), None], TestNetworkAddress::A);
+    let mut bb1 = fixture_bb([Some(&mut iface1), None], TestNetworkAddress::B);
+
+    assert_empty_recv_slice(&mut [&mut bb0, &mut bb1]);
+    assert_eq!(
+        bb0.get_link_health(TestNetworkAddress::B).unwrap().rtt_ms,
+        None
+    );
+
+    // bb0 pings, bb1 pongs, and bb0 receives it 4ms after it pinged
+    bb0.poll_1ms(PING_INTERVAL_MS);
+    assert!(bb1.recv_packet().unwrap().is_none());
+    bb0.poll_1ms(PING_INTERVAL_MS + 4);
+    assert!(bb0.recv_packet().unwrap().is_none());
+
+    assert_eq!(
+        bb0.get_link_health(TestNetworkAddress::B).unwrap().rtt_ms,
+        Some(4)
+    );
+
+    let packet = TestPacket::SomeData {
+        a: 1,
+        b: 2,
+        c: true,
+    };
+
+    for i in 0..10 {
+        if i == 4 {
+            network.lock().unwrap().drop_next_payloads(2);
+        }
+
+        bb1.send_packet(&packet, TestNetworkAddress::A).unwrap();
+    }
+
+    for _ in 0..8 {
+        assert_eq!(bb0.recv_packet().unwrap().unwrap().0, packet);
+    }
+    assert!(bb0.recv_packet().unwrap().is_none());
+
+    // Loss rate only updates at the end of each measurement window
+    assert_eq!(
+        bb0.get_link_health(TestNetworkAddress::B)
+            .unwrap()
+            .loss_rate,
+        0.0
+    );
+    bb0.poll_1ms(BITRATE_MEASUREMENT_DURATION_MS + 1);
+
+    let loss_rate = bb0
+        .get_link_health(TestNetworkAddress::B)
+        .unwrap()
+        .loss_rate;
+    assert!(loss_rate > 0.1 && loss_rate < 0.2);
+}
+
+fn fixture_large_packet(size: usize) -> TestPacket {
+    TestPacket::LargeData((0..size).map(|i| (i * 13) as u8).collect())
+}
+
+fn send_forged_packet(
+    iface: &mut MockInterface,
+    destination: BigBrotherEndpoint,
+    from_addr: TestNetworkAddress,
+    to_addr: TestNetworkAddress,
+    counter: u32,
+    packet: BigBrotherPacket<&TestPacket>,
+) -> Result<(), BigBrotherError> {
+    let mut buffer = [0_u8; WORKING_BUFFER_SIZE];
+    let size = serialize_packet(&packet, from_addr, to_addr, counter, &mut buffer)?;
+
+    iface.send_udp(destination, &mut buffer[..size])
+}
+
+/// Sequences of every ack received on the interface, ignoring anything else
+fn take_acks(iface: &mut MockInterface) -> Vec<SequenceType> {
+    let mut buffer = [0_u8; WORKING_BUFFER_SIZE];
+    let mut acks = Vec::new();
+
+    while iface.recv_udp(&mut buffer).unwrap().is_some() {
+        let packet: BigBrotherPacket<TestPacket> = deserialize_packet(&buffer).unwrap();
+        if let BigBrotherPacket::MetaPacket(BigBrotherMetapacket::Ack { sequence }) = packet {
+            acks.push(sequence);
+        }
+    }
+
+    acks
+}
+
 impl Broadcastable for TestNetworkAddress {
     fn is_broadcast(&self) -> bool {
         matches!(self, TestNetworkAddress::Broadcast)
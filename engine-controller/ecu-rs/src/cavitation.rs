@@ -0,0 +1,192 @@
+use shared::ecu_hal::{EcuAlert, EcuCommand, EcuLinearOutput, EcuSensor, PumpType, RedlineAction};
+use strum::{EnumCount, IntoEnumIterator};
+
+use crate::{silprintln, Ecu};
+
+/// Watches the suction side of each pump for pressures low enough to boil the propellant
+pub struct CavitationMonitor {
+    time_cavitating_s: [f32; PumpType::COUNT],
+    tripped: [bool; PumpType::COUNT],
+}
+
+impl CavitationMonitor {
+    pub const fn new() -> Self {
+        Self {
+            time_cavitating_s: [0.0; PumpType::COUNT],
+            tripped: [false; PumpType::COUNT],
+        }
+    }
+}
+
+impl Default for CavitationMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Ecu<'a> {
+    /// Pressure above the propellant's vapor pressure at the pump, taking the lower of the inlet
+    /// and inducer so either sensor is enough. None without a cavitation config or readings
+    pub fn pump_npsh_margin_pa(&self, pump: PumpType) -> Option<f32> {
+        let config = self.config.pump_cavitation_config?;
+        let (vapor_pressure_pa, inlet_sensor, inducer_sensor) = match pump {
+            PumpType::FuelMain => (
+                config.fuel_vapor_pressure_pa,
+                EcuSensor::FuelPumpInletPressure,
+                EcuSensor::FuelPumpInducerPressure,
+            ),
+            PumpType::OxidizerMain => (
+                config.oxidizer_vapor_pressure_pa,
+                EcuSensor::OxidizerPumpInletPressure,
+                EcuSensor::OxidizerPumpInducerPressure,
+            ),
+        };
+
+        [inlet_sensor, inducer_sensor]
+            .into_iter()
+            .filter_map(|sensor| self.state_vector.sensor_value(sensor))
+            .reduce(f32::min)
+            .map(|pressure_pa| pressure_pa - vapor_pressure_pa)
+    }
+
+    /// Like a redline, a tripped pump's alert stays set so the cause is visible afterwards, but
+    /// it can trip again once the margin has recovered or the pump has stopped
+    pub(crate) fn update_cavitation_monitor(&mut self, dt: f32) {
+        let config = match self.config.pump_cavitation_config {
+            Some(config) => config,
+            None => return,
+        };
+
+        for (index, pump) in PumpType::iter().enumerate() {
+            // Without flow there's nothing to cavitate, however low the inlet pressure is
+            let cavitating = self.driver.get_linear_output(pump_output(pump)) > 0.01
+                && self
+                    .pump_npsh_margin_pa(pump)
+                    .is_some_and(|margin_pa| margin_pa < config.min_npsh_margin_pa);
+
+            if !cavitating {
+                self.cavitation_monitor.time_cavitating_s[index] = 0.0;
+                self.cavitation_monitor.tripped[index] = false;
+                continue;
+            }
+
+            self.cavitation_monitor.time_cavitating_s[index] += dt;
+
+            if self.cavitation_monitor.tripped[index]
+                || self.cavitation_monitor.time_cavitating_s[index] < config.persistence_s
+            {
+                continue;
+            }
+
+            silprintln!("{:?} pump is cavitating", pump);
+            self.cavitation_monitor.tripped[index] = true;
+            self.alert_manager
+                .set_condition(EcuAlert::pump_cavitation(pump));
+
+            match config.action {
+                RedlineAction::Alert => continue,
+                RedlineAction::ShutdownEngine => self.shutdown_engine(),
+                RedlineAction::SafeEcu => self.safe_ecu(self.config.safe_tank_action),
+            }
+
+            // The engine only stops its pumps if it was running
+            if !self.enqueue_command(EcuCommand::SetPumpDuty((pump, 0.0))) {
+                silprintln!("Local command queue full, couldn't stop {:?} pump", pump);
+            }
+        }
+    }
+
+    /// Called on reconfiguration, as the limits may have changed or monitoring turned off
+    pub(crate) fn reset_cavitation_monitor(&mut self) {
+        self.cavitation_monitor = CavitationMonitor::new();
+
+        for pump in PumpType::iter() {
+            self.alert_manager
+                .clear_condition(EcuAlert::pump_cavitation(pump));
+        }
+    }
+}
+
+fn pump_output(pump: PumpType) -> EcuLinearOutput {
+    match pump {
+        PumpType::FuelMain => EcuLinearOutput::FuelPump,
+        PumpType::OxidizerMain => EcuLinearOutput::OxidizerPump,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::{
+        alerts::is_condition_set,
+        ecu_hal::{
+            EcuAlert, EcuCommand, EcuConfig, EcuLinearOutput, EcuSensor, PumpCavitationConfig,
+            PumpState, PumpType, RedlineAction,
+        },
+        SensorData,
+    };
+
+    use crate::{
+        test_fixture::{run, EcuFixture},
+        Ecu,
+    };
+
+    fn set_pressure(ecu: &mut Ecu, sensor: EcuSensor, pressure_pa: f32) {
+        ecu.state_vector.update_sensor_data(
+            sensor,
+            &SensorData::Pressure {
+                pressure_pa,
+                raw_data: 0,
+            },
+        );
+    }
+
+    #[test]
+    fn test_cavitating_pump_is_stopped() {
+        let mut fixture = EcuFixture::new();
+        let mut ecu = fixture.ecu();
+        let mut config = EcuConfig::default();
+        config.pump_cavitation_config = Some(PumpCavitationConfig {
+            fuel_vapor_pressure_pa: 1e4,
+            oxidizer_vapor_pressure_pa: 5e6,
+            min_npsh_margin_pa: 1e5,
+            persistence_s: 0.05,
+            action: RedlineAction::ShutdownEngine,
+        });
+        ecu.configure_ecu(config);
+
+        set_pressure(&mut ecu, EcuSensor::FuelPumpInletPressure, 5e5);
+        set_pressure(&mut ecu, EcuSensor::FuelPumpInducerPressure, 8e5);
+        assert_eq!(ecu.pump_npsh_margin_pa(PumpType::FuelMain), Some(4.9e5));
+        assert_eq!(ecu.pump_npsh_margin_pa(PumpType::OxidizerMain), None);
+
+        ecu.enqueue_command(EcuCommand::SetPumpDuty((PumpType::FuelMain, 0.5)));
+        run(&mut ecu, 0.1);
+        assert_eq!(
+            ecu.fuel_pump.as_ref().unwrap().hal_state(),
+            PumpState::Pumping
+        );
+
+        // Short dips are ignored
+        set_pressure(&mut ecu, EcuSensor::FuelPumpInletPressure, 5e4);
+        run(&mut ecu, 0.02);
+        set_pressure(&mut ecu, EcuSensor::FuelPumpInletPressure, 5e5);
+        run(&mut ecu, 0.1);
+        assert_eq!(
+            ecu.fuel_pump.as_ref().unwrap().hal_state(),
+            PumpState::Pumping
+        );
+
+        set_pressure(&mut ecu, EcuSensor::FuelPumpInducerPressure, 5e4);
+        run(&mut ecu, 0.1);
+        assert!(is_condition_set(
+            ecu.alert_manager.get_condition_bitmask(),
+            EcuAlert::FuelPumpCavitation.into()
+        ));
+        assert!(!is_condition_set(
+            ecu.alert_manager.get_condition_bitmask(),
+            EcuAlert::OxidizerPumpCavitation.into()
+        ));
+        assert_eq!(ecu.fuel_pump.as_ref().unwrap().hal_state(), PumpState::Idle);
+        assert_eq!(ecu.driver.get_linear_output(EcuLinearOutput::FuelPump), 0.0);
+    }
+}
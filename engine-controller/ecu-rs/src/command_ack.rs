@@ -0,0 +1,323 @@
+use shared::{
+    comms_hal::{CommandAck, CommandRejection, CommandResult, NetworkAddress, Packet},
+    ecu_hal::{EcuCommand, EcuConfigError, PumpType, TankType},
+};
+
+use crate::{ecu::PACKET_QUEUE_SIZE, silprintln, Ecu};
+
+/// Who to answer once the tracked command at the same position in the packet queue is handled
+#[derive(Debug, Clone, Copy)]
+pub struct PendingAck {
+    id: u16,
+    source: NetworkAddress,
+}
+
+pub(crate) const fn empty_pending_acks() -> [Option<PendingAck>; PACKET_QUEUE_SIZE] {
+    [None; PACKET_QUEUE_SIZE]
+}
+
+impl<'a> Ecu<'a> {
+    /// Unwraps a tracked command so the rest of the update handles it like any other command.
+    /// Whichever handler acts on it answers it with what it actually did
+    pub(crate) fn track_command(
+        &mut self,
+        index: usize,
+        packet: Packet,
+        source: NetworkAddress,
+    ) -> Packet {
+        match packet {
+            Packet::TrackedEcuCommand { id, command } => {
+                self.pending_acks[index] = Some(PendingAck { id, source });
+                Packet::EcuCommand(command)
+            }
+            packet => {
+                self.pending_acks[index] = None;
+                packet
+            }
+        }
+    }
+
+    /// Answers the command at this position in the packet queue, if it was tracked and hasn't
+    /// been answered yet
+    pub(crate) fn acknowledge_command(&mut self, index: usize, result: CommandResult) {
+        let pending_ack = match self.pending_acks.get_mut(index).and_then(Option::take) {
+            Some(pending_ack) => pending_ack,
+            None => return,
+        };
+
+        if result != CommandResult::Accepted {
+            silprintln!("Command {} not accepted: {:?}", pending_ack.id, result);
+        }
+
+        self.send_packet(
+            &Packet::CommandAck(CommandAck {
+                id: pending_ack.id,
+                result,
+            }),
+            pending_ack.source,
+        );
+    }
+
+    /// FSM commands that won't reach an FSM state are answered before the FSMs update, and the
+    /// states answer the rest themselves
+    pub(crate) fn acknowledge_blocked_fsm_commands(
+        &mut self,
+        packets: &[(NetworkAddress, Packet)],
+    ) {
+        for (index, (_remote, packet)) in packets.iter().enumerate() {
+            let configured = match packet {
+                Packet::EcuCommand(command) => self.fsm_command_configured(command),
+                _ => None,
+            };
+
+            match configured {
+                Some(false) => self.acknowledge_command(
+                    index,
+                    CommandResult::Rejected(CommandRejection::NotConfigured),
+                ),
+                Some(true) if self.manual_mode.is_some() => self.acknowledge_command(
+                    index,
+                    CommandResult::Rejected(CommandRejection::ManualModeActive),
+                ),
+                _ => {}
+            }
+        }
+    }
+
+    /// Anything still unanswered reached a handler that had nothing to do with it, like
+    /// shutting down an idle engine
+    pub(crate) fn acknowledge_remaining_commands(&mut self) {
+        for index in 0..PACKET_QUEUE_SIZE {
+            self.acknowledge_command(index, CommandResult::NotApplicable);
+        }
+    }
+
+    /// Whether the FSM an FSM command is for exists, or None for any other command
+    fn fsm_command_configured(&self, command: &EcuCommand) -> Option<bool> {
+        let configured = match command {
+            EcuCommand::FireEngine | EcuCommand::ShutdownEngine => self.engine.is_some(),
+            EcuCommand::FireIgniter => self.igniter.is_some(),
+            EcuCommand::SetTankState((TankType::FuelMain, _)) => self.fuel_tank.is_some(),
+            EcuCommand::SetTankState((TankType::OxidizerMain, _)) => self.oxidizer_tank.is_some(),
+            EcuCommand::SetPumpDuty((pump, _)) | EcuCommand::SetPumpControlMode((pump, _)) => {
+                match pump {
+                    PumpType::FuelMain => self.fuel_pump.is_some(),
+                    PumpType::OxidizerMain => self.oxidizer_pump.is_some(),
+                }
+            }
+            _ => return None,
+        };
+
+        Some(configured)
+    }
+}
+
+/// Busy FSMs get their own rejection so the operator knows retrying later might work
+pub(crate) fn config_result(result: Result<(), EcuConfigError>) -> CommandResult {
+    match result {
+        Ok(()) => CommandResult::Accepted,
+        Err(EcuConfigError::NotIdle) => CommandResult::Rejected(CommandRejection::NotIdle),
+        Err(error) => CommandResult::Rejected(CommandRejection::InvalidConfig(error)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use big_brother::{
+        interface::{
+            mock_interface::MockInterface,
+            mock_topology::{MockPhysicalInterface, MockPhysicalNet},
+        },
+        BigBrother,
+    };
+    use shared::{
+        comms_hal::{CommandRejection, CommandResult, NetworkAddress, Packet},
+        ecu_hal::{
+            EcuBinaryOutput, EcuCommand, EcuConfig, EcuSensor, EngineState, TankConfig, TankState,
+            TankType,
+        },
+        ecu_mock::EcuDriverMock,
+        SensorData, COMMS_NETWORK_MAP_SIZE,
+    };
+
+    use crate::{ecu::EcuBigBrother, engine_fsm::firing::Firing, Ecu};
+
+    type MissionControl<'a> = BigBrother<'a, COMMS_NETWORK_MAP_SIZE, Packet, NetworkAddress>;
+
+    /// Swaps heartbeats so both ends know where the other is
+    fn connect(ecu: &mut Ecu, mission_ctrl: &mut MissionControl) {
+        mission_ctrl.poll_1ms(200);
+        ecu.driver
+            .as_mut_any()
+            .downcast_mut::<EcuDriverMock>()
+            .unwrap()
+            .set_timestamp(0.2);
+        ecu.update(0.001);
+        while mission_ctrl.recv_packet().unwrap().is_some() {}
+    }
+
+    /// Sends the commands as tracked commands all in the same update, returning their acks
+    fn send_tracked(
+        ecu: &mut Ecu,
+        mission_ctrl: &mut MissionControl,
+        commands: &[EcuCommand],
+    ) -> Vec<Option<CommandResult>> {
+        for (id, command) in commands.iter().enumerate() {
+            mission_ctrl
+                .send_packet(
+                    &Packet::TrackedEcuCommand {
+                        id: id as u16,
+                        command: command.clone(),
+                    },
+                    NetworkAddress::EngineController(0),
+                )
+                .unwrap();
+        }
+
+        ecu.update(0.001);
+
+        let mut results = vec![None; commands.len()];
+        while let Some((packet, _source)) = mission_ctrl.recv_packet().unwrap() {
+            if let Packet::CommandAck(ack) = packet {
+                results[ack.id as usize] = Some(ack.result);
+            }
+        }
+
+        results
+    }
+
+    fn tank_config() -> TankConfig {
+        TankConfig {
+            press_valve: None,
+            fill_valve: None,
+            vent_valve: None,
+            press_min_threshold_pa: 1e5,
+            press_max_threshold_pa: 2e5,
+            press_relief_threshold_pa: None,
+            press_valve_min_cycle_time_s: 0.1,
+        }
+    }
+
+    #[test]
+    fn test_acks_match_what_the_ecu_did() {
+        let network = Arc::new(Mutex::new(MockPhysicalNet::new(
+            [192, 168, 0, 0],
+            [true, true, false, false],
+            [192, 168, 255, 255],
+        )));
+        let mut ecu_iface = MockInterface::new_networked(Arc::new(Mutex::new(
+            MockPhysicalInterface::new(network.clone()),
+        )));
+        let mut mission_ctrl_iface = MockInterface::new_networked(Arc::new(Mutex::new(
+            MockPhysicalInterface::new(network.clone()),
+        )));
+
+        let mut driver = EcuDriverMock::new();
+        let mut comms: EcuBigBrother = BigBrother::new(
+            NetworkAddress::EngineController(0),
+            1,
+            NetworkAddress::Broadcast,
+            [Some(&mut ecu_iface), None],
+        );
+        let mut mission_ctrl: MissionControl = BigBrother::new(
+            NetworkAddress::MissionControl,
+            2,
+            NetworkAddress::Broadcast,
+            [Some(&mut mission_ctrl_iface), None],
+        );
+
+        let mut ecu = Ecu::new(&mut driver, &mut comms);
+        let mut config = EcuConfig::default();
+        config.fuel_tank_config = Some(tank_config());
+        ecu.configure_ecu(config.clone());
+        connect(&mut ecu, &mut mission_ctrl);
+
+        assert_eq!(
+            send_tracked(
+                &mut ecu,
+                &mut mission_ctrl,
+                &[
+                    EcuCommand::FireEngine,
+                    EcuCommand::ShutdownEngine,
+                    EcuCommand::SetTankState((TankType::OxidizerMain, TankState::Pressurized)),
+                ]
+            ),
+            [
+                Some(CommandResult::Rejected(
+                    CommandRejection::TanksNotPressurized
+                )),
+                Some(CommandResult::NotApplicable),
+                Some(CommandResult::Rejected(CommandRejection::NotConfigured)),
+            ]
+        );
+
+        // Entering manual mode lets the valve command after it through in the same update
+        assert_eq!(
+            send_tracked(
+                &mut ecu,
+                &mut mission_ctrl,
+                &[
+                    EcuCommand::EnterManualMode { timeout_s: 1.0 },
+                    EcuCommand::SetBinaryValve {
+                        valve: EcuBinaryOutput::FuelVentValve,
+                        state: true,
+                    },
+                    EcuCommand::SetTankState((TankType::FuelMain, TankState::Pressurized)),
+                ]
+            ),
+            [
+                Some(CommandResult::Accepted),
+                Some(CommandResult::Accepted),
+                Some(CommandResult::Rejected(CommandRejection::ManualModeActive)),
+            ]
+        );
+        assert!(ecu.driver.get_binary_valve(EcuBinaryOutput::FuelVentValve));
+
+        assert_eq!(
+            send_tracked(
+                &mut ecu,
+                &mut mission_ctrl,
+                &[
+                    EcuCommand::ExitManualMode,
+                    EcuCommand::SetTankState((TankType::FuelMain, TankState::Pressurized)),
+                ]
+            ),
+            [Some(CommandResult::Accepted), Some(CommandResult::Accepted)]
+        );
+        assert_eq!(ecu.fuel_tank_state(), Some(TankState::Pressurized));
+
+        // Keep the firing state's own chamber pressure check out of the way
+        let mut engine_config = config.engine_config.clone().unwrap();
+        engine_config.engine_combustion_pressure_tolerance_pa = 1e9;
+        ecu.update_sensor_data(
+            EcuSensor::EngineChamberPressure,
+            &SensorData::Pressure {
+                pressure_pa: 1e6,
+                raw_data: 0,
+            },
+        );
+        let mut engine = ecu.engine.take().unwrap();
+        engine.force_state(&mut ecu, Firing::new(engine_config));
+        ecu.engine = Some(engine);
+
+        assert_eq!(
+            send_tracked(
+                &mut ecu,
+                &mut mission_ctrl,
+                &[
+                    EcuCommand::ConfigureEcu(config),
+                    EcuCommand::FireEngine,
+                    EcuCommand::ShutdownEngine,
+                ]
+            ),
+            [
+                Some(CommandResult::Rejected(CommandRejection::NotIdle)),
+                Some(CommandResult::NotApplicable),
+                Some(CommandResult::Accepted),
+            ]
+        );
+        assert_eq!(ecu.engine_state(), EngineState::EngineShutdown);
+    }
+}
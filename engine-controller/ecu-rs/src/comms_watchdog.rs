@@ -0,0 +1,149 @@
+use big_brother::link_health::LinkEvent;
+use shared::{comms_hal::NetworkAddress, ecu_hal::EcuAlert};
+
+use crate::{silprintln, Ecu};
+
+impl<'a> Ecu<'a> {
+    pub(crate) fn update_comms_watchdog(&mut self) {
+        while let Some(event) = self.comms.take_link_event() {
+            match event {
+                LinkEvent::PeerLost(NetworkAddress::MissionControl) => {
+                    if let Some(watchdog_config) = self.config.comms_watchdog_config.clone() {
+                        silprintln!("Lost comms with mission control, safing");
+                        self.alert_manager
+                            .set_condition(EcuAlert::MissionControlCommsLost);
+                        self.safe_ecu(watchdog_config.tank_action);
+                    }
+                }
+                LinkEvent::PeerAppeared(NetworkAddress::MissionControl) => {
+                    self.alert_manager
+                        .clear_condition(EcuAlert::MissionControlCommsLost);
+                }
+                _ => {}
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use big_brother::{
+        interface::{
+            mock_interface::MockInterface,
+            mock_topology::{MockPhysicalInterface, MockPhysicalNet},
+        },
+        BigBrother,
+    };
+    use shared::{
+        alerts::is_condition_set,
+        comms_hal::{NetworkAddress, Packet},
+        ecu_hal::{
+            CommsWatchdogConfig, EcuAlert, EcuBinaryOutput, EcuCommand, EcuConfig, EngineState,
+            IgniterState, SafeTankAction, TankConfig, TankState, TankType,
+        },
+        ecu_mock::EcuDriverMock,
+        COMMS_NETWORK_MAP_SIZE,
+    };
+
+    use crate::{
+        ecu::EcuBigBrother, engine_fsm::firing::Firing as EngineFiring,
+        igniter_fsm::firing::Firing as IgniterFiring, Ecu,
+    };
+
+    #[test]
+    fn test_comms_loss_safes_ecu() {
+        let network = Arc::new(Mutex::new(MockPhysicalNet::new(
+            [192, 168, 0, 0],
+            [true, true, false, false],
+            [192, 168, 255, 255],
+        )));
+        let mut ecu_iface = MockInterface::new_networked(Arc::new(Mutex::new(
+            MockPhysicalInterface::new(network.clone()),
+        )));
+        let mut mission_ctrl_iface = MockInterface::new_networked(Arc::new(Mutex::new(
+            MockPhysicalInterface::new(network.clone()),
+        )));
+
+        let mut driver = EcuDriverMock::new();
+        let mut comms: EcuBigBrother = BigBrother::new(
+            NetworkAddress::EngineController(0),
+            1,
+            NetworkAddress::Broadcast,
+            [Some(&mut ecu_iface), None],
+        );
+        let mut mission_ctrl: BigBrother<COMMS_NETWORK_MAP_SIZE, Packet, NetworkAddress> =
+            BigBrother::new(
+                NetworkAddress::MissionControl,
+                2,
+                NetworkAddress::Broadcast,
+                [Some(&mut mission_ctrl_iface), None],
+            );
+
+        let mut ecu = Ecu::new(&mut driver, &mut comms);
+        let mut config = EcuConfig::default();
+        config.fuel_tank_config = Some(TankConfig {
+            press_valve: Some(EcuBinaryOutput::FuelPressValve),
+            fill_valve: Some(EcuBinaryOutput::FuelFillValve),
+            vent_valve: Some(EcuBinaryOutput::FuelVentValve),
+            press_min_threshold_pa: 0.0,
+            press_max_threshold_pa: 1e6,
+            press_relief_threshold_pa: None,
+            press_valve_min_cycle_time_s: 0.0,
+        });
+        config.comms_watchdog_config = Some(CommsWatchdogConfig {
+            timeout_s: 0.5,
+            tank_action: SafeTankAction::Vent,
+        });
+        ecu.configure_ecu(config.clone());
+
+        ecu.enqueue_command(EcuCommand::SetTankState((
+            TankType::FuelMain,
+            TankState::Pressurized,
+        )));
+        ecu.update(0.001);
+        assert_eq!(ecu.fuel_tank_state(), Some(TankState::Pressurized));
+
+        let mut engine = ecu.engine.take().unwrap();
+        engine.force_state(&mut ecu, EngineFiring::new(config.engine_config.unwrap()));
+        ecu.engine = Some(engine);
+
+        let mut igniter = ecu.igniter.take().unwrap();
+        igniter.force_state(&mut ecu, IgniterFiring::new(config.igniter_config.unwrap()));
+        ecu.igniter = Some(igniter);
+
+        // Mission control goes silent
+        ecu.driver
+            .as_mut_any()
+            .downcast_mut::<EcuDriverMock>()
+            .unwrap()
+            .set_timestamp(0.6);
+        ecu.update(0.001);
+
+        let alerts = ecu.alert_manager.get_condition_bitmask();
+        assert!(is_condition_set(
+            alerts,
+            EcuAlert::MissionControlCommsLost.into()
+        ));
+        assert!(!is_condition_set(
+            alerts,
+            EcuAlert::EngineChamberPressureOffNominal.into()
+        ));
+        assert_eq!(ecu.engine_state(), EngineState::EngineShutdown);
+        assert_eq!(ecu.igniter_state(), IgniterState::Shutdown);
+        assert_eq!(ecu.fuel_tank_state(), Some(TankState::Venting));
+        assert!(ecu.driver.get_binary_valve(EcuBinaryOutput::FuelVentValve));
+        assert!(!ecu.driver.get_binary_valve(EcuBinaryOutput::FuelPressValve));
+
+        // And comes back
+        mission_ctrl.poll_1ms(700);
+        ecu.update(0.001);
+
+        let alerts = ecu.alert_manager.get_condition_bitmask();
+        assert!(!is_condition_set(
+            alerts,
+            EcuAlert::MissionControlCommsLost.into()
+        ));
+    }
+}
@@ -1,25 +1,34 @@
 use big_brother::BigBrother;
 use shared::{
     alerts::AlertManager,
-    comms_hal::{NetworkAddress, Packet},
+    comms_hal::{CommandRejection, CommandResult, NetworkAddress, Packet},
     ecu_hal::{
-        EcuAlert, EcuCommand, EcuConfig, EcuDebugInfoVariant, EcuDriver, EcuResponse, EcuSensor,
-        EcuTankTelemetryFrame, EcuTelemetry, EcuTelemetryFrame, EngineState, IgniterState,
-        PumpState, TankState, TankType,
+        EcuAlert, EcuCommand, EcuConfig, EcuConfigError, EcuDebugInfoVariant, EcuDriver,
+        EcuLinearOutput, EcuOutputState, EcuResponse, EcuSensor, EcuTankTelemetryFrame,
+        EcuTelemetry, EcuTelemetryFrame, EngineState, IgniterState, PumpState, PumpType,
+        SensorVoteConfig, TankState, TankType, ThrottleProfile, ValveInterlock, MAX_INTERLOCKS,
+        MAX_REDLINES, MAX_THROTTLE_PROFILE_POINTS,
     },
-    ControllerEntity, SensorData, COMMS_NETWORK_MAP_SIZE,
+    ControllerEntity, SensorConfig, SensorData, COMMS_NETWORK_MAP_SIZE,
 };
 
 use crate::{
+    cavitation::CavitationMonitor,
+    command_ack::{config_result, empty_pending_acks, PendingAck},
     engine_fsm::{self, EngineFsm},
     igniter_fsm::{self, IgniterFsm},
-    pump_fsm::PumpFsm,
+    manual_mode::ManualMode,
+    pump_fsm::{self, PumpControlConfig, PumpFsm},
+    purge::PurgeRequests,
+    redlines::Redlines,
+    sequence::Sequence,
     silprintln,
     state_vector::StateVector,
     tank_fsm::{self, TankFsm},
+    valve_monitor::ValveMonitor,
 };
 
-use strum::IntoEnumIterator;
+use strum::{EnumCount, IntoEnumIterator};
 
 pub const PACKET_QUEUE_SIZE: usize = 16;
 pub const LOCAL_COMMAND_QUEUE_SIZE: usize = 8;
@@ -41,10 +50,20 @@ pub struct Ecu<'a> {
     pub fuel_pump: Option<ControllerEntity<PumpFsm, Ecu<'a>, PumpState>>,
     pub oxidizer_pump: Option<ControllerEntity<PumpFsm, Ecu<'a>, PumpState>>,
 
+    pub throttle_profile: ThrottleProfile,
+    pub redlines: Redlines,
+    pub sequence: Sequence,
+    pub manual_mode: Option<ManualMode>,
+    pub interlocks: [Option<ValveInterlock>; MAX_INTERLOCKS],
+    pub valve_monitor: ValveMonitor,
+    pub cavitation_monitor: CavitationMonitor,
+    pub purge_requests: PurgeRequests,
+
     pub last_telemetry_frame: Option<EcuTelemetryFrame>,
     time_since_last_telemetry: f32,
 
     pub local_command_queue: [Option<EcuCommand>; LOCAL_COMMAND_QUEUE_SIZE],
+    pub(crate) pending_acks: [Option<PendingAck>; PACKET_QUEUE_SIZE],
 }
 
 impl<'a> Ecu<'a> {
@@ -62,9 +81,18 @@ impl<'a> Ecu<'a> {
             oxidizer_tank: None,
             fuel_pump: None,
             oxidizer_pump: None,
+            throttle_profile: ThrottleProfile::new(),
+            redlines: Redlines::new(),
+            sequence: Sequence::new(),
+            manual_mode: None,
+            interlocks: [None; MAX_INTERLOCKS],
+            valve_monitor: ValveMonitor::new(),
+            cavitation_monitor: CavitationMonitor::new(),
+            purge_requests: PurgeRequests::new(),
             last_telemetry_frame: None,
             time_since_last_telemetry: 1e3,
             local_command_queue: empty_command_array(),
+            pending_acks: empty_pending_acks(),
         }
     }
 
@@ -75,6 +103,7 @@ impl<'a> Ecu<'a> {
         let mut packet_queue = empty_packet_array();
         while let Some((packet, source)) = self.comms.recv_packet().ok().flatten() {
             silprintln!("Received from {:?} got {:?}", source, packet);
+            let packet = self.track_command(num_packets, packet, source);
             packet_queue[num_packets] = (source, packet);
             num_packets += 1;
 
@@ -84,53 +113,31 @@ impl<'a> Ecu<'a> {
             }
         }
 
-        for command in &mut self.local_command_queue {
-            if let Some(command) = command.take() {
-                packet_queue[num_packets] =
-                    (NetworkAddress::MissionControl, Packet::EcuCommand(command));
-                num_packets += 1;
+        num_packets = self.drain_local_commands(&mut packet_queue, num_packets);
+
+        // Commands issued by the sequence are drained straight away, so they're handled this
+        // update rather than after the next round of commands from the network
+        self.handle_sequence_commands(&packet_queue[..num_packets]);
+        self.update_sequence(dt);
+        num_packets = self.drain_local_commands(&mut packet_queue, num_packets);
 
-                if num_packets >= PACKET_QUEUE_SIZE {
-                    silprintln!("Packet queue full?! (from commands");
-                    break;
-                }
-            } else {
-                break;
-            }
-        }
         let packets = &packet_queue[..num_packets];
 
+        self.update_comms_watchdog();
         self.handle_non_fsm_commands(packets);
+        self.handle_manual_mode_commands(packets);
+        self.update_sensor_health(dt);
+        self.update_redlines(dt);
+        self.update_valve_monitor(dt);
+        self.update_cavitation_monitor(dt);
 
-        if let Some(mut engine) = self.engine.take() {
-            engine.update(self, dt, packets);
-            self.engine = Some(engine);
-        }
+        self.update_manual_mode(dt);
 
-        if let Some(mut igniter) = self.igniter.take() {
-            igniter.update(self, dt, packets);
-            self.igniter = Some(igniter);
-        }
-
-        if let Some(mut fuel_tank) = self.fuel_tank.take() {
-            fuel_tank.update(self, dt, packets);
-            self.fuel_tank = Some(fuel_tank);
-        }
-
-        if let Some(mut oxidizer_tank) = self.oxidizer_tank.take() {
-            oxidizer_tank.update(self, dt, packets);
-            self.oxidizer_tank = Some(oxidizer_tank);
-        }
-
-        if let Some(mut fuel_pump) = self.fuel_pump.take() {
-            fuel_pump.update(self, dt, packets);
-            self.fuel_pump = Some(fuel_pump);
-        }
-
-        if let Some(mut oxidizer_pump) = self.oxidizer_pump.take() {
-            oxidizer_pump.update(self, dt, packets);
-            self.oxidizer_pump = Some(oxidizer_pump);
+        self.acknowledge_blocked_fsm_commands(packets);
+        if self.manual_mode.is_none() {
+            self.update_fsms(dt, packets);
         }
+        self.acknowledge_remaining_commands();
 
         self.time_since_last_telemetry += dt;
         if self.time_since_last_telemetry >= self.config.telemetry_rate_s {
@@ -168,19 +175,170 @@ impl<'a> Ecu<'a> {
         }
     }
 
+    fn update_fsms(&mut self, dt: f32, packets: &[(NetworkAddress, Packet)]) {
+        if let Some(mut engine) = self.engine.take() {
+            engine.update(self, dt, packets);
+            self.engine = Some(engine);
+        }
+
+        if let Some(mut igniter) = self.igniter.take() {
+            igniter.update(self, dt, packets);
+            self.igniter = Some(igniter);
+        }
+
+        if let Some(mut fuel_tank) = self.fuel_tank.take() {
+            fuel_tank.update(self, dt, packets);
+            self.fuel_tank = Some(fuel_tank);
+        }
+
+        if let Some(mut oxidizer_tank) = self.oxidizer_tank.take() {
+            oxidizer_tank.update(self, dt, packets);
+            self.oxidizer_tank = Some(oxidizer_tank);
+        }
+
+        if let Some(mut fuel_pump) = self.fuel_pump.take() {
+            fuel_pump.update(self, dt, packets);
+            self.fuel_pump = Some(fuel_pump);
+        }
+
+        if let Some(mut oxidizer_pump) = self.oxidizer_pump.take() {
+            oxidizer_pump.update(self, dt, packets);
+            self.oxidizer_pump = Some(oxidizer_pump);
+        }
+    }
+
+    fn drain_local_commands(
+        &mut self,
+        packet_queue: &mut [(NetworkAddress, Packet); PACKET_QUEUE_SIZE],
+        mut num_packets: usize,
+    ) -> usize {
+        for command in &mut self.local_command_queue {
+            if num_packets >= PACKET_QUEUE_SIZE {
+                silprintln!("Packet queue full?! (from commands");
+                break;
+            }
+
+            if let Some(command) = command.take() {
+                packet_queue[num_packets] =
+                    (NetworkAddress::MissionControl, Packet::EcuCommand(command));
+                num_packets += 1;
+            } else {
+                break;
+            }
+        }
+
+        num_packets
+    }
+
     pub fn poll_interfaces(&mut self) {
         self.comms.poll_1ms((self.driver.timestamp() * 1e3) as u32);
     }
 
     pub fn handle_non_fsm_commands(&mut self, packets: &[(NetworkAddress, Packet)]) {
-        for (remote, packet) in packets {
-            match packet {
-                Packet::EcuCommand(EcuCommand::GetConfig) => {
+        for (index, (remote, packet)) in packets.iter().enumerate() {
+            let command = match packet {
+                Packet::EcuCommand(command) => command,
+                _ => continue,
+            };
+
+            let result = match command {
+                EcuCommand::GetConfig => {
                     silprintln!("Received get config command");
                     self.send_response_packet(EcuResponse::Config(self.config.clone()), *remote);
+
+                    for sensor in EcuSensor::iter() {
+                        if let Some(config) = self.state_vector.sensor_monitor.config(sensor) {
+                            self.send_response_packet(
+                                EcuResponse::ActiveSensorConfig { sensor, config },
+                                *remote,
+                            );
+                        }
+                    }
+
+                    CommandResult::Accepted
                 }
-                _ => {}
-            }
+                EcuCommand::SetThrottleProfilePoint { index, point } => {
+                    if self.engine_state() != EngineState::Idle {
+                        silprintln!("Ignoring throttle profile change while the engine is running");
+                        CommandResult::Rejected(CommandRejection::NotIdle)
+                    } else if *index as usize >= MAX_THROTTLE_PROFILE_POINTS {
+                        CommandResult::Rejected(CommandRejection::InvalidArgument)
+                    } else {
+                        let mut profile = self.throttle_profile.clone();
+                        profile.points[*index as usize] = *point;
+
+                        if profile.is_valid() {
+                            self.throttle_profile = profile;
+                            CommandResult::Accepted
+                        } else {
+                            silprintln!("Ignoring invalid throttle profile point {}", index);
+                            CommandResult::Rejected(CommandRejection::InvalidArgument)
+                        }
+                    }
+                }
+                EcuCommand::ClearThrottleProfile => {
+                    if self.engine_state() != EngineState::Idle {
+                        silprintln!("Ignoring throttle profile change while the engine is running");
+                        CommandResult::Rejected(CommandRejection::NotIdle)
+                    } else {
+                        self.throttle_profile = ThrottleProfile::new();
+                        CommandResult::Accepted
+                    }
+                }
+                EcuCommand::SetRedline { index, redline } => {
+                    if self.engine_state() != EngineState::Idle {
+                        silprintln!("Ignoring redline change while the engine is running");
+                        CommandResult::Rejected(CommandRejection::NotIdle)
+                    } else if *index as usize >= MAX_REDLINES {
+                        CommandResult::Rejected(CommandRejection::InvalidArgument)
+                    } else {
+                        self.set_redline(*index, *redline);
+                        CommandResult::Accepted
+                    }
+                }
+                EcuCommand::ClearRedlines => {
+                    if self.engine_state() != EngineState::Idle {
+                        silprintln!("Ignoring redline change while the engine is running");
+                        CommandResult::Rejected(CommandRejection::NotIdle)
+                    } else {
+                        self.clear_redlines();
+                        CommandResult::Accepted
+                    }
+                }
+                EcuCommand::ConfigureEcu(config) => {
+                    let result = self.ecu_config_result(config);
+
+                    if result.is_ok() {
+                        self.configure_ecu(config.clone());
+                    }
+
+                    self.send_config_result(result, *remote);
+                    config_result(result)
+                }
+                EcuCommand::ConfigureSensor { sensor, config } => {
+                    let result = self.sensor_config_result(*sensor, config);
+
+                    if result.is_ok() {
+                        self.state_vector.sensor_monitor.configure(*sensor, *config);
+                    }
+
+                    self.send_config_result(result, *remote);
+                    config_result(result)
+                }
+                EcuCommand::ConfigureSensorVote { sensor, vote } => {
+                    let result = self.sensor_vote_result(*sensor, vote);
+
+                    if result.is_ok() {
+                        self.state_vector.sensor_monitor.set_vote(*sensor, *vote);
+                    }
+
+                    self.send_config_result(result, *remote);
+                    config_result(result)
+                }
+                _ => continue,
+            };
+
+            self.acknowledge_command(index, result);
         }
     }
 
@@ -217,6 +375,25 @@ impl<'a> Ecu<'a> {
                 .state_vector
                 .sensor_data
                 .oxidizer_pump_outlet_pressure_pa,
+            faulted_sensors: self.state_vector.sensor_monitor.faulted_bitmask(),
+            manual_mode: self.manual_mode.is_some(),
+            sequence_state: self.sequence.state(),
+            sequence_step: self.sequence.current_step() as u8,
+            outputs: self.output_state(),
+            engine_performance: self.engine_performance(),
+        }
+    }
+
+    pub fn output_state(&self) -> EcuOutputState {
+        let mut linear_outputs = [0.0; EcuLinearOutput::COUNT];
+        for output in EcuLinearOutput::iter() {
+            linear_outputs[output.index()] = self.driver.get_linear_output(output);
+        }
+
+        EcuOutputState {
+            binary_valves: self.open_valves(),
+            sparking: self.driver.get_sparking(),
+            linear_outputs,
         }
     }
 
@@ -245,11 +422,12 @@ impl<'a> Ecu<'a> {
     }
 
     pub fn update_sensor_data(&mut self, sensor: EcuSensor, data: &SensorData) {
-        self.state_vector.update_sensor_data(sensor, data);
+        let data = self.state_vector.calibrate(sensor, data);
+        self.state_vector.update_sensor_data(sensor, &data);
 
         if self.debug_info_enabled {
             self.send_telemetry_packet(
-                EcuTelemetry::DebugSensorMeasurement((sensor, data.clone())),
+                EcuTelemetry::DebugSensorMeasurement((sensor, data)),
                 NetworkAddress::MissionControl,
             );
         }
@@ -257,6 +435,13 @@ impl<'a> Ecu<'a> {
 
     pub fn configure_ecu(&mut self, config: EcuConfig) {
         self.config = config;
+        self.reset_valve_monitor();
+        self.reset_cavitation_monitor();
+
+        if let Some(watchdog_config) = &self.config.comms_watchdog_config {
+            self.comms
+                .set_peer_timeout((watchdog_config.timeout_s * 1e3) as u32);
+        }
 
         if let Some(engine_config) = self.config.engine_config.clone() {
             self.engine = Some(ControllerEntity::new(
@@ -267,6 +452,39 @@ impl<'a> Ecu<'a> {
             self.engine = None;
         }
 
+        if let Some(engine_config) = self
+            .config
+            .engine_config
+            .clone()
+            .filter(|engine_config| engine_config.use_pumps)
+        {
+            self.fuel_pump = Some(ControllerEntity::new(
+                self,
+                pump_fsm::idle::Idle::new(
+                    PumpType::FuelMain,
+                    EcuLinearOutput::FuelPump,
+                    PumpControlConfig {
+                        setpoint_pa: engine_config.fuel_injector_pressure_setpoint_pa,
+                        pid_config: engine_config.pump_pressure_pid,
+                    },
+                ),
+            ));
+            self.oxidizer_pump = Some(ControllerEntity::new(
+                self,
+                pump_fsm::idle::Idle::new(
+                    PumpType::OxidizerMain,
+                    EcuLinearOutput::OxidizerPump,
+                    PumpControlConfig {
+                        setpoint_pa: engine_config.oxidizer_injector_pressure_setpoint_pa,
+                        pid_config: engine_config.pump_pressure_pid,
+                    },
+                ),
+            ));
+        } else {
+            self.fuel_pump = None;
+            self.oxidizer_pump = None;
+        }
+
         if let Some(igniter_config) = self.config.igniter_config.clone() {
             self.igniter = Some(ControllerEntity::new(
                 self,
@@ -325,6 +543,82 @@ impl<'a> Ecu<'a> {
         self.send_packet(&Packet::EcuResponse(response), destination);
     }
 
+    fn send_config_result(
+        &mut self,
+        result: Result<(), EcuConfigError>,
+        destination: NetworkAddress,
+    ) {
+        let response = match result {
+            Ok(()) => EcuResponse::ConfigAccepted,
+            Err(error) => {
+                silprintln!("Rejected config change: {:?}", error);
+                EcuResponse::ConfigRejected(error)
+            }
+        };
+
+        self.send_response_packet(response, destination);
+    }
+
+    pub(crate) fn ecu_config_result(&self, config: &EcuConfig) -> Result<(), EcuConfigError> {
+        if !self.config_change_allowed() {
+            Err(EcuConfigError::NotIdle)
+        } else {
+            config.validate()
+        }
+    }
+
+    pub(crate) fn sensor_config_result(
+        &self,
+        sensor: EcuSensor,
+        config: &SensorConfig,
+    ) -> Result<(), EcuConfigError> {
+        if !self.config_change_allowed() {
+            Err(EcuConfigError::NotIdle)
+        } else if !config.is_valid() {
+            Err(EcuConfigError::InvalidSensorConfig(sensor))
+        } else {
+            Ok(())
+        }
+    }
+
+    pub(crate) fn sensor_vote_result(
+        &self,
+        sensor: EcuSensor,
+        vote: &Option<SensorVoteConfig>,
+    ) -> Result<(), EcuConfigError> {
+        if !self.config_change_allowed() {
+            Err(EcuConfigError::NotIdle)
+        } else if vote.is_some_and(|vote| {
+            vote.max_disagreement < 0.0 || vote.redundant_sensors.contains(&Some(sensor))
+        }) {
+            Err(EcuConfigError::InvalidSensorConfig(sensor))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Config changes swap out the FSMs, so they're only allowed while nothing is running
+    fn config_change_allowed(&self) -> bool {
+        self.all_fsms_idle() && self.manual_mode.is_none() && !self.sequence.is_active()
+    }
+
+    pub(crate) fn all_fsms_idle(&self) -> bool {
+        self.engine_state() == EngineState::Idle
+            && self.igniter_state() == IgniterState::Idle
+            && self
+                .fuel_tank_state()
+                .is_none_or(|state| state == TankState::Idle)
+            && self
+                .oxidizer_tank_state()
+                .is_none_or(|state| state == TankState::Idle)
+            && [&self.fuel_pump, &self.oxidizer_pump]
+                .into_iter()
+                .all(|pump| {
+                    pump.as_ref()
+                        .is_none_or(|pump| pump.hal_state() == PumpState::Idle)
+                })
+    }
+
     pub(crate) fn engine_state(&self) -> EngineState {
         self.engine
             .as_ref()
@@ -386,3 +680,187 @@ fn empty_packet_array() -> [(NetworkAddress, Packet); PACKET_QUEUE_SIZE] {
 fn empty_command_array() -> [Option<EcuCommand>; LOCAL_COMMAND_QUEUE_SIZE] {
     [None, None, None, None, None, None, None, None]
 }
+
+#[cfg(test)]
+mod tests {
+    use shared::{
+        ecu_hal::{
+            BinaryOutputMask, EcuBinaryOutput, EcuCommand, EcuConfig, EcuLinearOutput, EcuSensor,
+            EngineState,
+        },
+        SensorCalibration, SensorConfig, SensorData,
+    };
+
+    use crate::{engine_fsm::firing::Firing, test_fixture::EcuFixture};
+
+    #[test]
+    fn test_configure_ecu_command() {
+        let mut fixture = EcuFixture::new();
+        let mut ecu = fixture.ecu();
+        ecu.configure_ecu(EcuConfig::default());
+
+        let mut config = EcuConfig::default();
+        config.telemetry_rate_s = 0.05;
+        ecu.enqueue_command(EcuCommand::ConfigureEcu(config.clone()));
+        ecu.update(0.001);
+        assert_eq!(ecu.config.telemetry_rate_s, 0.05);
+
+        let mut invalid_config = config.clone();
+        invalid_config.telemetry_rate_s = 0.1;
+        invalid_config
+            .igniter_config
+            .as_mut()
+            .unwrap()
+            .shutdown_duration_s = -1.0;
+        ecu.enqueue_command(EcuCommand::ConfigureEcu(invalid_config));
+        ecu.update(0.001);
+        assert_eq!(ecu.config.telemetry_rate_s, 0.05);
+
+        let mut engine = ecu.engine.take().unwrap();
+        engine.force_state(&mut ecu, Firing::new(config.engine_config.clone().unwrap()));
+        ecu.engine = Some(engine);
+
+        let mut running_config = config.clone();
+        running_config.telemetry_rate_s = 0.1;
+        ecu.enqueue_command(EcuCommand::ConfigureEcu(running_config));
+        ecu.update(0.001);
+        assert_eq!(ecu.config.telemetry_rate_s, 0.05);
+        assert_ne!(ecu.engine_state(), EngineState::Idle);
+    }
+
+    #[test]
+    fn test_sensor_calibration() {
+        let mut fixture = EcuFixture::new();
+        let mut ecu = fixture.ecu();
+        ecu.configure_ecu(EcuConfig::default());
+
+        let reading = SensorData::Pressure {
+            pressure_pa: 123.0,
+            raw_data: 2048,
+        };
+
+        // Without a config the driver's conversion is used as is
+        ecu.update_sensor_data(EcuSensor::FuelTankPressure, &reading);
+        assert_eq!(
+            ecu.state_vector.sensor_value(EcuSensor::FuelTankPressure),
+            Some(123.0)
+        );
+
+        ecu.enqueue_command(EcuCommand::ConfigureSensor {
+            sensor: EcuSensor::FuelTankPressure,
+            config: SensorConfig {
+                premin: 0.0,
+                premax: 4096.0,
+                postmin: 0.0,
+                postmax: 10e6,
+                calibration: Some(SensorCalibration {
+                    x0: 1000.0,
+                    x1: 0.0,
+                    x2: 0.0,
+                    x3: 0.0,
+                }),
+                max_rate_per_s: None,
+                stale_timeout_s: None,
+            },
+        });
+        ecu.update(0.001);
+        assert_eq!(
+            ecu.state_vector.sensor_value(EcuSensor::FuelTankPressure),
+            None
+        );
+
+        ecu.update_sensor_data(EcuSensor::FuelTankPressure, &reading);
+        assert_eq!(
+            ecu.state_vector.sensor_value(EcuSensor::FuelTankPressure),
+            Some(5e6 + 1000.0)
+        );
+        assert_eq!(
+            ecu.state_vector.sensor_data.fuel_tank_pressure_pa,
+            Some(5e6 + 1000.0)
+        );
+    }
+
+    #[test]
+    fn test_sensor_config_blocked_in_manual_mode() {
+        let mut fixture = EcuFixture::new();
+        let mut ecu = fixture.ecu();
+        ecu.configure_ecu(EcuConfig::default());
+
+        let configure_sensor = EcuCommand::ConfigureSensor {
+            sensor: EcuSensor::FuelTankPressure,
+            config: SensorConfig {
+                premin: 0.0,
+                premax: 4096.0,
+                postmin: 0.0,
+                postmax: 10e6,
+                calibration: None,
+                max_rate_per_s: None,
+                stale_timeout_s: None,
+            },
+        };
+        let reading = SensorData::Pressure {
+            pressure_pa: 123.0,
+            raw_data: 2048,
+        };
+
+        // Every FSM is idle, but manual mode still owns the outputs
+        ecu.enqueue_command(EcuCommand::EnterManualMode { timeout_s: 1.0 });
+        ecu.update(0.001);
+        assert!(ecu.all_fsms_idle());
+        ecu.enqueue_command(configure_sensor.clone());
+        ecu.update(0.001);
+        ecu.update_sensor_data(EcuSensor::FuelTankPressure, &reading);
+        assert_eq!(
+            ecu.state_vector.sensor_value(EcuSensor::FuelTankPressure),
+            Some(123.0)
+        );
+
+        ecu.enqueue_command(EcuCommand::ExitManualMode);
+        ecu.update(0.001);
+        ecu.enqueue_command(configure_sensor);
+        ecu.update(0.001);
+        ecu.update_sensor_data(EcuSensor::FuelTankPressure, &reading);
+        assert_eq!(
+            ecu.state_vector.sensor_value(EcuSensor::FuelTankPressure),
+            Some(5e6)
+        );
+    }
+
+    #[test]
+    fn test_output_state_telemetry() {
+        let mut fixture = EcuFixture::new();
+        let mut ecu = fixture.ecu();
+        ecu.configure_ecu(EcuConfig::default());
+
+        let outputs = ecu.generate_telemetry_frame().outputs;
+        assert_eq!(outputs.binary_valves, BinaryOutputMask::empty());
+        assert!(!outputs.sparking);
+
+        for command in [
+            EcuCommand::EnterManualMode { timeout_s: 1.0 },
+            EcuCommand::SetBinaryValve {
+                valve: EcuBinaryOutput::FuelVentValve,
+                state: true,
+            },
+            EcuCommand::SetSparking(true),
+            EcuCommand::SetLinearOutput {
+                output: EcuLinearOutput::OxidizerPump,
+                value: 0.25,
+            },
+        ] {
+            ecu.enqueue_command(command);
+        }
+        ecu.update(0.001);
+
+        let outputs = ecu.generate_telemetry_frame().outputs;
+        assert_eq!(
+            outputs.binary_valves,
+            BinaryOutputMask::empty().with(EcuBinaryOutput::FuelVentValve)
+        );
+        assert!(outputs.sparking);
+        assert_eq!(
+            outputs.linear_outputs[EcuLinearOutput::OxidizerPump.index()],
+            0.25
+        );
+    }
+}
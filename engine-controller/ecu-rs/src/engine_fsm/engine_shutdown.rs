@@ -4,7 +4,7 @@ use shared::{
     ControllerState,
 };
 
-use crate::{silprintln, Ecu};
+use crate::{purge::PurgeRequester, silprintln, Ecu};
 
 use super::{idle::Idle, EngineFsm};
 
@@ -16,16 +16,27 @@ pub struct EngineShutdown {
 impl<'f> ControllerState<EngineFsm, Ecu<'f>> for EngineShutdown {
     fn update<'a>(
         &mut self,
-        _ecu: &mut Ecu,
+        ecu: &mut Ecu,
         dt: f32,
         _packets: &[(NetworkAddress, Packet)],
     ) -> Option<EngineFsm> {
-        if self.time_since_state_transition >= self.engine_config.engine_shutdown_duration_s {
+        if self.shutdown_complete() {
             return Some(Idle::new(self.engine_config.clone()));
         }
 
         self.time_since_state_transition += dt;
 
+        if let Some(purge) = self.engine_config.purge {
+            if self.time_since_state_transition >= purge.propellant_close_time_s() {
+                close_propellant_valves(ecu);
+            }
+
+            ecu.request_purge(
+                PurgeRequester::Engine,
+                purge.purge_open_at(self.time_since_state_transition),
+            );
+        }
+
         None
     }
 
@@ -33,15 +44,19 @@ impl<'f> ControllerState<EngineFsm, Ecu<'f>> for EngineShutdown {
         silprintln!("Entered engine shutdown state");
         ecu.enqueue_command(EcuCommand::SetPumpDuty((PumpType::FuelMain, 0.0)));
         ecu.enqueue_command(EcuCommand::SetPumpDuty((PumpType::OxidizerMain, 0.0)));
-        ecu.driver
-            .set_binary_valve(EcuBinaryOutput::EngineFuelValve, false);
-        ecu.driver
-            .set_binary_valve(EcuBinaryOutput::EngineOxidizerValve, false);
-        // Nothing
+
+        match self.engine_config.purge {
+            Some(purge) if purge.lead_s.is_some() => {
+                ecu.request_purge(PurgeRequester::Engine, true);
+            }
+            _ => close_propellant_valves(ecu),
+        }
     }
 
-    fn exit_state(&mut self, _ecu: &mut Ecu) {
-        // Nothing
+    fn exit_state(&mut self, ecu: &mut Ecu) {
+        if self.engine_config.purge.is_some() {
+            ecu.request_purge(PurgeRequester::Engine, false);
+        }
     }
 }
 
@@ -52,4 +67,116 @@ impl EngineShutdown {
             time_since_state_transition: 0.0,
         })
     }
+
+    /// The shutdown duration counts from when the propellant valves close, and the purge has to
+    /// finish too
+    fn shutdown_complete(&self) -> bool {
+        let end_time_s = match self.engine_config.purge {
+            Some(purge) => (purge.propellant_close_time_s()
+                + self.engine_config.engine_shutdown_duration_s)
+                .max(purge.purge_close_time_s()),
+            None => self.engine_config.engine_shutdown_duration_s,
+        };
+
+        self.time_since_state_transition >= end_time_s
+    }
+}
+
+fn close_propellant_valves(ecu: &mut Ecu) {
+    ecu.driver
+        .set_binary_valve(EcuBinaryOutput::EngineFuelValve, false);
+    ecu.driver
+        .set_binary_valve(EcuBinaryOutput::EngineOxidizerValve, false);
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::ecu_hal::{EcuBinaryOutput, EcuConfig, EngineConfig, EngineState, PurgeConfig};
+
+    use crate::{
+        engine_fsm::firing::Firing,
+        test_fixture::{run, EcuFixture},
+        Ecu,
+    };
+
+    use super::EngineShutdown;
+
+    fn shutdown_with_purge(ecu: &mut Ecu, purge: PurgeConfig) {
+        let mut engine_config = EngineConfig::default();
+        engine_config.purge = Some(purge);
+
+        let mut config = EcuConfig::default();
+        config.engine_config = Some(engine_config.clone());
+        ecu.configure_ecu(config);
+
+        let mut engine = ecu.engine.take().unwrap();
+        engine.force_state(ecu, Firing::new(engine_config.clone()));
+        ecu.driver
+            .set_binary_valve(EcuBinaryOutput::EngineFuelValve, true);
+        ecu.driver
+            .set_binary_valve(EcuBinaryOutput::EngineOxidizerValve, true);
+        engine.force_state(ecu, EngineShutdown::new(engine_config));
+        ecu.engine = Some(engine);
+    }
+
+    #[test]
+    fn test_purge_after_shutdown() {
+        let mut fixture = EcuFixture::new();
+        let mut ecu = fixture.ecu();
+
+        shutdown_with_purge(
+            &mut ecu,
+            PurgeConfig {
+                lead_s: None,
+                delay_s: 0.1,
+                duration_s: 1.0,
+            },
+        );
+
+        assert!(!ecu
+            .driver
+            .get_binary_valve(EcuBinaryOutput::EngineFuelValve));
+        run(&mut ecu, 0.05);
+        assert!(!ecu.driver.get_binary_valve(EcuBinaryOutput::FuelPurgeValve));
+        run(&mut ecu, 0.1);
+        assert!(ecu.driver.get_binary_valve(EcuBinaryOutput::FuelPurgeValve));
+
+        // Outlasts the shutdown duration
+        run(&mut ecu, 0.8);
+        assert_eq!(ecu.engine_state(), EngineState::EngineShutdown);
+
+        run(&mut ecu, 0.2);
+        assert_eq!(ecu.engine_state(), EngineState::Idle);
+        assert!(!ecu.driver.get_binary_valve(EcuBinaryOutput::FuelPurgeValve));
+    }
+
+    #[test]
+    fn test_purge_lead() {
+        let mut fixture = EcuFixture::new();
+        let mut ecu = fixture.ecu();
+
+        shutdown_with_purge(
+            &mut ecu,
+            PurgeConfig {
+                lead_s: Some(0.1),
+                delay_s: 0.0,
+                duration_s: 0.3,
+            },
+        );
+
+        assert!(ecu.driver.get_binary_valve(EcuBinaryOutput::FuelPurgeValve));
+        assert!(ecu
+            .driver
+            .get_binary_valve(EcuBinaryOutput::EngineOxidizerValve));
+
+        run(&mut ecu, 0.15);
+        assert!(ecu.driver.get_binary_valve(EcuBinaryOutput::FuelPurgeValve));
+        assert!(!ecu
+            .driver
+            .get_binary_valve(EcuBinaryOutput::EngineOxidizerValve));
+
+        run(&mut ecu, 0.2);
+        assert!(!ecu.driver.get_binary_valve(EcuBinaryOutput::FuelPurgeValve));
+        assert_eq!(ecu.engine_state(), EngineState::EngineShutdown);
+    }
 }
@@ -1,6 +1,6 @@
 use shared::{
     comms_hal::{NetworkAddress, Packet},
-    ecu_hal::{EcuAlert, EcuBinaryOutput, EcuCommand, EngineConfig, PumpType},
+    ecu_hal::{EcuAlert, EcuBinaryOutput, EcuCommand, EcuSensor, EngineConfig, PumpType},
     ControllerState,
 };
 
@@ -65,10 +65,13 @@ impl EngineStartup {
     }
 
     fn achieved_stable_pressure(&self, ecu: &Ecu) -> bool {
-        (ecu.state_vector.sensor_data.engine_chamber_pressure_pa
-            - self.engine_config.engine_target_combustion_pressure_pa)
-            .abs()
-            < self.engine_config.engine_combustion_pressure_tolerance_pa
+        ecu.state_vector
+            .sensor_value(EcuSensor::EngineChamberPressure)
+            .is_some_and(|chamber_pressure_pa| {
+                (chamber_pressure_pa - self.engine_config.engine_target_combustion_pressure_pa)
+                    .abs()
+                    < self.engine_config.engine_combustion_pressure_tolerance_pa
+            })
     }
 
     fn startup_timed_out(&self) -> bool {
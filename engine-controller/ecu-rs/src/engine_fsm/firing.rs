@@ -1,6 +1,6 @@
 use shared::{
-    comms_hal::{NetworkAddress, Packet},
-    ecu_hal::{EcuAlert, EcuCommand, EngineConfig},
+    comms_hal::{CommandResult, NetworkAddress, Packet},
+    ecu_hal::{EcuAlert, EcuCommand, EcuSensor, EngineConfig, PumpType, ThrottlePoint},
     ControllerState,
 };
 
@@ -11,6 +11,7 @@ use super::{engine_shutdown::EngineShutdown, EngineFsm};
 pub struct Firing {
     engine_config: EngineConfig,
     startup_elapsed_time: f32,
+    commanded_pump_duty: Option<f32>,
 }
 
 impl<'f> ControllerState<EngineFsm, Ecu<'f>> for Firing {
@@ -20,7 +21,9 @@ impl<'f> ControllerState<EngineFsm, Ecu<'f>> for Firing {
         dt: f32,
         packets: &[(NetworkAddress, Packet)],
     ) -> Option<EngineFsm> {
-        if self.chamber_pressure_degraded(ecu) {
+        let setpoint = self.throttle_setpoint(ecu);
+
+        if self.chamber_pressure_degraded(ecu, setpoint.chamber_pressure_pa) {
             ecu.alert_manager
                 .set_condition(EcuAlert::EngineChamberPressureOffNominal);
 
@@ -34,10 +37,15 @@ impl<'f> ControllerState<EngineFsm, Ecu<'f>> for Firing {
             return Some(EngineShutdown::new(self.engine_config.clone()));
         }
 
-        if self.received_shutdown_command(packets) {
+        if let Some(index) = self.received_shutdown_command(packets) {
+            ecu.acknowledge_command(index, CommandResult::Accepted);
             return Some(EngineShutdown::new(self.engine_config.clone()));
         }
 
+        if let Some(pump_duty) = setpoint.pump_duty {
+            self.command_pump_duty(ecu, pump_duty);
+        }
+
         self.startup_elapsed_time += dt;
 
         None
@@ -58,26 +66,57 @@ impl Firing {
         EngineFsm::Firing(Self {
             engine_config,
             startup_elapsed_time: 0.0,
+            commanded_pump_duty: None,
         })
     }
 
-    fn chamber_pressure_degraded(&self, ecu: &Ecu) -> bool {
-        (ecu.state_vector.sensor_data.engine_chamber_pressure_pa
-            - self.engine_config.engine_target_combustion_pressure_pa)
-            .abs()
-            > self.engine_config.engine_combustion_pressure_tolerance_pa
+    fn throttle_setpoint(&self, ecu: &Ecu) -> ThrottlePoint {
+        ecu.throttle_profile
+            .setpoint(self.startup_elapsed_time)
+            .unwrap_or(ThrottlePoint {
+                time_s: self.startup_elapsed_time,
+                chamber_pressure_pa: self.engine_config.engine_target_combustion_pressure_pa,
+                pump_duty: None,
+            })
+    }
+
+    fn chamber_pressure_degraded(&self, ecu: &Ecu, chamber_pressure_setpoint_pa: f32) -> bool {
+        // Without a trustworthy chamber pressure there's no way to tell the engine is healthy
+        ecu.state_vector
+            .sensor_value(EcuSensor::EngineChamberPressure)
+            .is_none_or(|chamber_pressure_pa| {
+                (chamber_pressure_pa - chamber_pressure_setpoint_pa).abs()
+                    > self.engine_config.engine_combustion_pressure_tolerance_pa
+            })
+    }
+
+    /// Only sends the duty when it changes so the local command queue isn't flooded
+    fn command_pump_duty(&mut self, ecu: &mut Ecu, pump_duty: f32) {
+        if !self.engine_config.use_pumps
+            || self
+                .commanded_pump_duty
+                .is_some_and(|commanded_duty| (commanded_duty - pump_duty).abs() < 1e-3)
+        {
+            return;
+        }
+
+        if ecu.enqueue_command(EcuCommand::SetPumpDuty((PumpType::FuelMain, pump_duty)))
+            && ecu.enqueue_command(EcuCommand::SetPumpDuty((PumpType::OxidizerMain, pump_duty)))
+        {
+            self.commanded_pump_duty = Some(pump_duty);
+        }
     }
 
-    fn received_shutdown_command(&self, packets: &[(NetworkAddress, Packet)]) -> bool {
-        for (_address, packet) in packets {
+    fn received_shutdown_command(&self, packets: &[(NetworkAddress, Packet)]) -> Option<usize> {
+        for (index, (_address, packet)) in packets.iter().enumerate() {
             if let Packet::EcuCommand(command) = packet {
                 if let EcuCommand::ShutdownEngine = command {
-                    return true;
+                    return Some(index);
                 }
             }
         }
 
-        false
+        None
     }
 
     fn engine_firing_timer_expired(&self) -> bool {
@@ -88,3 +127,168 @@ impl Firing {
                 .unwrap_or(f32::INFINITY)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use shared::{
+        alerts::is_condition_set,
+        ecu_hal::{
+            EcuAlert, EcuCommand, EcuConfig, EcuLinearOutput, EcuSensor, EngineConfig, EngineState,
+            ThrottlePoint, ThrottleProfile,
+        },
+        SensorData,
+    };
+
+    use crate::{test_fixture::EcuFixture, Ecu};
+
+    use super::Firing;
+
+    const THROTTLE_POINTS: [ThrottlePoint; 2] = [
+        ThrottlePoint {
+            time_s: 0.0,
+            chamber_pressure_pa: 1e6,
+            pump_duty: Some(0.5),
+        },
+        ThrottlePoint {
+            time_s: 1.0,
+            chamber_pressure_pa: 2e6,
+            pump_duty: Some(1.0),
+        },
+    ];
+
+    fn start_firing(ecu: &mut Ecu) -> EngineConfig {
+        let mut engine_config = EngineConfig::default();
+        engine_config.engine_combustion_pressure_tolerance_pa = 1e5;
+
+        let mut config = EcuConfig::default();
+        config.engine_config = Some(engine_config.clone());
+        ecu.configure_ecu(config);
+
+        for (index, point) in THROTTLE_POINTS.iter().enumerate() {
+            ecu.enqueue_command(EcuCommand::SetThrottleProfilePoint {
+                index: index as u8,
+                point: Some(*point),
+            });
+        }
+        ecu.update(0.001);
+
+        let mut engine = ecu.engine.take().unwrap();
+        engine.force_state(ecu, Firing::new(engine_config.clone()));
+        ecu.engine = Some(engine);
+
+        engine_config
+    }
+
+    fn set_chamber_pressure(ecu: &mut Ecu, pressure_pa: f32) {
+        ecu.update_sensor_data(
+            EcuSensor::EngineChamberPressure,
+            &SensorData::Pressure {
+                pressure_pa,
+                raw_data: 0,
+            },
+        );
+    }
+
+    #[test]
+    fn test_profile_interpolation() {
+        let mut profile = ThrottleProfile::new();
+        assert!(profile.setpoint(0.0).is_none());
+
+        profile.points[0] = Some(THROTTLE_POINTS[0]);
+        profile.points[1] = Some(THROTTLE_POINTS[1]);
+
+        assert_eq!(profile.setpoint(-1.0).unwrap().chamber_pressure_pa, 1e6);
+        assert_eq!(profile.setpoint(0.25).unwrap().chamber_pressure_pa, 1.25e6);
+        assert_eq!(profile.setpoint(0.25).unwrap().pump_duty, Some(0.625));
+        assert_eq!(profile.setpoint(5.0).unwrap().chamber_pressure_pa, 2e6);
+    }
+
+    #[test]
+    fn test_rejects_invalid_throttle_points() {
+        let mut fixture = EcuFixture::new();
+        let mut ecu = fixture.ecu();
+        ecu.configure_ecu(EcuConfig::default());
+
+        for (index, point) in THROTTLE_POINTS.iter().enumerate() {
+            ecu.enqueue_command(EcuCommand::SetThrottleProfilePoint {
+                index: index as u8,
+                point: Some(*point),
+            });
+        }
+        ecu.update(0.001);
+
+        let point_at = |time_s: f32, chamber_pressure_pa: f32| {
+            Some(ThrottlePoint {
+                time_s,
+                chamber_pressure_pa,
+                pump_duty: None,
+            })
+        };
+        let invalid_points = [
+            (2, point_at(0.5, 1e6)),
+            (2, point_at(1.0, 1e6)),
+            (2, point_at(2.0, f32::NAN)),
+            (2, point_at(f32::INFINITY, 1e6)),
+            (3, point_at(2.0, 1e6)),
+            (0, None),
+        ];
+        for (index, point) in invalid_points {
+            ecu.enqueue_command(EcuCommand::SetThrottleProfilePoint { index, point });
+            ecu.update(0.001);
+            assert_eq!(
+                &ecu.throttle_profile.points[..2],
+                &THROTTLE_POINTS.map(Some)
+            );
+            assert!(ecu.throttle_profile.points[2..].iter().all(Option::is_none));
+        }
+
+        ecu.enqueue_command(EcuCommand::SetThrottleProfilePoint {
+            index: 2,
+            point: point_at(2.0, 1e6),
+        });
+        ecu.update(0.001);
+        assert_eq!(ecu.throttle_profile.points[2], point_at(2.0, 1e6));
+    }
+
+    #[test]
+    fn test_follows_throttle_profile() {
+        let mut fixture = EcuFixture::new();
+        let mut ecu = fixture.ecu();
+        start_firing(&mut ecu);
+
+        // The profile can't be changed underneath a running engine
+        ecu.enqueue_command(EcuCommand::ClearThrottleProfile);
+
+        // Chamber pressure tracking the profile ramp, well away from the constant target
+        for i in 0..500 {
+            set_chamber_pressure(&mut ecu, 1e6 + i as f32 * 1e3);
+            ecu.update(0.001);
+            assert_eq!(ecu.engine_state(), EngineState::Firing);
+        }
+
+        let fuel_pump_duty = ecu.driver.get_linear_output(EcuLinearOutput::FuelPump);
+        let oxidizer_pump_duty = ecu.driver.get_linear_output(EcuLinearOutput::OxidizerPump);
+        assert!((fuel_pump_duty - 0.75).abs() < 0.01);
+        assert!((oxidizer_pump_duty - 0.75).abs() < 0.01);
+        assert_eq!(ecu.throttle_profile.points[1], Some(THROTTLE_POINTS[1]));
+    }
+
+    #[test]
+    fn test_degraded_against_profile_setpoint() {
+        let mut fixture = EcuFixture::new();
+        let mut ecu = fixture.ecu();
+        start_firing(&mut ecu);
+
+        // Stuck at the start of the ramp, so it falls out of tolerance once the setpoint moves on
+        set_chamber_pressure(&mut ecu, 1e6);
+        for _ in 0..200 {
+            ecu.update(0.001);
+        }
+
+        assert_eq!(ecu.engine_state(), EngineState::EngineShutdown);
+        assert!(is_condition_set(
+            ecu.alert_manager.get_condition_bitmask(),
+            EcuAlert::EngineChamberPressureOffNominal.into()
+        ));
+    }
+}
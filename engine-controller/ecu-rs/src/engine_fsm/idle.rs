@@ -1,5 +1,5 @@
 use shared::{
-    comms_hal::{NetworkAddress, Packet},
+    comms_hal::{CommandRejection, CommandResult, NetworkAddress, Packet},
     ecu_hal::{EcuAlert, EcuCommand, EngineConfig},
     ControllerState,
 };
@@ -19,8 +19,9 @@ impl<'f> ControllerState<EngineFsm, Ecu<'f>> for Idle {
         _dt: f32,
         packets: &[(NetworkAddress, Packet)],
     ) -> Option<EngineFsm> {
-        if self.received_fire_command(packets) {
+        if let Some(index) = self.received_fire_command(packets) {
             if fsm_tanks_pressurized(ecu) {
+                ecu.acknowledge_command(index, CommandResult::Accepted);
                 ecu.alert_manager
                     .clear_condition(EcuAlert::EngineTankOffNominal);
 
@@ -30,6 +31,10 @@ impl<'f> ControllerState<EngineFsm, Ecu<'f>> for Idle {
                     return Some(IgniterStartup::new(self.engine_config.clone()));
                 }
             } else {
+                ecu.acknowledge_command(
+                    index,
+                    CommandResult::Rejected(CommandRejection::TanksNotPressurized),
+                );
                 ecu.alert_manager
                     .set_condition(EcuAlert::EngineTankOffNominal);
             }
@@ -53,15 +58,15 @@ impl Idle {
         EngineFsm::Idle(Self { engine_config })
     }
 
-    fn received_fire_command(&self, packets: &[(NetworkAddress, Packet)]) -> bool {
-        for (_address, packet) in packets {
+    fn received_fire_command(&self, packets: &[(NetworkAddress, Packet)]) -> Option<usize> {
+        for (index, (_address, packet)) in packets.iter().enumerate() {
             if let Packet::EcuCommand(command) = packet {
                 if let EcuCommand::FireEngine = command {
-                    return true;
+                    return Some(index);
                 }
             }
         }
 
-        false
+        None
     }
 }
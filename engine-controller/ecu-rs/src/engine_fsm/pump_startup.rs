@@ -1,6 +1,8 @@
 use shared::{
     comms_hal::{NetworkAddress, Packet},
-    ecu_hal::{EcuAlert, EcuCommand, EngineConfig, PumpState, PumpType},
+    ecu_hal::{
+        EcuAlert, EcuCommand, EcuSensor, EngineConfig, PumpControlMode, PumpState, PumpType,
+    },
     ControllerState,
 };
 
@@ -41,8 +43,14 @@ impl<'f> ControllerState<EngineFsm, Ecu<'f>> for PumpStartup {
 
     fn enter_state(&mut self, ecu: &mut Ecu) {
         silprintln!("Entered engine pump startup state");
-        ecu.enqueue_command(EcuCommand::SetPumpDuty((PumpType::FuelMain, 1.0)));
-        ecu.enqueue_command(EcuCommand::SetPumpDuty((PumpType::OxidizerMain, 1.0)));
+        ecu.enqueue_command(EcuCommand::SetPumpControlMode((
+            PumpType::FuelMain,
+            PumpControlMode::ClosedLoop,
+        )));
+        ecu.enqueue_command(EcuCommand::SetPumpControlMode((
+            PumpType::OxidizerMain,
+            PumpControlMode::ClosedLoop,
+        )));
     }
 
     fn exit_state(&mut self, _ecu: &mut Ecu) {
@@ -72,20 +80,25 @@ impl PumpStartup {
                 .as_ref()
                 .map(|pump| pump.hal_state() == PumpState::Pumping)
                 .unwrap_or(false)
-            && (ecu.state_vector.sensor_data.fuel_pump_outlet_pressure_pa
-                - self.engine_config.fuel_injector_pressure_setpoint_pa)
-                .abs()
-                < self
-                    .engine_config
-                    .fuel_injector_startup_pressure_tolerance_pa
-            && (ecu
+            && ecu
+                .state_vector
+                .sensor_value(EcuSensor::FuelPumpOutletPressure)
+                .is_some_and(|outlet_pressure_pa| {
+                    (outlet_pressure_pa - self.engine_config.fuel_injector_pressure_setpoint_pa)
+                        .abs()
+                        < self
+                            .engine_config
+                            .fuel_injector_startup_pressure_tolerance_pa
+                })
+            && ecu
                 .state_vector
-                .sensor_data
-                .oxidizer_pump_outlet_pressure_pa
-                - self.engine_config.oxidizer_injector_pressure_setpoint_pa)
-                .abs()
-                < self
-                    .engine_config
-                    .oxidizer_injector_startup_pressure_tolerance_pa
+                .sensor_value(EcuSensor::OxidizerPumpOutletPressure)
+                .is_some_and(|outlet_pressure_pa| {
+                    (outlet_pressure_pa - self.engine_config.oxidizer_injector_pressure_setpoint_pa)
+                        .abs()
+                        < self
+                            .engine_config
+                            .oxidizer_injector_startup_pressure_tolerance_pa
+                })
     }
 }
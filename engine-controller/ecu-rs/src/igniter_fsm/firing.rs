@@ -1,6 +1,6 @@
 use shared::{
     comms_hal::{NetworkAddress, Packet},
-    ecu_hal::{EcuAlert, EcuBinaryOutput, IgniterConfig, TankState},
+    ecu_hal::{EcuAlert, EcuBinaryOutput, EcuSensor, IgniterConfig, TankState},
     ControllerState,
 };
 
@@ -28,7 +28,7 @@ impl<'f> ControllerState<IgniterFsm, Ecu<'f>> for Firing {
             return Some(Shutdown::new(self.igniter_config.clone()));
         }
 
-        if self.throat_too_hot() {
+        if self.throat_too_hot(ecu) {
             ecu.alert_manager
                 .set_condition(EcuAlert::IgniterThroatOverheat);
             return Some(Shutdown::new(self.igniter_config.clone()));
@@ -74,9 +74,53 @@ impl Firing {
         self.elapsed_time >= self.igniter_config.test_firing_duration_s
     }
 
-    fn throat_too_hot(&self) -> bool {
-        let igniter_throat_temp_max = 0.0; // TODO
+    fn throat_too_hot(&self, ecu: &Ecu) -> bool {
+        ecu.state_vector
+            .sensor_value(EcuSensor::IgniterThroatTemperature)
+            .is_some_and(|temperature_k| temperature_k >= self.igniter_config.max_throat_temp_k)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::{
+        alerts::is_condition_set,
+        ecu_hal::{EcuAlert, EcuConfig, EcuSensor, IgniterState},
+        SensorData,
+    };
+
+    use crate::test_fixture::EcuFixture;
+
+    use super::Firing;
+
+    #[test]
+    fn test_throat_overheat_shuts_down() {
+        let mut fixture = EcuFixture::new();
+        let mut ecu = fixture.ecu();
+        let config = EcuConfig::default();
+        let igniter_config = config.igniter_config.clone().unwrap();
+        ecu.configure_ecu(config);
+
+        let mut igniter = ecu.igniter.take().unwrap();
+        igniter.force_state(&mut ecu, Firing::new(igniter_config.clone()));
+        ecu.igniter = Some(igniter);
+
+        ecu.update(0.001);
+        assert_eq!(ecu.igniter_state(), IgniterState::Firing);
+
+        ecu.state_vector.update_sensor_data(
+            EcuSensor::IgniterThroatTemperature,
+            &SensorData::Temperature {
+                temperature_k: igniter_config.max_throat_temp_k + 1.0,
+                raw_data: 0,
+            },
+        );
+        ecu.update(0.001);
 
-        igniter_throat_temp_max >= self.igniter_config.max_throat_temp_k
+        assert_eq!(ecu.igniter_state(), IgniterState::Shutdown);
+        assert!(is_condition_set(
+            ecu.alert_manager.get_condition_bitmask(),
+            EcuAlert::IgniterThroatOverheat.into()
+        ));
     }
 }
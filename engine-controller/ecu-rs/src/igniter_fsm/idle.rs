@@ -1,5 +1,5 @@
 use shared::{
-    comms_hal::{NetworkAddress, Packet},
+    comms_hal::{CommandRejection, CommandResult, NetworkAddress, Packet},
     ecu_hal::{EcuAlert, EcuBinaryOutput, EcuCommand, IgniterConfig, TankState},
     ControllerState,
 };
@@ -19,13 +19,18 @@ impl<'f> ControllerState<IgniterFsm, Ecu<'f>> for Idle {
         _dt: f32,
         packets: &[(NetworkAddress, Packet)],
     ) -> Option<IgniterFsm> {
-        if self.received_fire_igniter(packets) {
+        if let Some(index) = self.received_fire_igniter(packets) {
             if self.tanks_pressurized(ecu) {
+                ecu.acknowledge_command(index, CommandResult::Accepted);
                 ecu.alert_manager
                     .clear_condition(EcuAlert::IgniterTankOffNominal);
 
                 return Some(Startup::new(self.igniter_config.clone()));
             } else {
+                ecu.acknowledge_command(
+                    index,
+                    CommandResult::Rejected(CommandRejection::TanksNotPressurized),
+                );
                 ecu.alert_manager
                     .set_condition(EcuAlert::IgniterTankOffNominal);
             }
@@ -52,16 +57,16 @@ impl Idle {
         IgniterFsm::Idle(Self { igniter_config })
     }
 
-    fn received_fire_igniter(&self, packets: &[(NetworkAddress, Packet)]) -> bool {
-        for (_address, packet) in packets {
+    fn received_fire_igniter(&self, packets: &[(NetworkAddress, Packet)]) -> Option<usize> {
+        for (index, (_address, packet)) in packets.iter().enumerate() {
             if let Packet::EcuCommand(command) = packet {
                 if let EcuCommand::FireIgniter = command {
-                    return true;
+                    return Some(index);
                 }
             }
         }
 
-        false
+        None
     }
 
     fn tanks_pressurized(&self, ecu: &Ecu) -> bool {
@@ -1,7 +1,7 @@
 use core::borrow::BorrowMut;
 
 use super::{idle::Idle, IgniterFsm};
-use crate::Ecu;
+use crate::{purge::PurgeRequester, Ecu};
 use shared::{
     comms_hal::{NetworkAddress, Packet},
     ecu_hal::{EcuBinaryOutput, IgniterConfig},
@@ -16,7 +16,7 @@ pub struct Shutdown {
 impl<'f> ControllerState<IgniterFsm, Ecu<'f>> for Shutdown {
     fn update<'a>(
         &mut self,
-        _ecu: &mut Ecu,
+        ecu: &mut Ecu,
         dt: f32,
         _packets: &[(NetworkAddress, Packet)],
     ) -> Option<IgniterFsm> {
@@ -26,19 +26,40 @@ impl<'f> ControllerState<IgniterFsm, Ecu<'f>> for Shutdown {
             return Some(Idle::new(self.igniter_config.clone()));
         }
 
+        if let Some(purge) = self.igniter_config.purge {
+            if self.elapsed_time >= purge.propellant_close_time_s() {
+                ecu.driver
+                    .set_binary_valve(EcuBinaryOutput::IgniterFuelValve, false);
+            }
+
+            ecu.request_purge(
+                PurgeRequester::Igniter,
+                purge.purge_open_at(self.elapsed_time),
+            );
+        }
+
         None
     }
 
     fn enter_state(&mut self, ecu: &mut Ecu) {
-        let driver = ecu.driver.borrow_mut();
+        match self.igniter_config.purge {
+            Some(purge) if purge.lead_s.is_some() => {
+                ecu.request_purge(PurgeRequester::Igniter, true);
+            }
+            _ => ecu
+                .driver
+                .set_binary_valve(EcuBinaryOutput::IgniterFuelValve, false),
+        }
 
-        driver.set_binary_valve(EcuBinaryOutput::IgniterFuelValve, false);
+        let driver = ecu.driver.borrow_mut();
         driver.set_binary_valve(EcuBinaryOutput::IgniterOxidizerValve, true);
         driver.set_sparking(false);
     }
 
-    fn exit_state(&mut self, _ecu: &mut Ecu) {
-        // Nothing
+    fn exit_state(&mut self, ecu: &mut Ecu) {
+        if self.igniter_config.purge.is_some() {
+            ecu.request_purge(PurgeRequester::Igniter, false);
+        }
     }
 }
 
@@ -50,7 +71,15 @@ impl Shutdown {
         })
     }
 
+    /// Like the engine, the shutdown duration counts from when the fuel valve closes
     fn shutdown_time_elapsed(&self) -> bool {
-        self.elapsed_time >= self.igniter_config.shutdown_duration_s
+        let end_time_s = match self.igniter_config.purge {
+            Some(purge) => (purge.propellant_close_time_s()
+                + self.igniter_config.shutdown_duration_s)
+                .max(purge.purge_close_time_s()),
+            None => self.igniter_config.shutdown_duration_s,
+        };
+
+        self.elapsed_time >= end_time_s
     }
 }
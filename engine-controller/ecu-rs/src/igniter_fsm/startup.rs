@@ -2,7 +2,7 @@ use core::borrow::BorrowMut;
 
 use shared::{
     comms_hal::{NetworkAddress, Packet},
-    ecu_hal::{EcuAlert, EcuBinaryOutput, IgniterConfig, TankState},
+    ecu_hal::{EcuAlert, EcuBinaryOutput, EcuSensor, IgniterConfig, TankState},
     ControllerState,
 };
 
@@ -38,7 +38,7 @@ impl<'f> ControllerState<IgniterFsm, Ecu<'f>> for Startup {
             return Some(Shutdown::new(self.igniter_config.clone()));
         }
 
-        if self.throat_too_hot() {
+        if self.throat_too_hot(ecu) {
             ecu.alert_manager
                 .set_condition(EcuAlert::IgniterThroatOverheat);
             return Some(Shutdown::new(self.igniter_config.clone()));
@@ -84,7 +84,10 @@ impl Startup {
     fn update_stable_pressure_timer(&mut self, ecu: &mut Ecu, dt: f32) {
         let startup_pressure_threshold_pa = self.igniter_config.startup_pressure_threshold_pa;
 
-        if ecu.state_vector.sensor_data.igniter_chamber_pressure_pa >= startup_pressure_threshold_pa
+        if ecu
+            .state_vector
+            .sensor_value(EcuSensor::IgniterChamberPressure)
+            .is_some_and(|chamber_pressure_pa| chamber_pressure_pa >= startup_pressure_threshold_pa)
         {
             self.stable_pressure_time += dt;
         } else {
@@ -96,10 +99,10 @@ impl Startup {
         self.startup_elapsed_time >= self.igniter_config.startup_timeout_s
     }
 
-    fn throat_too_hot(&self) -> bool {
-        let igniter_throat_temp_max = 0.0; // TODO
-
-        igniter_throat_temp_max >= self.igniter_config.max_throat_temp_k
+    fn throat_too_hot(&self, ecu: &Ecu) -> bool {
+        ecu.state_vector
+            .sensor_value(EcuSensor::IgniterThroatTemperature)
+            .is_some_and(|temperature_k| temperature_k >= self.igniter_config.max_throat_temp_k)
     }
 
     fn achieved_stable_pressure(&self) -> bool {
@@ -2,13 +2,27 @@
 #![deny(unsafe_code)]
 
 pub mod alert_watchdog;
+pub mod cavitation;
+pub mod command_ack;
+pub mod comms_watchdog;
 pub mod debug_info;
 pub mod ecu;
 pub mod engine_fsm;
 pub mod igniter_fsm;
+pub mod manual_mode;
+pub mod performance;
+pub mod pid;
 pub mod pump_fsm;
+pub mod purge;
+pub mod redlines;
+pub mod safing;
+pub mod sensor_health;
+pub mod sequence;
 pub mod state_vector;
 pub mod tank_fsm;
+#[cfg(test)]
+mod test_fixture;
+pub mod valve_monitor;
 
 pub use ecu::Ecu;
 
@@ -0,0 +1,339 @@
+use shared::{
+    comms_hal::{CommandRejection, CommandResult, NetworkAddress, Packet},
+    ecu_hal::{
+        BinaryOutputMask, EcuAlert, EcuBinaryOutput, EcuCommand, EcuLinearOutput, MAX_INTERLOCKS,
+    },
+};
+use strum::IntoEnumIterator;
+
+use crate::{silprintln, Ecu};
+
+/// The FSMs are left idle while outputs are driven directly by command
+pub struct ManualMode {
+    timeout_s: f32,
+    time_since_command_s: f32,
+}
+
+impl<'a> Ecu<'a> {
+    pub(crate) fn handle_manual_mode_commands(&mut self, packets: &[(NetworkAddress, Packet)]) {
+        for (index, (_remote, packet)) in packets.iter().enumerate() {
+            let command = match packet {
+                Packet::EcuCommand(command) => command,
+                _ => continue,
+            };
+
+            let result = match command {
+                EcuCommand::EnterManualMode { timeout_s } => self.enter_manual_mode(*timeout_s),
+                EcuCommand::ExitManualMode => {
+                    if self.manual_mode.is_none() {
+                        CommandResult::NotApplicable
+                    } else {
+                        self.exit_manual_mode();
+                        CommandResult::Accepted
+                    }
+                }
+                EcuCommand::SetInterlock { index, interlock } => {
+                    if self.manual_mode.is_some() {
+                        silprintln!("Ignoring interlock change while in manual mode");
+                        CommandResult::Rejected(CommandRejection::ManualModeActive)
+                    } else if let Some(element) = self.interlocks.get_mut(*index as usize) {
+                        *element = *interlock;
+                        CommandResult::Accepted
+                    } else {
+                        CommandResult::Rejected(CommandRejection::InvalidArgument)
+                    }
+                }
+                EcuCommand::ClearInterlocks => {
+                    if self.manual_mode.is_some() {
+                        silprintln!("Ignoring interlock change while in manual mode");
+                        CommandResult::Rejected(CommandRejection::ManualModeActive)
+                    } else {
+                        self.interlocks = [None; MAX_INTERLOCKS];
+                        CommandResult::Accepted
+                    }
+                }
+                EcuCommand::SetBinaryValve { valve, state } => {
+                    self.manual_set_binary_valve(*valve, *state)
+                }
+                EcuCommand::SetSparking(state) => self.manual_set_sparking(*state),
+                EcuCommand::SetLinearOutput { output, value } => {
+                    self.manual_set_linear_output(*output, *value)
+                }
+                _ => continue,
+            };
+
+            self.acknowledge_command(index, result);
+        }
+    }
+
+    pub(crate) fn update_manual_mode(&mut self, dt: f32) {
+        if let Some(manual_mode) = &mut self.manual_mode {
+            manual_mode.time_since_command_s += dt;
+
+            if manual_mode.time_since_command_s > manual_mode.timeout_s {
+                silprintln!("Manual mode timed out");
+                self.exit_manual_mode();
+            }
+        }
+    }
+
+    /// Every output goes back to off, which is where the idle FSMs left them
+    pub(crate) fn exit_manual_mode(&mut self) {
+        if self.manual_mode.take().is_none() {
+            return;
+        }
+
+        silprintln!("Exited manual mode");
+        for valve in EcuBinaryOutput::iter() {
+            self.driver.set_binary_valve(valve, false);
+        }
+        for output in EcuLinearOutput::iter() {
+            self.driver.set_linear_output(output, 0.0);
+        }
+        self.driver.set_sparking(false);
+
+        self.alert_manager
+            .clear_condition(EcuAlert::ManualModeEnabled);
+        self.alert_manager
+            .clear_condition(EcuAlert::ManualInterlockBlocked);
+    }
+
+    fn enter_manual_mode(&mut self, timeout_s: f32) -> CommandResult {
+        if self.manual_mode.is_some() {
+            return CommandResult::NotApplicable;
+        }
+
+        let rejection = if timeout_s <= 0.0 {
+            Some(CommandRejection::InvalidArgument)
+        } else if self.sequence.is_active() {
+            Some(CommandRejection::SequenceActive)
+        } else if !self.all_fsms_idle() {
+            Some(CommandRejection::NotIdle)
+        } else {
+            None
+        };
+
+        if let Some(rejection) = rejection {
+            silprintln!("Ignoring manual mode request: {:?}", rejection);
+            return CommandResult::Rejected(rejection);
+        }
+
+        silprintln!("Entered manual mode");
+        self.manual_mode = Some(ManualMode {
+            timeout_s,
+            time_since_command_s: 0.0,
+        });
+        self.alert_manager
+            .set_condition(EcuAlert::ManualModeEnabled);
+        CommandResult::Accepted
+    }
+
+    fn manual_set_binary_valve(&mut self, valve: EcuBinaryOutput, state: bool) -> CommandResult {
+        if !self.accept_manual_command() {
+            return CommandResult::NotApplicable;
+        }
+
+        let open_valves = if state {
+            self.open_valves().with(valve)
+        } else {
+            self.open_valves().without(valve)
+        };
+
+        if !self.check_interlocks(open_valves, self.driver.get_sparking()) {
+            return CommandResult::Rejected(CommandRejection::InterlockBlocked);
+        }
+
+        self.driver.set_binary_valve(valve, state);
+        CommandResult::Accepted
+    }
+
+    fn manual_set_sparking(&mut self, state: bool) -> CommandResult {
+        if !self.accept_manual_command() {
+            return CommandResult::NotApplicable;
+        }
+
+        if !self.check_interlocks(self.open_valves(), state) {
+            return CommandResult::Rejected(CommandRejection::InterlockBlocked);
+        }
+
+        self.driver.set_sparking(state);
+        CommandResult::Accepted
+    }
+
+    fn manual_set_linear_output(&mut self, output: EcuLinearOutput, value: f32) -> CommandResult {
+        if !self.accept_manual_command() {
+            return CommandResult::NotApplicable;
+        }
+
+        self.driver.set_linear_output(output, value.clamp(0.0, 1.0));
+        CommandResult::Accepted
+    }
+
+    fn accept_manual_command(&mut self) -> bool {
+        match &mut self.manual_mode {
+            Some(manual_mode) => {
+                manual_mode.time_since_command_s = 0.0;
+                true
+            }
+            None => {
+                silprintln!("Ignoring manual command outside of manual mode");
+                false
+            }
+        }
+    }
+
+    pub(crate) fn open_valves(&self) -> BinaryOutputMask {
+        EcuBinaryOutput::iter()
+            .filter(|valve| self.driver.get_binary_valve(*valve))
+            .fold(BinaryOutputMask::empty(), |mask, valve| mask.with(valve))
+    }
+
+    pub(crate) fn interlocks_allow(&self, open_valves: BinaryOutputMask, sparking: bool) -> bool {
+        self.interlocks.iter().flatten().all(|interlock| {
+            interlock.valves == BinaryOutputMask::empty()
+                || !open_valves.contains_all(interlock.valves)
+                || (interlock.allowed_while_sparking && sparking)
+        })
+    }
+
+    /// Whether the outputs would be allowed, raising an alert if they aren't
+    fn check_interlocks(&mut self, open_valves: BinaryOutputMask, sparking: bool) -> bool {
+        let allowed = self.interlocks_allow(open_valves, sparking);
+
+        if allowed {
+            self.alert_manager
+                .clear_condition(EcuAlert::ManualInterlockBlocked);
+        } else {
+            silprintln!("Manual command blocked by interlock");
+            self.alert_manager
+                .set_condition(EcuAlert::ManualInterlockBlocked);
+        }
+
+        allowed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::{
+        alerts::is_condition_set,
+        ecu_hal::{
+            BinaryOutputMask, EcuAlert, EcuBinaryOutput, EcuCommand, EcuConfig, EcuLinearOutput,
+            ValveInterlock,
+        },
+    };
+
+    use crate::{engine_fsm::firing::Firing, test_fixture::EcuFixture, Ecu};
+
+    fn command(ecu: &mut Ecu, command: EcuCommand) {
+        ecu.enqueue_command(command);
+        ecu.update(0.001);
+    }
+
+    fn open_valve(ecu: &mut Ecu, valve: EcuBinaryOutput) {
+        command(ecu, EcuCommand::SetBinaryValve { valve, state: true });
+    }
+
+    #[test]
+    fn test_manual_mode_interlocks() {
+        let mut fixture = EcuFixture::new();
+        let mut ecu = fixture.ecu();
+        ecu.configure_ecu(EcuConfig::default());
+
+        command(
+            &mut ecu,
+            EcuCommand::SetInterlock {
+                index: 0,
+                interlock: Some(ValveInterlock {
+                    valves: BinaryOutputMask::empty()
+                        .with(EcuBinaryOutput::EngineFuelValve)
+                        .with(EcuBinaryOutput::EngineOxidizerValve),
+                    allowed_while_sparking: true,
+                }),
+            },
+        );
+
+        // Nothing happens outside of manual mode
+        open_valve(&mut ecu, EcuBinaryOutput::EngineFuelValve);
+        assert!(!ecu
+            .driver
+            .get_binary_valve(EcuBinaryOutput::EngineFuelValve));
+
+        command(&mut ecu, EcuCommand::EnterManualMode { timeout_s: 1.0 });
+        assert!(ecu.manual_mode.is_some());
+
+        open_valve(&mut ecu, EcuBinaryOutput::EngineFuelValve);
+        open_valve(&mut ecu, EcuBinaryOutput::EngineOxidizerValve);
+        assert!(ecu
+            .driver
+            .get_binary_valve(EcuBinaryOutput::EngineFuelValve));
+        assert!(!ecu
+            .driver
+            .get_binary_valve(EcuBinaryOutput::EngineOxidizerValve));
+        assert!(is_condition_set(
+            ecu.alert_manager.get_condition_bitmask(),
+            EcuAlert::ManualInterlockBlocked.into()
+        ));
+
+        command(&mut ecu, EcuCommand::SetSparking(true));
+        open_valve(&mut ecu, EcuBinaryOutput::EngineOxidizerValve);
+        assert!(ecu
+            .driver
+            .get_binary_valve(EcuBinaryOutput::EngineOxidizerValve));
+
+        // The ignition source can't be taken away with both valves open
+        command(&mut ecu, EcuCommand::SetSparking(false));
+        assert!(ecu.driver.get_sparking());
+
+        command(
+            &mut ecu,
+            EcuCommand::SetLinearOutput {
+                output: EcuLinearOutput::FuelPump,
+                value: 2.0,
+            },
+        );
+        assert_eq!(ecu.driver.get_linear_output(EcuLinearOutput::FuelPump), 1.0);
+
+        command(&mut ecu, EcuCommand::ExitManualMode);
+        assert!(ecu.manual_mode.is_none());
+        assert!(!ecu
+            .driver
+            .get_binary_valve(EcuBinaryOutput::EngineFuelValve));
+        assert!(!ecu
+            .driver
+            .get_binary_valve(EcuBinaryOutput::EngineOxidizerValve));
+        assert!(!ecu.driver.get_sparking());
+        assert_eq!(ecu.driver.get_linear_output(EcuLinearOutput::FuelPump), 0.0);
+    }
+
+    #[test]
+    fn test_manual_mode_entry_and_timeout() {
+        let mut fixture = EcuFixture::new();
+        let mut ecu = fixture.ecu();
+        ecu.configure_ecu(EcuConfig::default());
+
+        let engine_config = ecu.config.engine_config.clone().unwrap();
+        let mut engine = ecu.engine.take().unwrap();
+        engine.force_state(&mut ecu, Firing::new(engine_config.clone()));
+        ecu.engine = Some(engine);
+
+        command(&mut ecu, EcuCommand::EnterManualMode { timeout_s: 0.1 });
+        assert!(ecu.manual_mode.is_none());
+
+        ecu.configure_ecu(EcuConfig::default());
+        command(&mut ecu, EcuCommand::EnterManualMode { timeout_s: 0.1 });
+        open_valve(&mut ecu, EcuBinaryOutput::FuelPurgeValve);
+        assert!(ecu.driver.get_binary_valve(EcuBinaryOutput::FuelPurgeValve));
+
+        for _ in 0..150 {
+            ecu.update(0.001);
+        }
+
+        assert!(ecu.manual_mode.is_none());
+        assert!(!ecu.driver.get_binary_valve(EcuBinaryOutput::FuelPurgeValve));
+        assert!(!is_condition_set(
+            ecu.alert_manager.get_condition_bitmask(),
+            EcuAlert::ManualModeEnabled.into()
+        ));
+    }
+}
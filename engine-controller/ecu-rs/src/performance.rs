@@ -0,0 +1,205 @@
+use libm::sqrtf;
+use shared::ecu_hal::{
+    EcuSensor, EnginePerformance, EnginePerformanceConfig, FUEL_STARVED_MIXTURE_RATIO,
+};
+
+use crate::Ecu;
+
+impl<'a> Ecu<'a> {
+    /// None without a performance config, or while any of the pressures it needs are faulted
+    pub fn engine_performance(&self) -> Option<EnginePerformance> {
+        let config = self.config.engine_config.as_ref()?.performance?;
+
+        Some(estimate_performance(
+            &config,
+            self.state_vector
+                .sensor_value(EcuSensor::EngineChamberPressure)?,
+            self.state_vector
+                .sensor_value(EcuSensor::EngineFuelInjectorPressure)?,
+            self.state_vector
+                .sensor_value(EcuSensor::EngineOxidizerInjectorPressure)?,
+        ))
+    }
+}
+
+/// Flow through each injector from its pressure drop, and c* and thrust from chamber pressure
+pub fn estimate_performance(
+    config: &EnginePerformanceConfig,
+    chamber_pressure_pa: f32,
+    fuel_injector_pressure_pa: f32,
+    oxidizer_injector_pressure_pa: f32,
+) -> EnginePerformance {
+    let fuel_mass_flow_kg_s = injector_mass_flow_kg_s(
+        config.fuel_injector_cda_m2,
+        config.fuel_density_kg_m3,
+        fuel_injector_pressure_pa - chamber_pressure_pa,
+    );
+    let oxidizer_mass_flow_kg_s = injector_mass_flow_kg_s(
+        config.oxidizer_injector_cda_m2,
+        config.oxidizer_density_kg_m3,
+        oxidizer_injector_pressure_pa - chamber_pressure_pa,
+    );
+    let total_mass_flow_kg_s = fuel_mass_flow_kg_s + oxidizer_mass_flow_kg_s;
+
+    EnginePerformance {
+        fuel_mass_flow_kg_s,
+        oxidizer_mass_flow_kg_s,
+        // Oxidizer without fuel still gets a ratio, so a max redline catches fuel starvation
+        mixture_ratio: (total_mass_flow_kg_s > 0.0).then(|| {
+            if fuel_mass_flow_kg_s > 0.0 {
+                oxidizer_mass_flow_kg_s / fuel_mass_flow_kg_s
+            } else {
+                FUEL_STARVED_MIXTURE_RATIO
+            }
+        }),
+        c_star_m_s: (total_mass_flow_kg_s > 0.0)
+            .then(|| chamber_pressure_pa * config.throat_area_m2 / total_mass_flow_kg_s),
+        thrust_n: config.thrust_coefficient * chamber_pressure_pa * config.throat_area_m2,
+    }
+}
+
+/// Incompressible orifice flow, with no reverse flow when the chamber is above the injector
+fn injector_mass_flow_kg_s(cda_m2: f32, density_kg_m3: f32, pressure_drop_pa: f32) -> f32 {
+    cda_m2 * sqrtf(2.0 * density_kg_m3 * pressure_drop_pa.max(0.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::{
+        alerts::is_condition_set,
+        ecu_hal::{
+            EcuAlert, EcuCommand, EcuConfig, EcuSensor, EnginePerformanceConfig, EngineState,
+            EngineStateMask, RedlineAction, RedlineConfig, RedlineInput,
+            FUEL_STARVED_MIXTURE_RATIO,
+        },
+        SensorData,
+    };
+
+    use super::estimate_performance;
+    use crate::{engine_fsm::firing::Firing, test_fixture::EcuFixture, Ecu};
+
+    const CONFIG: EnginePerformanceConfig = EnginePerformanceConfig {
+        fuel_injector_cda_m2: 1e-5,
+        oxidizer_injector_cda_m2: 1e-5,
+        fuel_density_kg_m3: 800.0,
+        oxidizer_density_kg_m3: 1250.0,
+        throat_area_m2: 5e-4,
+        thrust_coefficient: 1.5,
+    };
+
+    fn set_pressure(ecu: &mut Ecu, sensor: EcuSensor, pressure_pa: f32) {
+        ecu.state_vector.update_sensor_data(
+            sensor,
+            &SensorData::Pressure {
+                pressure_pa,
+                raw_data: 0,
+            },
+        );
+    }
+
+    fn assert_near(value: f32, expected: f32) {
+        assert!(
+            (value - expected).abs() < expected.abs() * 1e-3,
+            "{} != {}",
+            value,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_estimate_performance() {
+        let performance = estimate_performance(&CONFIG, 2e6, 3e6, 3e6);
+        assert_near(performance.fuel_mass_flow_kg_s, 0.4);
+        assert_near(performance.oxidizer_mass_flow_kg_s, 0.5);
+        assert_near(performance.mixture_ratio.unwrap(), 1.25);
+        assert_near(performance.c_star_m_s.unwrap(), 2e6 * 5e-4 / 0.9);
+        assert_near(performance.thrust_n, 1500.0);
+
+        // No flow with the chamber above the fuel injector
+        let performance = estimate_performance(&CONFIG, 2e6, 1e6, 3e6);
+        assert_eq!(performance.fuel_mass_flow_kg_s, 0.0);
+        assert_eq!(performance.mixture_ratio, Some(FUEL_STARVED_MIXTURE_RATIO));
+        assert!(performance.c_star_m_s.is_some());
+
+        let performance = estimate_performance(&CONFIG, 2e6, 1e6, 1e6);
+        assert_eq!(performance.mixture_ratio, None);
+        assert_eq!(performance.c_star_m_s, None);
+    }
+
+    fn fire_with_mixture_ratio_redline(ecu: &mut Ecu) {
+        let mut config = EcuConfig::default();
+        let engine_config = config.engine_config.as_mut().unwrap();
+        engine_config.performance = Some(CONFIG);
+        // Keep the firing state's own chamber pressure check out of the way
+        engine_config.engine_combustion_pressure_tolerance_pa = 1e9;
+        ecu.configure_ecu(config.clone());
+
+        ecu.enqueue_command(EcuCommand::SetRedline {
+            index: 0,
+            redline: Some(RedlineConfig {
+                input: RedlineInput::MixtureRatio,
+                min: Some(0.5),
+                max: Some(1.0),
+                persistence_s: 0.01,
+                engine_states: EngineStateMask::empty().with(EngineState::Firing),
+                action: RedlineAction::ShutdownEngine,
+            }),
+        });
+        ecu.update(0.001);
+
+        let mut engine = ecu.engine.take().unwrap();
+        engine.force_state(ecu, Firing::new(config.engine_config.unwrap()));
+        ecu.engine = Some(engine);
+    }
+
+    fn mixture_ratio_redline_tripped(ecu: &mut Ecu) -> bool {
+        is_condition_set(
+            ecu.alert_manager.get_condition_bitmask(),
+            EcuAlert::RedlineMixtureRatio.into(),
+        )
+    }
+
+    #[test]
+    fn test_mixture_ratio_redline() {
+        let mut fixture = EcuFixture::new();
+        let mut ecu = fixture.ecu();
+        fire_with_mixture_ratio_redline(&mut ecu);
+        assert_eq!(ecu.engine_performance(), None);
+
+        set_pressure(&mut ecu, EcuSensor::EngineChamberPressure, 2e6);
+        set_pressure(&mut ecu, EcuSensor::EngineFuelInjectorPressure, 3e6);
+        set_pressure(&mut ecu, EcuSensor::EngineOxidizerInjectorPressure, 2.5e6);
+        for _ in 0..20 {
+            ecu.update(0.001);
+        }
+        assert_eq!(ecu.engine_state(), EngineState::Firing);
+        assert!(ecu
+            .generate_telemetry_frame()
+            .engine_performance
+            .is_some_and(|performance| performance.mixture_ratio.unwrap() < 1.0));
+
+        set_pressure(&mut ecu, EcuSensor::EngineOxidizerInjectorPressure, 3e6);
+        for _ in 0..20 {
+            ecu.update(0.001);
+        }
+        assert!(mixture_ratio_redline_tripped(&mut ecu));
+        assert_eq!(ecu.engine_state(), EngineState::EngineShutdown);
+    }
+
+    #[test]
+    fn test_fuel_starvation_trips_mixture_ratio_redline() {
+        let mut fixture = EcuFixture::new();
+        let mut ecu = fixture.ecu();
+        fire_with_mixture_ratio_redline(&mut ecu);
+
+        // Fuel injector below the chamber, so only oxidizer is flowing
+        set_pressure(&mut ecu, EcuSensor::EngineChamberPressure, 2e6);
+        set_pressure(&mut ecu, EcuSensor::EngineFuelInjectorPressure, 1.5e6);
+        set_pressure(&mut ecu, EcuSensor::EngineOxidizerInjectorPressure, 3e6);
+        for _ in 0..20 {
+            ecu.update(0.001);
+        }
+        assert!(mixture_ratio_redline_tripped(&mut ecu));
+        assert_eq!(ecu.engine_state(), EngineState::EngineShutdown);
+    }
+}
@@ -0,0 +1,130 @@
+use shared::ecu_hal::PidConfig;
+
+#[derive(Debug, Clone)]
+pub struct PidController {
+    config: PidConfig,
+    integral: f32,
+    last_measurement: Option<f32>,
+    output: f32,
+}
+
+impl PidController {
+    /// The integral starts at the initial output so switching from open loop is bumpless
+    pub fn new(config: PidConfig, initial_output: f32) -> Self {
+        let initial_output = initial_output.clamp(config.output_min, config.output_max);
+
+        Self {
+            config,
+            integral: initial_output,
+            last_measurement: None,
+            output: initial_output,
+        }
+    }
+
+    pub fn update(&mut self, setpoint: f32, measurement: f32, dt: f32) -> f32 {
+        if dt <= 0.0 {
+            return self.output;
+        }
+
+        let error = setpoint - measurement;
+        let proportional = self.config.kp * error;
+
+        // Derivative on measurement so setpoint changes don't kick the output
+        let derivative = self
+            .last_measurement
+            .map_or(0.0, |last| -self.config.kd * (measurement - last) / dt);
+        self.last_measurement = Some(measurement);
+
+        // Anti-windup, stop integrating while the output is saturated in the direction the
+        // error is pushing it
+        let integral = self.integral + self.config.ki * error * dt;
+        let unclamped_output = proportional + integral + derivative;
+        let saturated_high = unclamped_output > self.config.output_max && error > 0.0;
+        let saturated_low = unclamped_output < self.config.output_min && error < 0.0;
+
+        if !saturated_high && !saturated_low {
+            self.integral = integral;
+        }
+
+        let output = (proportional + self.integral + derivative)
+            .clamp(self.config.output_min, self.config.output_max);
+
+        let max_step = self.config.max_output_rate_per_s * dt;
+        self.output = output.clamp(self.output - max_step, self.output + max_step);
+
+        self.output
+    }
+
+    pub fn output(&self) -> f32 {
+        self.output
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: PidConfig = PidConfig {
+        kp: 0.1,
+        ki: 0.05,
+        kd: 0.0,
+        output_min: 0.0,
+        output_max: 1.0,
+        max_output_rate_per_s: 1000.0,
+    };
+
+    #[test]
+    fn test_output_clamped() {
+        let mut pid = PidController::new(CONFIG, 0.0);
+
+        assert_eq!(pid.update(1000.0, 0.0, 0.01), 1.0);
+        assert_eq!(pid.update(-1000.0, 0.0, 0.01), 0.0);
+    }
+
+    #[test]
+    fn test_rate_limited() {
+        let config = PidConfig {
+            max_output_rate_per_s: 2.0,
+            ..CONFIG
+        };
+        let mut pid = PidController::new(config, 0.0);
+
+        assert!((pid.update(1000.0, 0.0, 0.01) - 0.02).abs() < 1e-6);
+        assert!((pid.update(1000.0, 0.0, 0.01) - 0.04).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_anti_windup() {
+        let mut pid = PidController::new(CONFIG, 0.0);
+
+        // Saturate for a long time, then the error flips sign and the output should come off
+        // the limit straight away instead of waiting for a wound up integral to unwind
+        for _ in 0..1000 {
+            pid.update(100.0, 0.0, 0.01);
+        }
+        assert_eq!(pid.output(), 1.0);
+
+        assert!(pid.update(0.0, 1.0, 0.01) < 1.0);
+    }
+
+    #[test]
+    fn test_converges_to_setpoint() {
+        let mut pid = PidController::new(CONFIG, 0.0);
+        let mut pressure = 0.0;
+
+        // A plant where pressure lags behind 10x the duty cycle
+        for _ in 0..10000 {
+            let duty = pid.update(5.0, pressure, 0.01);
+            pressure += (duty * 10.0 - pressure) * 0.05;
+        }
+
+        assert!((pressure - 5.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_bumpless_start() {
+        let mut pid = PidController::new(CONFIG, 0.6);
+
+        assert!((pid.update(5.0, 5.0, 0.01) - 0.6).abs() < 1e-6);
+    }
+}
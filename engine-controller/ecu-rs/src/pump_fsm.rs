@@ -1,4 +1,7 @@
-use shared::{ecu_hal::PumpState, ControllerFsm, ControllerState};
+use shared::{
+    ecu_hal::{EcuSensor, PidConfig, PumpState, PumpType},
+    ControllerFsm, ControllerState,
+};
 
 use crate::Ecu;
 
@@ -11,6 +14,13 @@ pub enum PumpFsm {
     Pumping(pumping::Pumping),
 }
 
+/// What closed loop control regulates the pump outlet pressure to, and how
+#[derive(Debug, Clone, Copy)]
+pub struct PumpControlConfig {
+    pub setpoint_pa: f32,
+    pub pid_config: PidConfig,
+}
+
 impl<'a> ControllerFsm<PumpFsm, Ecu<'a>, PumpState> for PumpFsm {
     fn to_controller_state(&mut self) -> &mut dyn ControllerState<PumpFsm, Ecu<'a>> {
         match self {
@@ -26,3 +36,14 @@ impl<'a> ControllerFsm<PumpFsm, Ecu<'a>, PumpState> for PumpFsm {
         }
     }
 }
+
+fn pump_outlet_pressure(ecu: &Ecu, pump_type: PumpType) -> Option<f32> {
+    match pump_type {
+        PumpType::FuelMain => ecu
+            .state_vector
+            .sensor_value(EcuSensor::FuelPumpOutletPressure),
+        PumpType::OxidizerMain => ecu
+            .state_vector
+            .sensor_value(EcuSensor::OxidizerPumpOutletPressure),
+    }
+}
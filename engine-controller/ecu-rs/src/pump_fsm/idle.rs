@@ -1,31 +1,52 @@
 use crate::Ecu;
 use shared::{
-    comms_hal::{NetworkAddress, Packet},
-    ecu_hal::{EcuCommand, EcuLinearOutput, PumpType},
+    comms_hal::{CommandResult, NetworkAddress, Packet},
+    ecu_hal::{EcuCommand, EcuLinearOutput, PumpControlMode, PumpType},
     ControllerState,
 };
 
-use super::{pumping::Pumping, PumpFsm};
+use super::{pumping::Pumping, PumpControlConfig, PumpFsm};
 
 #[derive(Debug)]
 pub struct Idle {
     pump_type: PumpType,
     linear_output: EcuLinearOutput,
+    control_config: PumpControlConfig,
 }
 
 impl<'f> ControllerState<PumpFsm, Ecu<'f>> for Idle {
     fn update<'a>(
         &mut self,
-        _ecu: &mut Ecu,
+        ecu: &mut Ecu,
         _dt: f32,
         packets: &[(NetworkAddress, Packet)],
     ) -> Option<PumpFsm> {
-        if let Some(duty) = self.received_pump_command(packets) {
+        if let Some((index, duty)) = self.received_pump_command(packets) {
             if duty > 0.01 {
-                return Some(Pumping::new(self.pump_type, self.linear_output, duty));
+                ecu.acknowledge_command(index, CommandResult::Accepted);
+                return Some(Pumping::new(
+                    self.pump_type,
+                    self.linear_output,
+                    self.control_config,
+                    duty,
+                    PumpControlMode::OpenLoop,
+                ));
             }
         }
 
+        if let Some((index, PumpControlMode::ClosedLoop)) =
+            self.received_control_mode_command(packets)
+        {
+            ecu.acknowledge_command(index, CommandResult::Accepted);
+            return Some(Pumping::new(
+                self.pump_type,
+                self.linear_output,
+                self.control_config,
+                0.0,
+                PumpControlMode::ClosedLoop,
+            ));
+        }
+
         None
     }
 
@@ -39,19 +60,24 @@ impl<'f> ControllerState<PumpFsm, Ecu<'f>> for Idle {
 }
 
 impl Idle {
-    pub fn new(pump_type: PumpType, linear_output: EcuLinearOutput) -> PumpFsm {
+    pub fn new(
+        pump_type: PumpType,
+        linear_output: EcuLinearOutput,
+        control_config: PumpControlConfig,
+    ) -> PumpFsm {
         PumpFsm::Idle(Self {
             pump_type,
             linear_output,
+            control_config,
         })
     }
 
-    fn received_pump_command(&self, packets: &[(NetworkAddress, Packet)]) -> Option<f32> {
-        for (_address, packet) in packets {
+    fn received_pump_command(&self, packets: &[(NetworkAddress, Packet)]) -> Option<(usize, f32)> {
+        for (index, (_address, packet)) in packets.iter().enumerate() {
             if let Packet::EcuCommand(command) = packet {
                 if let EcuCommand::SetPumpDuty((pump, duty)) = command {
                     if *pump == self.pump_type {
-                        return Some(*duty);
+                        return Some((index, *duty));
                     }
                 }
             }
@@ -59,4 +85,19 @@ impl Idle {
 
         None
     }
+
+    fn received_control_mode_command(
+        &self,
+        packets: &[(NetworkAddress, Packet)],
+    ) -> Option<(usize, PumpControlMode)> {
+        for (index, (_address, packet)) in packets.iter().enumerate() {
+            if let Packet::EcuCommand(EcuCommand::SetPumpControlMode((pump, mode))) = packet {
+                if *pump == self.pump_type {
+                    return Some((index, *mode));
+                }
+            }
+        }
+
+        None
+    }
 }
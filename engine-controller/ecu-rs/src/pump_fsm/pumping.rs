@@ -1,36 +1,70 @@
-use crate::Ecu;
+use crate::{pid::PidController, Ecu};
 use shared::{
-    comms_hal::{NetworkAddress, Packet},
-    ecu_hal::{EcuCommand, EcuLinearOutput, PumpType},
+    comms_hal::{CommandResult, NetworkAddress, Packet},
+    ecu_hal::{EcuCommand, EcuLinearOutput, PumpControlMode, PumpType},
     ControllerState,
 };
 
-use super::{idle::Idle, PumpFsm};
+use super::{idle::Idle, pump_outlet_pressure, PumpControlConfig, PumpFsm};
 
 #[derive(Debug)]
 pub struct Pumping {
     pump_type: PumpType,
     linear_output: EcuLinearOutput,
+    control_config: PumpControlConfig,
     duty_cycle: f32,
+    // Only Some while in closed loop control
+    pid: Option<PidController>,
 }
 
 impl<'f> ControllerState<PumpFsm, Ecu<'f>> for Pumping {
     fn update<'a>(
         &mut self,
         ecu: &mut Ecu,
-        _dt: f32,
+        dt: f32,
         packets: &[(NetworkAddress, Packet)],
     ) -> Option<PumpFsm> {
-        if let Some(duty) = self.received_pump_command(packets) {
+        if let Some((index, duty)) = self.received_pump_command(packets) {
+            ecu.acknowledge_command(index, CommandResult::Accepted);
+
             if duty > 0.01 {
+                // Setting a duty cycle always drops back to open loop
                 self.duty_cycle = duty;
-                ecu.driver
-                    .set_linear_output(self.linear_output, self.duty_cycle);
+                self.pid = None;
             } else {
-                return Some(Idle::new(self.pump_type, self.linear_output));
+                return Some(Idle::new(
+                    self.pump_type,
+                    self.linear_output,
+                    self.control_config,
+                ));
+            }
+        }
+
+        match self.received_control_mode_command(packets) {
+            Some((index, PumpControlMode::OpenLoop)) if self.pid.is_some() => {
+                ecu.acknowledge_command(index, CommandResult::Accepted);
+                self.pid = None;
+            }
+            Some((index, PumpControlMode::ClosedLoop)) if self.pid.is_none() => {
+                ecu.acknowledge_command(index, CommandResult::Accepted);
+                self.pid = Some(PidController::new(
+                    self.control_config.pid_config,
+                    self.duty_cycle,
+                ));
             }
+            _ => {}
         }
 
+        // Holds the last duty cycle while the outlet pressure sensor is faulted
+        if let (Some(pid), Some(outlet_pressure_pa)) =
+            (&mut self.pid, pump_outlet_pressure(ecu, self.pump_type))
+        {
+            self.duty_cycle = pid.update(self.control_config.setpoint_pa, outlet_pressure_pa, dt);
+        }
+
+        ecu.driver
+            .set_linear_output(self.linear_output, self.duty_cycle);
+
         None
     }
 
@@ -45,20 +79,35 @@ impl<'f> ControllerState<PumpFsm, Ecu<'f>> for Pumping {
 }
 
 impl Pumping {
-    pub fn new(pump_type: PumpType, linear_output: EcuLinearOutput, duty_cycle: f32) -> PumpFsm {
+    pub fn new(
+        pump_type: PumpType,
+        linear_output: EcuLinearOutput,
+        control_config: PumpControlConfig,
+        duty_cycle: f32,
+        control_mode: PumpControlMode,
+    ) -> PumpFsm {
+        let pid = match control_mode {
+            PumpControlMode::OpenLoop => None,
+            PumpControlMode::ClosedLoop => {
+                Some(PidController::new(control_config.pid_config, duty_cycle))
+            }
+        };
+
         PumpFsm::Pumping(Self {
             pump_type,
             linear_output,
+            control_config,
             duty_cycle,
+            pid,
         })
     }
 
-    fn received_pump_command(&self, packets: &[(NetworkAddress, Packet)]) -> Option<f32> {
-        for (_address, packet) in packets {
+    fn received_pump_command(&self, packets: &[(NetworkAddress, Packet)]) -> Option<(usize, f32)> {
+        for (index, (_address, packet)) in packets.iter().enumerate() {
             if let Packet::EcuCommand(command) = packet {
                 if let EcuCommand::SetPumpDuty((pump, duty)) = command {
                     if *pump == self.pump_type {
-                        return Some(*duty);
+                        return Some((index, *duty));
                     }
                 }
             }
@@ -66,4 +115,19 @@ impl Pumping {
 
         None
     }
+
+    fn received_control_mode_command(
+        &self,
+        packets: &[(NetworkAddress, Packet)],
+    ) -> Option<(usize, PumpControlMode)> {
+        for (index, (_address, packet)) in packets.iter().enumerate() {
+            if let Packet::EcuCommand(EcuCommand::SetPumpControlMode((pump, mode))) = packet {
+                if *pump == self.pump_type {
+                    return Some((index, *mode));
+                }
+            }
+        }
+
+        None
+    }
 }
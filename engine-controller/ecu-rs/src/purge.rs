@@ -0,0 +1,103 @@
+use shared::ecu_hal::EcuBinaryOutput;
+
+use crate::Ecu;
+
+/// The FSMs that can purge through FuelPurgeValve when they shut down
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PurgeRequester {
+    Engine,
+    Igniter,
+}
+
+/// The engine and igniter share the purge valve, so it stays open while either of them wants it
+pub struct PurgeRequests {
+    engine: bool,
+    igniter: bool,
+}
+
+impl PurgeRequests {
+    pub const fn new() -> Self {
+        Self {
+            engine: false,
+            igniter: false,
+        }
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.engine || self.igniter
+    }
+}
+
+impl Default for PurgeRequests {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Ecu<'a> {
+    pub(crate) fn request_purge(&mut self, requester: PurgeRequester, open: bool) {
+        match requester {
+            PurgeRequester::Engine => self.purge_requests.engine = open,
+            PurgeRequester::Igniter => self.purge_requests.igniter = open,
+        }
+
+        self.driver.set_binary_valve(
+            EcuBinaryOutput::FuelPurgeValve,
+            self.purge_requests.is_requested(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::ecu_hal::{EcuBinaryOutput, EcuConfig, EngineState, IgniterState, PurgeConfig};
+
+    use crate::{
+        engine_fsm::engine_shutdown::EngineShutdown,
+        igniter_fsm::shutdown::Shutdown,
+        test_fixture::{run, EcuFixture},
+    };
+
+    fn purge_for(duration_s: f32) -> Option<PurgeConfig> {
+        Some(PurgeConfig {
+            lead_s: None,
+            delay_s: 0.0,
+            duration_s,
+        })
+    }
+
+    #[test]
+    fn test_engine_and_igniter_purge_together() {
+        for (engine_purge_s, igniter_purge_s) in [(0.6, 1.0), (1.0, 0.6)] {
+            let mut fixture = EcuFixture::new();
+            let mut ecu = fixture.ecu();
+
+            let mut config = EcuConfig::default();
+            let engine_config = config.engine_config.as_mut().unwrap();
+            engine_config.purge = purge_for(engine_purge_s);
+            let engine_config = engine_config.clone();
+            let igniter_config = config.igniter_config.as_mut().unwrap();
+            igniter_config.purge = purge_for(igniter_purge_s);
+            let igniter_config = igniter_config.clone();
+            ecu.configure_ecu(config);
+
+            let mut engine = ecu.engine.take().unwrap();
+            engine.force_state(&mut ecu, EngineShutdown::new(engine_config));
+            ecu.engine = Some(engine);
+            let mut igniter = ecu.igniter.take().unwrap();
+            igniter.force_state(&mut ecu, Shutdown::new(igniter_config));
+            ecu.igniter = Some(igniter);
+
+            // The first one to finish mustn't cut the other's purge short
+            for _ in 0..950 {
+                ecu.update(0.001);
+                assert!(ecu.driver.get_binary_valve(EcuBinaryOutput::FuelPurgeValve));
+            }
+
+            run(&mut ecu, 0.1);
+            assert!(!ecu.driver.get_binary_valve(EcuBinaryOutput::FuelPurgeValve));
+            assert_eq!(ecu.engine_state(), EngineState::Idle);
+            assert_eq!(ecu.igniter_state(), IgniterState::Idle);
+        }
+    }
+}
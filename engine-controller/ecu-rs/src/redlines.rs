@@ -0,0 +1,319 @@
+use shared::ecu_hal::{EcuAlert, RedlineAction, RedlineConfig, RedlineInput, MAX_REDLINES};
+
+use crate::{silprintln, Ecu};
+
+pub struct Redlines {
+    configs: [Option<RedlineConfig>; MAX_REDLINES],
+    time_violated_s: [f32; MAX_REDLINES],
+    tripped: [bool; MAX_REDLINES],
+    /// Latched once the redline trips, as its alert is, until the redline is set again
+    alerted: [bool; MAX_REDLINES],
+}
+
+impl Redlines {
+    pub const fn new() -> Self {
+        Self {
+            configs: [None; MAX_REDLINES],
+            time_violated_s: [0.0; MAX_REDLINES],
+            tripped: [false; MAX_REDLINES],
+            alerted: [false; MAX_REDLINES],
+        }
+    }
+
+    pub fn get(&self, index: usize) -> Option<RedlineConfig> {
+        self.configs.get(index).copied().flatten()
+    }
+
+    fn set(&mut self, index: usize, redline: Option<RedlineConfig>) {
+        if index < MAX_REDLINES {
+            self.configs[index] = redline;
+            self.time_violated_s[index] = 0.0;
+            self.tripped[index] = false;
+            self.alerted[index] = false;
+        }
+    }
+
+    /// Alerts are per input, so one stays set while any redline on that input has raised it
+    fn alerted_on(&self, input: RedlineInput) -> bool {
+        (0..MAX_REDLINES).any(|index| {
+            self.alerted[index]
+                && self
+                    .get(index)
+                    .is_some_and(|redline| redline.input == input)
+        })
+    }
+}
+
+impl Default for Redlines {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Ecu<'a> {
+    pub(crate) fn set_redline(&mut self, index: u8, redline: Option<RedlineConfig>) {
+        let old_redline = self.redlines.get(index as usize);
+        self.redlines.set(index as usize, redline);
+
+        if let Some(old_redline) = old_redline {
+            if !self.redlines.alerted_on(old_redline.input) {
+                self.alert_manager
+                    .clear_condition(EcuAlert::redline(old_redline.input));
+            }
+        }
+    }
+
+    pub(crate) fn clear_redlines(&mut self) {
+        for index in 0..MAX_REDLINES {
+            self.set_redline(index as u8, None);
+        }
+    }
+
+    /// A tripped redline's alert stays set so the cause of an abort is visible afterwards,
+    /// but the redline can trip again once the sensor is back inside its limits
+    pub(crate) fn update_redlines(&mut self, dt: f32) {
+        let engine_state = self.engine_state();
+
+        for index in 0..MAX_REDLINES {
+            let redline = match self.redlines.configs[index] {
+                Some(redline) => redline,
+                None => continue,
+            };
+
+            let violated = redline.engine_states.contains(engine_state)
+                && self
+                    .redline_input_value(redline.input)
+                    .is_some_and(|value| {
+                        redline.min.is_some_and(|min| value < min)
+                            || redline.max.is_some_and(|max| value > max)
+                    });
+
+            if !violated {
+                self.redlines.time_violated_s[index] = 0.0;
+                self.redlines.tripped[index] = false;
+                continue;
+            }
+
+            self.redlines.time_violated_s[index] += dt;
+
+            if self.redlines.tripped[index]
+                || self.redlines.time_violated_s[index] < redline.persistence_s
+            {
+                continue;
+            }
+
+            silprintln!("Redline tripped on {:?}", redline.input);
+            self.redlines.tripped[index] = true;
+            self.redlines.alerted[index] = true;
+            self.alert_manager
+                .set_condition(EcuAlert::redline(redline.input));
+
+            match redline.action {
+                RedlineAction::Alert => {}
+                RedlineAction::ShutdownEngine => self.shutdown_engine(),
+                RedlineAction::SafeEcu => self.safe_ecu(self.config.safe_tank_action),
+            }
+        }
+    }
+
+    fn redline_input_value(&self, input: RedlineInput) -> Option<f32> {
+        match input {
+            RedlineInput::Sensor(sensor) => self.state_vector.sensor_value(sensor),
+            RedlineInput::MixtureRatio => self
+                .engine_performance()
+                .and_then(|performance| performance.mixture_ratio),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::{
+        alerts::is_condition_set,
+        ecu_hal::{
+            EcuAlert, EcuBinaryOutput, EcuCommand, EcuConfig, EcuSensor, EngineState,
+            EngineStateMask, RedlineAction, RedlineConfig, RedlineInput, SafeTankAction,
+            TankConfig, TankState, TankType,
+        },
+        SensorData,
+    };
+
+    use crate::{engine_fsm::firing::Firing, test_fixture::EcuFixture, Ecu};
+
+    fn set_chamber_pressure(ecu: &mut Ecu, pressure_pa: f32) {
+        ecu.state_vector.update_sensor_data(
+            EcuSensor::EngineChamberPressure,
+            &SensorData::Pressure {
+                pressure_pa,
+                raw_data: 0,
+            },
+        );
+    }
+
+    fn configure(ecu: &mut Ecu, config: EcuConfig, action: RedlineAction) {
+        ecu.configure_ecu(config);
+        ecu.enqueue_command(EcuCommand::SetRedline {
+            index: 3,
+            redline: Some(RedlineConfig {
+                input: RedlineInput::Sensor(EcuSensor::EngineChamberPressure),
+                min: None,
+                max: Some(5e6),
+                persistence_s: 0.01,
+                engine_states: EngineStateMask::empty().with(EngineState::Firing),
+                action,
+            }),
+        });
+        ecu.update(0.001);
+    }
+
+    fn force_firing(ecu: &mut Ecu) {
+        // Keep the firing state's own chamber pressure check out of the way
+        let mut engine_config = ecu.config.engine_config.clone().unwrap();
+        engine_config.engine_combustion_pressure_tolerance_pa = 1e9;
+        let mut engine = ecu.engine.take().unwrap();
+        engine.force_state(ecu, Firing::new(engine_config));
+        ecu.engine = Some(engine);
+    }
+
+    fn redline_tripped(ecu: &mut Ecu) -> bool {
+        is_condition_set(
+            ecu.alert_manager.get_condition_bitmask(),
+            EcuAlert::RedlineEngineChamberPressure.into(),
+        )
+    }
+
+    #[test]
+    fn test_redline_only_applies_in_engine_states() {
+        let mut fixture = EcuFixture::new();
+        let mut ecu = fixture.ecu();
+        configure(&mut ecu, EcuConfig::default(), RedlineAction::Alert);
+
+        set_chamber_pressure(&mut ecu, 6e6);
+        for _ in 0..100 {
+            ecu.update(0.001);
+        }
+
+        assert_eq!(ecu.engine_state(), EngineState::Idle);
+        assert!(!redline_tripped(&mut ecu));
+    }
+
+    #[test]
+    fn test_redline_persistence_and_abort() {
+        let mut fixture = EcuFixture::new();
+        let mut ecu = fixture.ecu();
+        configure(
+            &mut ecu,
+            EcuConfig::default(),
+            RedlineAction::ShutdownEngine,
+        );
+        force_firing(&mut ecu);
+
+        // A glitch shorter than the persistence time is ignored
+        set_chamber_pressure(&mut ecu, 6e6);
+        for _ in 0..5 {
+            ecu.update(0.001);
+        }
+        set_chamber_pressure(&mut ecu, 2e6);
+        ecu.update(0.001);
+        assert!(!redline_tripped(&mut ecu));
+        assert_eq!(ecu.engine_state(), EngineState::Firing);
+
+        set_chamber_pressure(&mut ecu, 6e6);
+        for _ in 0..20 {
+            ecu.update(0.001);
+        }
+        assert!(redline_tripped(&mut ecu));
+        assert_eq!(ecu.engine_state(), EngineState::EngineShutdown);
+    }
+
+    #[test]
+    fn test_clearing_a_redline_keeps_alert_of_another_on_the_same_input() {
+        let mut fixture = EcuFixture::new();
+        let mut ecu = fixture.ecu();
+        configure(
+            &mut ecu,
+            EcuConfig::default(),
+            RedlineAction::ShutdownEngine,
+        );
+        ecu.enqueue_command(EcuCommand::SetRedline {
+            index: 4,
+            redline: Some(RedlineConfig {
+                input: RedlineInput::Sensor(EcuSensor::EngineChamberPressure),
+                min: None,
+                max: Some(4e6),
+                persistence_s: 0.01,
+                engine_states: EngineStateMask::empty().with(EngineState::Firing),
+                action: RedlineAction::ShutdownEngine,
+            }),
+        });
+        ecu.update(0.001);
+        force_firing(&mut ecu);
+
+        set_chamber_pressure(&mut ecu, 6e6);
+        for _ in 0..20 {
+            ecu.update(0.001);
+        }
+        assert!(redline_tripped(&mut ecu));
+
+        // Redlines only change once the engine is back to idle
+        for _ in 0..1000 {
+            ecu.update(0.001);
+        }
+        assert_eq!(ecu.engine_state(), EngineState::Idle);
+
+        // Redline 3 tripped on the same input, so its alert has to stay
+        ecu.enqueue_command(EcuCommand::SetRedline {
+            index: 4,
+            redline: None,
+        });
+        ecu.update(0.001);
+        assert!(redline_tripped(&mut ecu));
+
+        ecu.enqueue_command(EcuCommand::SetRedline {
+            index: 3,
+            redline: None,
+        });
+        ecu.update(0.001);
+        assert!(!redline_tripped(&mut ecu));
+    }
+
+    #[test]
+    fn test_safe_ecu_uses_configured_tank_action() {
+        for (tank_action, tank_state) in [
+            (SafeTankAction::Vent, TankState::Venting),
+            (SafeTankAction::Idle, TankState::Idle),
+        ] {
+            let mut fixture = EcuFixture::new();
+            let mut ecu = fixture.ecu();
+
+            let mut config = EcuConfig::default();
+            config.fuel_tank_config = Some(TankConfig {
+                press_valve: Some(EcuBinaryOutput::FuelPressValve),
+                fill_valve: Some(EcuBinaryOutput::FuelFillValve),
+                vent_valve: Some(EcuBinaryOutput::FuelVentValve),
+                press_min_threshold_pa: 1e6,
+                press_max_threshold_pa: 2e6,
+                press_relief_threshold_pa: None,
+                press_valve_min_cycle_time_s: 0.1,
+            });
+            config.safe_tank_action = tank_action;
+            configure(&mut ecu, config, RedlineAction::SafeEcu);
+
+            ecu.enqueue_command(EcuCommand::SetTankState((
+                TankType::FuelMain,
+                TankState::Pressurized,
+            )));
+            ecu.update(0.001);
+            assert_eq!(ecu.fuel_tank_state(), Some(TankState::Pressurized));
+            force_firing(&mut ecu);
+
+            set_chamber_pressure(&mut ecu, 6e6);
+            for _ in 0..20 {
+                ecu.update(0.001);
+            }
+            assert!(redline_tripped(&mut ecu));
+            assert_eq!(ecu.engine_state(), EngineState::EngineShutdown);
+            assert_eq!(ecu.fuel_tank_state(), Some(tank_state));
+        }
+    }
+}
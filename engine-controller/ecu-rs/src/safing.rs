@@ -0,0 +1,96 @@
+use shared::{
+    ecu_hal::{EngineState, IgniterState, SafeTankAction, TankConfig, TankState, TankType},
+    ControllerEntity,
+};
+
+use crate::{
+    engine_fsm::engine_shutdown::EngineShutdown,
+    igniter_fsm::shutdown::Shutdown,
+    tank_fsm::{new_state_from_command, TankFsm},
+    Ecu,
+};
+
+impl<'a> Ecu<'a> {
+    /// Shuts down the engine and igniter, and puts the tanks into a safe state
+    pub(crate) fn safe_ecu(&mut self, tank_action: SafeTankAction) {
+        self.shutdown_engine();
+
+        if let Some(mut igniter) = self.igniter.take() {
+            if !matches!(
+                igniter.hal_state(),
+                IgniterState::Idle | IgniterState::Shutdown
+            ) {
+                if let Some(igniter_config) = self.config.igniter_config.clone() {
+                    igniter.force_state(self, Shutdown::new(igniter_config));
+                }
+            }
+
+            self.igniter = Some(igniter);
+        }
+
+        let tank_state = match tank_action {
+            SafeTankAction::Idle => TankState::Idle,
+            SafeTankAction::Vent => TankState::Venting,
+        };
+
+        if let Some(mut fuel_tank) = self.fuel_tank.take() {
+            if let Some(tank_config) = self.config.fuel_tank_config.clone() {
+                self.safe_tank(&mut fuel_tank, TankType::FuelMain, &tank_config, tank_state);
+            }
+
+            self.fuel_tank = Some(fuel_tank);
+        }
+
+        if let Some(mut oxidizer_tank) = self.oxidizer_tank.take() {
+            if let Some(tank_config) = self.config.oxidizer_tank_config.clone() {
+                self.safe_tank(
+                    &mut oxidizer_tank,
+                    TankType::OxidizerMain,
+                    &tank_config,
+                    tank_state,
+                );
+            }
+
+            self.oxidizer_tank = Some(oxidizer_tank);
+        }
+    }
+
+    pub(crate) fn shutdown_engine(&mut self) {
+        self.exit_manual_mode();
+        self.halt_sequence();
+
+        if let Some(mut engine) = self.engine.take() {
+            if !matches!(
+                engine.hal_state(),
+                EngineState::Idle | EngineState::EngineShutdown
+            ) {
+                if let Some(engine_config) = self.config.engine_config.clone() {
+                    engine.force_state(self, EngineShutdown::new(engine_config));
+                }
+            }
+
+            self.engine = Some(engine);
+        }
+    }
+
+    fn safe_tank(
+        &mut self,
+        tank: &mut ControllerEntity<TankFsm, Ecu<'a>, TankState>,
+        tank_type: TankType,
+        tank_config: &TankConfig,
+        tank_state: TankState,
+    ) {
+        if tank.hal_state() != tank_state {
+            tank.force_state(
+                self,
+                new_state_from_command(
+                    tank_state,
+                    tank_type,
+                    tank_config.press_valve,
+                    tank_config.fill_valve,
+                    tank_config.vent_valve,
+                ),
+            );
+        }
+    }
+}
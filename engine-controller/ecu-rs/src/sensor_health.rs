@@ -0,0 +1,354 @@
+use serde::Serialize;
+use shared::{
+    ecu_hal::{EcuAlert, EcuSensor, SensorHealth, SensorVoteConfig},
+    SensorConfig,
+};
+use strum::{EnumCount, IntoEnumIterator};
+
+use crate::Ecu;
+
+/// Checks every reading against its sensor's config, so faulted sensors are left out of
+/// the values the FSMs see
+#[derive(Debug, Clone, Serialize)]
+pub struct SensorMonitor {
+    configs: [Option<SensorConfig>; EcuSensor::COUNT],
+    votes: [Option<SensorVoteConfig>; EcuSensor::COUNT],
+    health: [SensorHealth; EcuSensor::COUNT],
+    /// Last reading that passed the range and rate checks
+    values: [Option<f32>; EcuSensor::COUNT],
+    time_since_reading_s: [f32; EcuSensor::COUNT],
+    time_since_value_s: [f32; EcuSensor::COUNT],
+    last_dt: f32,
+}
+
+impl SensorMonitor {
+    pub const fn new() -> Self {
+        Self {
+            configs: [None; EcuSensor::COUNT],
+            votes: [None; EcuSensor::COUNT],
+            health: [SensorHealth::NoData; EcuSensor::COUNT],
+            values: [None; EcuSensor::COUNT],
+            time_since_reading_s: [0.0; EcuSensor::COUNT],
+            time_since_value_s: [0.0; EcuSensor::COUNT],
+            last_dt: 0.0,
+        }
+    }
+
+    pub fn health(&self, sensor: EcuSensor) -> SensorHealth {
+        self.health[sensor.index()]
+    }
+
+    /// Voted value of the sensor and its redundant sensors, or None if there's no healthy
+    /// reading to trust
+    pub fn value(&self, sensor: EcuSensor) -> Option<f32> {
+        let (values, count) = self.voting_group(sensor);
+
+        match count {
+            0 => None,
+            1 => Some(values[0]),
+            2 if self.disagreement(sensor) => None,
+            2 => Some((values[0] + values[1]) / 2.0),
+            _ => Some(values[1]),
+        }
+    }
+
+    pub fn disagreement(&self, sensor: EcuSensor) -> bool {
+        let vote = match self.votes[sensor.index()] {
+            Some(vote) => vote,
+            None => return false,
+        };
+
+        let (values, count) = self.voting_group(sensor);
+        count >= 2 && values[count - 1] - values[0] > vote.max_disagreement
+    }
+
+    pub fn faulted_bitmask(&self) -> u32 {
+        EcuSensor::iter()
+            .filter(|sensor| self.health(*sensor).is_faulted() || self.disagreement(*sensor))
+            .fold(0, |bitmask, sensor| bitmask | (1 << sensor.index()))
+    }
+
+    pub fn config(&self, sensor: EcuSensor) -> Option<SensorConfig> {
+        self.configs[sensor.index()]
+    }
+
+    /// Earlier readings were converted differently, so the sensor starts over without data
+    pub(crate) fn configure(&mut self, sensor: EcuSensor, config: SensorConfig) {
+        let index = sensor.index();
+        self.configs[index] = Some(config);
+        self.health[index] = SensorHealth::NoData;
+        self.values[index] = None;
+    }
+
+    pub(crate) fn set_vote(&mut self, sensor: EcuSensor, vote: Option<SensorVoteConfig>) {
+        self.votes[sensor.index()] = vote;
+    }
+
+    pub(crate) fn record(&mut self, sensor: EcuSensor, value: f32) {
+        let index = sensor.index();
+        let config = self.configs[index];
+        self.time_since_reading_s[index] = 0.0;
+
+        // Readings between ECU updates are treated as a full update apart
+        let exceeds_rate = match (
+            self.values[index],
+            config.and_then(|config| config.max_rate_per_s),
+        ) {
+            (Some(last_value), Some(max_rate_per_s)) => {
+                (value - last_value).abs()
+                    > max_rate_per_s * self.time_since_value_s[index].max(self.last_dt)
+            }
+            _ => false,
+        };
+
+        self.health[index] = if config.is_some_and(|config| !config.in_range(value)) {
+            SensorHealth::OutOfRange
+        } else if exceeds_rate {
+            SensorHealth::ExcessiveRate
+        } else {
+            self.values[index] = Some(value);
+            self.time_since_value_s[index] = 0.0;
+            SensorHealth::Ok
+        };
+    }
+
+    pub(crate) fn advance(&mut self, dt: f32) {
+        self.last_dt = dt;
+
+        for index in 0..EcuSensor::COUNT {
+            self.time_since_reading_s[index] += dt;
+            self.time_since_value_s[index] += dt;
+
+            let stale = self.configs[index]
+                .and_then(|config| config.stale_timeout_s)
+                .is_some_and(|timeout_s| self.time_since_reading_s[index] > timeout_s);
+
+            if stale && self.health[index] != SensorHealth::NoData {
+                self.health[index] = SensorHealth::Stale;
+            }
+        }
+    }
+
+    /// Healthy values of the sensor and its redundant sensors, sorted
+    fn voting_group(&self, sensor: EcuSensor) -> ([f32; 3], usize) {
+        let redundant_sensors = self.votes[sensor.index()]
+            .map(|vote| vote.redundant_sensors)
+            .unwrap_or([None, None]);
+
+        let mut values = [0.0; 3];
+        let mut count = 0;
+        for member in [Some(sensor), redundant_sensors[0], redundant_sensors[1]]
+            .into_iter()
+            .flatten()
+        {
+            if self.health(member) == SensorHealth::Ok {
+                if let Some(value) = self.values[member.index()] {
+                    values[count] = value;
+                    count += 1;
+                }
+            }
+        }
+
+        values[..count].sort_unstable_by(|a, b| a.total_cmp(b));
+        (values, count)
+    }
+}
+
+impl Default for SensorMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Ecu<'a> {
+    /// Sensor alerts aren't latched, they clear as soon as every sensor is healthy again
+    pub(crate) fn update_sensor_health(&mut self, dt: f32) {
+        self.state_vector.sensor_monitor.advance(dt);
+
+        for (alert, health) in [
+            (EcuAlert::SensorStale, SensorHealth::Stale),
+            (EcuAlert::SensorOutOfRange, SensorHealth::OutOfRange),
+            (EcuAlert::SensorExcessiveRate, SensorHealth::ExcessiveRate),
+        ] {
+            if EcuSensor::iter().any(|sensor| self.state_vector.sensor_health(sensor) == health) {
+                self.alert_manager.set_condition(alert);
+            } else {
+                self.alert_manager.clear_condition(alert);
+            }
+        }
+
+        if EcuSensor::iter().any(|sensor| self.state_vector.sensor_monitor.disagreement(sensor)) {
+            self.alert_manager
+                .set_condition(EcuAlert::SensorDisagreement);
+        } else {
+            self.alert_manager
+                .clear_condition(EcuAlert::SensorDisagreement);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::{
+        alerts::is_condition_set,
+        ecu_hal::{EcuAlert, EcuCommand, EcuConfig, EcuSensor, SensorHealth, SensorVoteConfig},
+        SensorConfig, SensorData,
+    };
+
+    use crate::{test_fixture::EcuFixture, Ecu};
+
+    const SENSOR_CONFIG: SensorConfig = SensorConfig {
+        premin: 410.0,
+        premax: 3686.0,
+        postmin: 0.0,
+        postmax: 10e6,
+        calibration: None,
+        max_rate_per_s: Some(100e6),
+        stale_timeout_s: Some(0.01),
+    };
+
+    fn set_pressure(ecu: &mut Ecu, sensor: EcuSensor, pressure_pa: f32) {
+        ecu.update_sensor_data(
+            sensor,
+            &SensorData::Pressure {
+                pressure_pa,
+                raw_data: 0,
+            },
+        );
+    }
+
+    fn set_raw(ecu: &mut Ecu, sensor: EcuSensor, raw_data: u16) {
+        ecu.update_sensor_data(
+            sensor,
+            &SensorData::Pressure {
+                pressure_pa: 0.0,
+                raw_data,
+            },
+        );
+    }
+
+    fn alert_set(ecu: &mut Ecu, alert: EcuAlert) -> bool {
+        is_condition_set(ecu.alert_manager.get_condition_bitmask(), alert.into())
+    }
+
+    #[test]
+    fn test_faulted_sensor_is_not_trusted() {
+        let mut fixture = EcuFixture::new();
+        let mut ecu = fixture.ecu();
+        ecu.configure_ecu(EcuConfig::default());
+        ecu.enqueue_command(EcuCommand::ConfigureSensor {
+            sensor: EcuSensor::EngineChamberPressure,
+            config: SENSOR_CONFIG,
+        });
+        ecu.update(0.001);
+
+        set_raw(&mut ecu, EcuSensor::EngineChamberPressure, 1065);
+        ecu.update(0.001);
+        assert_eq!(
+            ecu.state_vector
+                .sensor_value(EcuSensor::EngineChamberPressure),
+            Some(SENSOR_CONFIG.apply(1065.0))
+        );
+
+        // Pinned to the supply rail
+        set_raw(&mut ecu, EcuSensor::EngineChamberPressure, 4095);
+        ecu.update(0.001);
+        assert_eq!(
+            ecu.state_vector
+                .sensor_health(EcuSensor::EngineChamberPressure),
+            SensorHealth::OutOfRange
+        );
+        assert_eq!(
+            ecu.state_vector
+                .sensor_value(EcuSensor::EngineChamberPressure),
+            None
+        );
+        assert!(alert_set(&mut ecu, EcuAlert::SensorOutOfRange));
+        assert_eq!(
+            ecu.generate_telemetry_frame().faulted_sensors,
+            1 << EcuSensor::EngineChamberPressure.index()
+        );
+
+        // Back in range, but too far from the last good reading to be real
+        set_raw(&mut ecu, EcuSensor::EngineChamberPressure, 3400);
+        ecu.update(0.001);
+        assert_eq!(
+            ecu.state_vector
+                .sensor_health(EcuSensor::EngineChamberPressure),
+            SensorHealth::ExcessiveRate
+        );
+        assert!(!alert_set(&mut ecu, EcuAlert::SensorOutOfRange));
+        assert!(alert_set(&mut ecu, EcuAlert::SensorExcessiveRate));
+
+        set_raw(&mut ecu, EcuSensor::EngineChamberPressure, 1080);
+        ecu.update(0.001);
+        assert_eq!(
+            ecu.state_vector
+                .sensor_health(EcuSensor::EngineChamberPressure),
+            SensorHealth::Ok
+        );
+        assert_eq!(ecu.generate_telemetry_frame().faulted_sensors, 0);
+
+        // Stops reporting
+        for _ in 0..20 {
+            ecu.update(0.001);
+        }
+        assert_eq!(
+            ecu.state_vector
+                .sensor_health(EcuSensor::EngineChamberPressure),
+            SensorHealth::Stale
+        );
+        assert!(alert_set(&mut ecu, EcuAlert::SensorStale));
+    }
+
+    #[test]
+    fn test_redundant_sensor_voting() {
+        let mut fixture = EcuFixture::new();
+        let mut ecu = fixture.ecu();
+        ecu.configure_ecu(EcuConfig::default());
+        ecu.enqueue_command(EcuCommand::ConfigureSensorVote {
+            sensor: EcuSensor::FuelTankPressure,
+            vote: Some(SensorVoteConfig {
+                redundant_sensors: [
+                    Some(EcuSensor::FuelPumpInletPressure),
+                    Some(EcuSensor::FuelPumpInducerPressure),
+                ],
+                max_disagreement: 0.1e6,
+            }),
+        });
+        ecu.update(0.001);
+
+        // An outlier is outvoted by the median
+        set_pressure(&mut ecu, EcuSensor::FuelTankPressure, 0.0);
+        set_pressure(&mut ecu, EcuSensor::FuelPumpInletPressure, 3e6);
+        set_pressure(&mut ecu, EcuSensor::FuelPumpInducerPressure, 3.02e6);
+        ecu.update(0.001);
+        assert_eq!(
+            ecu.state_vector.sensor_value(EcuSensor::FuelTankPressure),
+            Some(3e6)
+        );
+        assert!(alert_set(&mut ecu, EcuAlert::SensorDisagreement));
+
+        // With two sensors left there's no telling which one is right
+        ecu.enqueue_command(EcuCommand::ConfigureSensorVote {
+            sensor: EcuSensor::FuelTankPressure,
+            vote: Some(SensorVoteConfig {
+                redundant_sensors: [Some(EcuSensor::FuelPumpInletPressure), None],
+                max_disagreement: 0.1e6,
+            }),
+        });
+        ecu.update(0.001);
+        assert_eq!(
+            ecu.state_vector.sensor_value(EcuSensor::FuelTankPressure),
+            None
+        );
+
+        set_pressure(&mut ecu, EcuSensor::FuelTankPressure, 3.04e6);
+        ecu.update(0.001);
+        assert_eq!(
+            ecu.state_vector.sensor_value(EcuSensor::FuelTankPressure),
+            Some(3.02e6)
+        );
+        assert!(!alert_set(&mut ecu, EcuAlert::SensorDisagreement));
+    }
+}
@@ -0,0 +1,341 @@
+use shared::{
+    comms_hal::{CommandRejection, CommandResult, NetworkAddress, Packet},
+    ecu_hal::{
+        EcuAlert, EcuCommand, SequenceAction, SequenceCondition, SequenceState, SequenceStep,
+        TankType, MAX_SEQUENCE_STEPS,
+    },
+};
+
+use crate::{silprintln, Ecu};
+
+/// A timeline of commands run by the ECU itself, so the countdown doesn't depend on the network
+pub struct Sequence {
+    steps: [Option<SequenceStep>; MAX_SEQUENCE_STEPS],
+    state: SequenceState,
+    current_step: usize,
+    time_in_step_s: f32,
+}
+
+impl Sequence {
+    pub const fn new() -> Self {
+        Self {
+            steps: [None; MAX_SEQUENCE_STEPS],
+            state: SequenceState::Idle,
+            current_step: 0,
+            time_in_step_s: 0.0,
+        }
+    }
+
+    pub fn state(&self) -> SequenceState {
+        self.state
+    }
+
+    pub fn current_step(&self) -> usize {
+        self.current_step
+    }
+
+    pub fn is_active(&self) -> bool {
+        matches!(self.state, SequenceState::Running | SequenceState::Holding)
+    }
+
+    pub fn has_steps(&self) -> bool {
+        self.steps.iter().any(Option::is_some)
+    }
+
+    fn advance(&mut self) {
+        self.current_step += 1;
+        self.time_in_step_s = 0.0;
+    }
+}
+
+impl Default for Sequence {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Ecu<'a> {
+    pub(crate) fn handle_sequence_commands(&mut self, packets: &[(NetworkAddress, Packet)]) {
+        for (index, (_remote, packet)) in packets.iter().enumerate() {
+            let command = match packet {
+                Packet::EcuCommand(command) => command,
+                _ => continue,
+            };
+
+            let result = match command {
+                EcuCommand::SetSequenceStep { index, step } => {
+                    if self.sequence.is_active() {
+                        silprintln!("Ignoring sequence change while the sequence is running");
+                        CommandResult::Rejected(CommandRejection::SequenceActive)
+                    } else if !step.is_none_or(|step| step_is_valid(&step)) {
+                        silprintln!("Ignoring invalid sequence step {}", index);
+                        CommandResult::Rejected(CommandRejection::InvalidArgument)
+                    } else if let Some(element) = self.sequence.steps.get_mut(*index as usize) {
+                        *element = *step;
+                        CommandResult::Accepted
+                    } else {
+                        CommandResult::Rejected(CommandRejection::InvalidArgument)
+                    }
+                }
+                EcuCommand::ClearSequence => {
+                    if self.sequence.is_active() {
+                        silprintln!("Ignoring sequence change while the sequence is running");
+                        CommandResult::Rejected(CommandRejection::SequenceActive)
+                    } else {
+                        self.sequence.steps = [None; MAX_SEQUENCE_STEPS];
+                        CommandResult::Accepted
+                    }
+                }
+                EcuCommand::StartSequence => self.start_sequence(),
+                EcuCommand::HoldSequence => {
+                    if self.sequence.state == SequenceState::Running {
+                        silprintln!("Holding sequence at step {}", self.sequence.current_step);
+                        self.sequence.state = SequenceState::Holding;
+                        CommandResult::Accepted
+                    } else {
+                        CommandResult::NotApplicable
+                    }
+                }
+                EcuCommand::AbortSequence => {
+                    self.abort_sequence();
+                    CommandResult::Accepted
+                }
+                _ => continue,
+            };
+
+            self.acknowledge_command(index, result);
+        }
+    }
+
+    /// Runs every step that's due, queueing its commands so they're handled this update
+    pub(crate) fn update_sequence(&mut self, dt: f32) {
+        if self.sequence.state != SequenceState::Running {
+            return;
+        }
+
+        self.sequence.time_in_step_s += dt;
+
+        while self.sequence.state == SequenceState::Running {
+            let index = self.sequence.current_step;
+            if index >= MAX_SEQUENCE_STEPS {
+                silprintln!("Sequence complete");
+                self.sequence.state = SequenceState::Complete;
+                break;
+            }
+
+            let step = match self.sequence.steps[index] {
+                Some(step) => step,
+                None => {
+                    self.sequence.advance();
+                    continue;
+                }
+            };
+
+            let time_waiting_s = self.sequence.time_in_step_s - step.delay_s;
+            if time_waiting_s < 0.0 {
+                break;
+            }
+
+            match step.action {
+                SequenceAction::Command(command) => {
+                    if !self.enqueue_command(command.into()) {
+                        silprintln!("Local command queue full, aborting sequence");
+                        self.abort_sequence();
+                        break;
+                    }
+                }
+                SequenceAction::WaitUntil {
+                    condition,
+                    timeout_s,
+                } => {
+                    if !self.sequence_condition_met(condition) {
+                        if time_waiting_s > timeout_s {
+                            silprintln!("Sequence timed out waiting for {:?}", condition);
+                            self.abort_sequence();
+                        }
+
+                        break;
+                    }
+                }
+            }
+
+            self.sequence.advance();
+        }
+    }
+
+    /// Stops the sequence without safing, for when something else is already doing that
+    pub(crate) fn halt_sequence(&mut self) {
+        if self.sequence.is_active() {
+            silprintln!("Halting sequence at step {}", self.sequence.current_step);
+            self.sequence.state = SequenceState::Aborted;
+        }
+    }
+
+    fn start_sequence(&mut self) -> CommandResult {
+        match self.sequence.state {
+            SequenceState::Running => CommandResult::NotApplicable,
+            SequenceState::Holding => {
+                silprintln!("Resuming sequence at step {}", self.sequence.current_step);
+                self.sequence.state = SequenceState::Running;
+                CommandResult::Accepted
+            }
+            _ => {
+                if self.manual_mode.is_some() {
+                    silprintln!("Ignoring sequence start in manual mode");
+                    CommandResult::Rejected(CommandRejection::ManualModeActive)
+                } else if !self.all_fsms_idle() {
+                    silprintln!("Ignoring sequence start, FSMs aren't idle");
+                    CommandResult::Rejected(CommandRejection::NotIdle)
+                } else if !self.sequence.has_steps() {
+                    silprintln!("Ignoring sequence start, no steps uploaded");
+                    CommandResult::Rejected(CommandRejection::NotConfigured)
+                } else {
+                    silprintln!("Starting sequence");
+                    self.sequence.state = SequenceState::Running;
+                    self.sequence.current_step = 0;
+                    self.sequence.time_in_step_s = 0.0;
+                    self.alert_manager
+                        .clear_condition(EcuAlert::SequenceAborted);
+                    CommandResult::Accepted
+                }
+            }
+        }
+    }
+
+    /// Always safes the ECU, so it doubles as an abort button when no sequence is running
+    pub(crate) fn abort_sequence(&mut self) {
+        silprintln!("Aborting sequence");
+        self.sequence.state = SequenceState::Aborted;
+        self.alert_manager.set_condition(EcuAlert::SequenceAborted);
+        self.safe_ecu(self.config.safe_tank_action);
+    }
+
+    fn sequence_condition_met(&self, condition: SequenceCondition) -> bool {
+        match condition {
+            SequenceCondition::TankState((TankType::FuelMain, state)) => {
+                self.fuel_tank_state() == Some(state)
+            }
+            SequenceCondition::TankState((TankType::OxidizerMain, state)) => {
+                self.oxidizer_tank_state() == Some(state)
+            }
+            SequenceCondition::EngineState(state) => self.engine_state() == state,
+            SequenceCondition::IgniterState(state) => self.igniter_state() == state,
+        }
+    }
+}
+
+fn step_is_valid(step: &SequenceStep) -> bool {
+    let timeout_valid = match step.action {
+        SequenceAction::Command(_) => true,
+        SequenceAction::WaitUntil { timeout_s, .. } => timeout_s > 0.0,
+    };
+
+    step.delay_s.is_finite() && step.delay_s >= 0.0 && timeout_valid
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::{
+        alerts::is_condition_set,
+        ecu_hal::{
+            EcuAlert, EcuCommand, EcuConfig, EngineState, IgniterState, SequenceAction,
+            SequenceCommand, SequenceCondition, SequenceState, SequenceStep,
+        },
+    };
+
+    use crate::{
+        test_fixture::{run, EcuFixture},
+        Ecu,
+    };
+
+    fn upload(ecu: &mut Ecu, steps: &[SequenceStep]) {
+        for (index, step) in steps.iter().enumerate() {
+            ecu.enqueue_command(EcuCommand::SetSequenceStep {
+                index: index as u8,
+                step: Some(*step),
+            });
+        }
+        ecu.update(0.001);
+    }
+
+    #[test]
+    fn test_sequence_timing_and_hold() {
+        let mut fixture = EcuFixture::new();
+        let mut ecu = fixture.ecu();
+        ecu.configure_ecu(EcuConfig::default());
+
+        upload(
+            &mut ecu,
+            &[
+                SequenceStep {
+                    delay_s: 0.5,
+                    action: SequenceAction::Command(SequenceCommand::FireIgniter),
+                },
+                SequenceStep {
+                    delay_s: 0.0,
+                    action: SequenceAction::WaitUntil {
+                        condition: SequenceCondition::IgniterState(IgniterState::Startup),
+                        timeout_s: 0.1,
+                    },
+                },
+            ],
+        );
+
+        ecu.enqueue_command(EcuCommand::StartSequence);
+        run(&mut ecu, 0.2);
+        ecu.enqueue_command(EcuCommand::HoldSequence);
+        run(&mut ecu, 1.0);
+        assert_eq!(ecu.sequence.state(), SequenceState::Holding);
+        assert_eq!(ecu.igniter_state(), IgniterState::Idle);
+
+        ecu.enqueue_command(EcuCommand::StartSequence);
+        run(&mut ecu, 0.2);
+        assert_eq!(ecu.sequence.state(), SequenceState::Running);
+        assert_eq!(ecu.igniter_state(), IgniterState::Idle);
+
+        run(&mut ecu, 0.15);
+        assert_eq!(ecu.sequence.state(), SequenceState::Complete);
+        assert_eq!(ecu.igniter_state(), IgniterState::Startup);
+    }
+
+    #[test]
+    fn test_sequence_hold_timeout_aborts() {
+        let mut fixture = EcuFixture::new();
+        let mut ecu = fixture.ecu();
+        ecu.configure_ecu(EcuConfig::default());
+
+        upload(
+            &mut ecu,
+            &[
+                SequenceStep {
+                    delay_s: 0.0,
+                    action: SequenceAction::WaitUntil {
+                        condition: SequenceCondition::EngineState(EngineState::Firing),
+                        timeout_s: 0.2,
+                    },
+                },
+                SequenceStep {
+                    delay_s: 0.0,
+                    action: SequenceAction::Command(SequenceCommand::FireIgniter),
+                },
+            ],
+        );
+
+        ecu.enqueue_command(EcuCommand::StartSequence);
+        run(&mut ecu, 0.1);
+        assert_eq!(ecu.sequence.state(), SequenceState::Running);
+
+        // Steps can't be changed while the sequence is running
+        upload(&mut ecu, &[]);
+        ecu.enqueue_command(EcuCommand::ClearSequence);
+        run(&mut ecu, 0.2);
+
+        assert_eq!(ecu.sequence.state(), SequenceState::Aborted);
+        assert_eq!(ecu.igniter_state(), IgniterState::Idle);
+        assert!(is_condition_set(
+            ecu.alert_manager.get_condition_bitmask(),
+            EcuAlert::SequenceAborted.into()
+        ));
+        assert_eq!(ecu.sequence.current_step(), 0);
+    }
+}
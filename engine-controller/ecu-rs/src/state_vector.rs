@@ -1,5 +1,10 @@
 use serde::Serialize;
-use shared::{ecu_hal::EcuSensor, SensorData};
+use shared::{
+    ecu_hal::{EcuSensor, SensorHealth},
+    SensorData,
+};
+
+use crate::sensor_health::SensorMonitor;
 
 #[derive(Debug, Clone, Serialize)]
 pub struct SensorDataVector {
@@ -9,7 +14,11 @@ pub struct SensorDataVector {
     pub igniter_fuel_injector_pressure_pa: Option<f32>,     // Pa
     pub igniter_oxidizer_injector_pressure_pa: Option<f32>, // Pa
     pub fuel_pump_outlet_pressure_pa: f32,                  // Pa
+    pub fuel_pump_inlet_pressure_pa: Option<f32>,           // Pa
+    pub fuel_pump_inducer_pressure_pa: Option<f32>,         // Pa
     pub oxidizer_pump_outlet_pressure_pa: f32,              // Pa
+    pub oxidizer_pump_inlet_pressure_pa: Option<f32>,       // Pa
+    pub oxidizer_pump_inducer_pressure_pa: Option<f32>,     // Pa
     pub engine_chamber_pressure_pa: f32,                    // Pa
     pub engine_fuel_injector_pressure_pa: f32,              // Pa
     pub engine_oxidizer_injector_pressure_pa: f32,          // Pa
@@ -17,7 +26,9 @@ pub struct SensorDataVector {
 
 #[derive(Debug, Clone, Serialize)]
 pub struct StateVector {
+    /// Latest reading from every sensor, faulted or not, for telemetry
     pub(crate) sensor_data: SensorDataVector,
+    pub(crate) sensor_monitor: SensorMonitor,
 }
 
 impl StateVector {
@@ -30,15 +41,57 @@ impl StateVector {
                 igniter_fuel_injector_pressure_pa: None,
                 igniter_oxidizer_injector_pressure_pa: None,
                 fuel_pump_outlet_pressure_pa: 0.0,
+                fuel_pump_inlet_pressure_pa: None,
+                fuel_pump_inducer_pressure_pa: None,
                 oxidizer_pump_outlet_pressure_pa: 0.0,
+                oxidizer_pump_inlet_pressure_pa: None,
+                oxidizer_pump_inducer_pressure_pa: None,
                 engine_chamber_pressure_pa: 0.0,
                 engine_fuel_injector_pressure_pa: 0.0,
                 engine_oxidizer_injector_pressure_pa: 0.0,
             },
+            sensor_monitor: SensorMonitor::new(),
+        }
+    }
+
+    /// Voted value from healthy readings, in Pa or K. This is what the FSMs should act on
+    pub fn sensor_value(&self, sensor: EcuSensor) -> Option<f32> {
+        self.sensor_monitor.value(sensor)
+    }
+
+    pub fn sensor_health(&self, sensor: EcuSensor) -> SensorHealth {
+        self.sensor_monitor.health(sensor)
+    }
+
+    /// Converts the raw ADC counts through the sensor's config when the ECU has one,
+    /// otherwise the driver's conversion is kept
+    pub fn calibrate(&self, sensor: EcuSensor, data: &SensorData) -> SensorData {
+        let config = match self.sensor_monitor.config(sensor) {
+            Some(config) => config,
+            None => return *data,
+        };
+
+        match *data {
+            SensorData::Pressure { raw_data, .. } => SensorData::Pressure {
+                pressure_pa: config.apply(raw_data as f32),
+                raw_data,
+            },
+            SensorData::Temperature { raw_data, .. } => SensorData::Temperature {
+                temperature_k: config.apply(raw_data as f32),
+                raw_data,
+            },
         }
     }
 
     pub fn update_sensor_data(&mut self, sensor: EcuSensor, data: &SensorData) {
+        self.sensor_monitor.record(
+            sensor,
+            match data {
+                SensorData::Pressure { pressure_pa, .. } => *pressure_pa,
+                SensorData::Temperature { temperature_k, .. } => *temperature_k,
+            },
+        );
+
         match sensor {
             EcuSensor::FuelTankPressure => {
                 if let SensorData::Pressure { pressure_pa, .. } = data {
@@ -70,11 +123,31 @@ impl StateVector {
                     self.sensor_data.fuel_pump_outlet_pressure_pa = *pressure_pa;
                 }
             }
+            EcuSensor::FuelPumpInletPressure => {
+                if let SensorData::Pressure { pressure_pa, .. } = data {
+                    self.sensor_data.fuel_pump_inlet_pressure_pa = Some(*pressure_pa);
+                }
+            }
+            EcuSensor::FuelPumpInducerPressure => {
+                if let SensorData::Pressure { pressure_pa, .. } = data {
+                    self.sensor_data.fuel_pump_inducer_pressure_pa = Some(*pressure_pa);
+                }
+            }
             EcuSensor::OxidizerPumpOutletPressure => {
                 if let SensorData::Pressure { pressure_pa, .. } = data {
                     self.sensor_data.oxidizer_pump_outlet_pressure_pa = *pressure_pa;
                 }
             }
+            EcuSensor::OxidizerPumpInletPressure => {
+                if let SensorData::Pressure { pressure_pa, .. } = data {
+                    self.sensor_data.oxidizer_pump_inlet_pressure_pa = Some(*pressure_pa);
+                }
+            }
+            EcuSensor::OxidizerPumpInducerPressure => {
+                if let SensorData::Pressure { pressure_pa, .. } = data {
+                    self.sensor_data.oxidizer_pump_inducer_pressure_pa = Some(*pressure_pa);
+                }
+            }
             EcuSensor::EngineChamberPressure => {
                 if let SensorData::Pressure { pressure_pa, .. } = data {
                     self.sensor_data.engine_chamber_pressure_pa = *pressure_pa;
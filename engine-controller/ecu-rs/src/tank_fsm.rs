@@ -1,5 +1,5 @@
 use shared::{
-    ecu_hal::{EcuBinaryOutput, TankState, TankType},
+    ecu_hal::{EcuAlert, EcuBinaryOutput, EcuSensor, TankConfig, TankState, TankType},
     ControllerFsm, ControllerState,
 };
 
@@ -38,7 +38,7 @@ impl<'a> ControllerFsm<TankFsm, Ecu<'a>, TankState> for TankFsm {
     }
 }
 
-fn new_state_from_command(
+pub(crate) fn new_state_from_command(
     state: TankState,
     tank_type: TankType,
     press_valve: Option<EcuBinaryOutput>,
@@ -54,3 +54,33 @@ fn new_state_from_command(
         TankState::Filling => filling::Filling::new(tank_type, press_valve, fill_valve, vent_valve),
     }
 }
+
+pub(crate) fn tank_config(ecu: &Ecu, tank_type: TankType) -> Option<TankConfig> {
+    match tank_type {
+        TankType::FuelMain => ecu.config.fuel_tank_config.clone(),
+        TankType::OxidizerMain => ecu.config.oxidizer_tank_config.clone(),
+    }
+}
+
+pub(crate) fn tank_pressure_pa(ecu: &Ecu, tank_type: TankType) -> Option<f32> {
+    match tank_type {
+        TankType::FuelMain => ecu.state_vector.sensor_value(EcuSensor::FuelTankPressure),
+        TankType::OxidizerMain => ecu
+            .state_vector
+            .sensor_value(EcuSensor::OxidizerTankPressure),
+    }
+}
+
+pub(crate) fn overpressure_alert(tank_type: TankType) -> EcuAlert {
+    match tank_type {
+        TankType::FuelMain => EcuAlert::FuelTankOverpressure,
+        TankType::OxidizerMain => EcuAlert::OxidizerTankOverpressure,
+    }
+}
+
+pub(crate) fn pressure_lost_alert(tank_type: TankType) -> EcuAlert {
+    match tank_type {
+        TankType::FuelMain => EcuAlert::FuelTankPressureLost,
+        TankType::OxidizerMain => EcuAlert::OxidizerTankPressureLost,
+    }
+}
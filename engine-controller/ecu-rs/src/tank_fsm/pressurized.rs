@@ -1,11 +1,14 @@
-use crate::Ecu;
+use crate::{silprintln, Ecu};
 use shared::{
-    comms_hal::{NetworkAddress, Packet},
-    ecu_hal::{EcuBinaryOutput, EcuCommand},
+    comms_hal::{CommandResult, NetworkAddress, Packet},
+    ecu_hal::{EcuBinaryOutput, EcuCommand, TankConfig},
     ControllerState,
 };
 
-use super::{new_state_from_command, TankFsm, TankType};
+use super::{
+    new_state_from_command, overpressure_alert, pressure_lost_alert, tank_config, tank_pressure_pa,
+    venting::Venting, TankFsm, TankType,
+};
 
 #[derive(Debug)]
 pub struct Pressurized {
@@ -13,27 +16,67 @@ pub struct Pressurized {
     press_valve: Option<EcuBinaryOutput>,
     fill_valve: Option<EcuBinaryOutput>,
     vent_valve: Option<EcuBinaryOutput>,
+    press_valve_open: bool,
+    time_since_press_valve_cycle: f32,
+    relieving_overpressure: bool,
 }
 
 impl<'f> ControllerState<TankFsm, Ecu<'f>> for Pressurized {
     fn update<'a>(
         &mut self,
-        _ecu: &mut Ecu,
-        _dt: f32,
+        ecu: &mut Ecu,
+        dt: f32,
         packets: &[(NetworkAddress, Packet)],
     ) -> Option<TankFsm> {
-        if let Some(new_state) = self.should_transition_state(packets) {
+        if let Some(new_state) = self.should_transition_state(ecu, packets) {
             return Some(new_state);
         }
 
+        self.time_since_press_valve_cycle += dt;
+
+        // A tank that can't be measured can't be regulated or relieved, so it's vented and
+        // stays that way until it's commanded again
+        let pressure_pa = match tank_pressure_pa(ecu, self.tank_type) {
+            Some(pressure_pa) => pressure_pa,
+            None => {
+                silprintln!("Lost {:?} pressure reading, venting", self.tank_type);
+                self.set_press_valve(ecu, false);
+                ecu.alert_manager
+                    .set_condition(pressure_lost_alert(self.tank_type));
+
+                return Some(Venting::new(
+                    self.tank_type,
+                    self.press_valve,
+                    self.fill_valve,
+                    self.vent_valve,
+                ));
+            }
+        };
+
+        if let Some(tank_config) = tank_config(ecu, self.tank_type) {
+            self.regulate_pressure(ecu, &tank_config, pressure_pa);
+        }
+
         None
     }
 
     fn enter_state(&mut self, ecu: &mut Ecu) {
-        if let Some(valve) = self.press_valve {
-            ecu.driver.set_binary_valve(valve, true);
+        let pressure_pa = tank_pressure_pa(ecu, self.tank_type);
+        if pressure_pa.is_some() {
+            ecu.alert_manager
+                .clear_condition(pressure_lost_alert(self.tank_type));
         }
 
+        // Without a reading the press valve stays shut, and the first update vents the tank
+        let below_max_pressure = match (tank_config(ecu, self.tank_type), pressure_pa) {
+            (Some(tank_config), Some(pressure_pa)) => {
+                pressure_pa < tank_config.press_max_threshold_pa
+            }
+            _ => false,
+        };
+
+        self.set_press_valve(ecu, below_max_pressure);
+
         if let Some(valve) = self.fill_valve {
             ecu.driver.set_binary_valve(valve, false);
         }
@@ -43,8 +86,11 @@ impl<'f> ControllerState<TankFsm, Ecu<'f>> for Pressurized {
         }
     }
 
-    fn exit_state(&mut self, _ecu: &mut Ecu) {
-        // Nothing
+    fn exit_state(&mut self, ecu: &mut Ecu) {
+        if self.relieving_overpressure {
+            ecu.alert_manager
+                .clear_condition(overpressure_alert(self.tank_type));
+        }
     }
 }
 
@@ -60,14 +106,73 @@ impl Pressurized {
             press_valve,
             fill_valve,
             vent_valve,
+            press_valve_open: false,
+            time_since_press_valve_cycle: 0.0,
+            relieving_overpressure: false,
         })
     }
 
-    fn should_transition_state(&self, packets: &[(NetworkAddress, Packet)]) -> Option<TankFsm> {
-        for (_address, packet) in packets {
+    /// Bang-bang regulation between the min and max thresholds. Relief venting overrides the
+    /// press valve cycle time, everything else waits for it
+    fn regulate_pressure(&mut self, ecu: &mut Ecu, tank_config: &TankConfig, pressure_pa: f32) {
+        let over_relief_pressure = tank_config
+            .press_relief_threshold_pa
+            .is_some_and(|threshold_pa| pressure_pa > threshold_pa);
+
+        if over_relief_pressure && !self.relieving_overpressure {
+            self.relieving_overpressure = true;
+            self.set_press_valve(ecu, false);
+            self.set_vent_valve(ecu, true);
+            ecu.alert_manager
+                .set_condition(overpressure_alert(self.tank_type));
+        } else if self.relieving_overpressure
+            && !over_relief_pressure
+            && pressure_pa < tank_config.press_max_threshold_pa
+        {
+            self.relieving_overpressure = false;
+            self.set_vent_valve(ecu, false);
+            ecu.alert_manager
+                .clear_condition(overpressure_alert(self.tank_type));
+        }
+
+        if self.relieving_overpressure
+            || self.time_since_press_valve_cycle < tank_config.press_valve_min_cycle_time_s
+        {
+            return;
+        }
+
+        if self.press_valve_open && pressure_pa > tank_config.press_max_threshold_pa {
+            self.set_press_valve(ecu, false);
+        } else if !self.press_valve_open && pressure_pa < tank_config.press_min_threshold_pa {
+            self.set_press_valve(ecu, true);
+        }
+    }
+
+    fn set_press_valve(&mut self, ecu: &mut Ecu, open: bool) {
+        self.press_valve_open = open;
+        self.time_since_press_valve_cycle = 0.0;
+
+        if let Some(valve) = self.press_valve {
+            ecu.driver.set_binary_valve(valve, open);
+        }
+    }
+
+    fn set_vent_valve(&mut self, ecu: &mut Ecu, open: bool) {
+        if let Some(valve) = self.vent_valve {
+            ecu.driver.set_binary_valve(valve, open);
+        }
+    }
+
+    fn should_transition_state(
+        &self,
+        ecu: &mut Ecu,
+        packets: &[(NetworkAddress, Packet)],
+    ) -> Option<TankFsm> {
+        for (index, (_address, packet)) in packets.iter().enumerate() {
             if let Packet::EcuCommand(command) = packet {
                 if let EcuCommand::SetTankState((tank, new_state)) = command {
                     if *tank == self.tank_type {
+                        ecu.acknowledge_command(index, CommandResult::Accepted);
                         return Some(new_state_from_command(
                             *new_state,
                             self.tank_type,
@@ -83,3 +188,176 @@ impl Pressurized {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use shared::{
+        alerts::is_condition_set,
+        ecu_hal::{
+            EcuAlert, EcuBinaryOutput, EcuCommand, EcuConfig, EcuSensor, TankConfig, TankState,
+            TankType,
+        },
+        SensorConfig, SensorData,
+    };
+
+    use crate::{test_fixture::EcuFixture, Ecu};
+
+    const MIN_PRESSURE_PA: f32 = 1e6;
+    const MAX_PRESSURE_PA: f32 = 2e6;
+    const RELIEF_PRESSURE_PA: f32 = 3e6;
+
+    // One count per kPa, for tests that need the sensor monitor to fault the reading
+    const SENSOR_CONFIG: SensorConfig = SensorConfig {
+        premin: 0.0,
+        premax: 10_000.0,
+        postmin: 0.0,
+        postmax: 10e6,
+        calibration: None,
+        max_rate_per_s: None,
+        stale_timeout_s: Some(0.01),
+    };
+
+    fn set_tank_pressure(ecu: &mut Ecu, pressure_pa: f32) {
+        ecu.update_sensor_data(
+            EcuSensor::FuelTankPressure,
+            &SensorData::Pressure {
+                pressure_pa,
+                raw_data: (pressure_pa / 1e3) as u16,
+            },
+        );
+    }
+
+    fn configure(ecu: &mut Ecu) {
+        let mut config = EcuConfig::default();
+        config.fuel_tank_config = Some(TankConfig {
+            press_valve: Some(EcuBinaryOutput::FuelPressValve),
+            fill_valve: Some(EcuBinaryOutput::FuelFillValve),
+            vent_valve: Some(EcuBinaryOutput::FuelVentValve),
+            press_min_threshold_pa: MIN_PRESSURE_PA,
+            press_max_threshold_pa: MAX_PRESSURE_PA,
+            press_relief_threshold_pa: Some(RELIEF_PRESSURE_PA),
+            press_valve_min_cycle_time_s: 0.1,
+        });
+        ecu.configure_ecu(config);
+    }
+
+    fn pressurize(ecu: &mut Ecu, pressure_pa: f32) {
+        set_tank_pressure(ecu, pressure_pa);
+        ecu.enqueue_command(EcuCommand::SetTankState((
+            TankType::FuelMain,
+            TankState::Pressurized,
+        )));
+        ecu.update(0.001);
+        assert_eq!(ecu.fuel_tank_state(), Some(TankState::Pressurized));
+    }
+
+    fn step(ecu: &mut Ecu, pressure_pa: f32, duration_s: f32) {
+        set_tank_pressure(ecu, pressure_pa);
+
+        for _ in 0..((duration_s / 0.001) as usize) {
+            ecu.update(0.001);
+        }
+    }
+
+    #[test]
+    fn test_regulates_within_band() {
+        let mut fixture = EcuFixture::new();
+        let mut ecu = fixture.ecu();
+
+        configure(&mut ecu);
+        pressurize(&mut ecu, 0.5e6);
+        assert!(ecu.driver.get_binary_valve(EcuBinaryOutput::FuelPressValve));
+
+        // Inside the band nothing changes
+        step(&mut ecu, 1.5e6, 0.2);
+        assert!(ecu.driver.get_binary_valve(EcuBinaryOutput::FuelPressValve));
+
+        step(&mut ecu, 2.1e6, 0.01);
+        assert!(!ecu.driver.get_binary_valve(EcuBinaryOutput::FuelPressValve));
+
+        // Hysteresis, falling back into the band doesn't reopen the valve
+        step(&mut ecu, 1.5e6, 0.2);
+        assert!(!ecu.driver.get_binary_valve(EcuBinaryOutput::FuelPressValve));
+
+        // Reopening is held off until the valve has been closed for the min cycle time
+        step(&mut ecu, 2.1e6, 0.2);
+        step(&mut ecu, 0.9e6, 0.05);
+        assert!(ecu.driver.get_binary_valve(EcuBinaryOutput::FuelPressValve));
+        step(&mut ecu, 2.1e6, 0.05);
+        assert!(ecu.driver.get_binary_valve(EcuBinaryOutput::FuelPressValve));
+        step(&mut ecu, 2.1e6, 0.06);
+        assert!(!ecu.driver.get_binary_valve(EcuBinaryOutput::FuelPressValve));
+
+        assert!(!ecu.driver.get_binary_valve(EcuBinaryOutput::FuelVentValve));
+    }
+
+    #[test]
+    fn test_relieves_overpressure() {
+        let mut fixture = EcuFixture::new();
+        let mut ecu = fixture.ecu();
+
+        configure(&mut ecu);
+        pressurize(&mut ecu, 0.5e6);
+
+        // Relief doesn't wait for the press valve cycle time
+        step(&mut ecu, 3.1e6, 0.001);
+        assert!(!ecu.driver.get_binary_valve(EcuBinaryOutput::FuelPressValve));
+        assert!(ecu.driver.get_binary_valve(EcuBinaryOutput::FuelVentValve));
+        assert!(is_condition_set(
+            ecu.alert_manager.get_condition_bitmask(),
+            EcuAlert::FuelTankOverpressure.into()
+        ));
+
+        // Keeps venting until the pressure is back under the max threshold
+        step(&mut ecu, 2.5e6, 0.2);
+        assert!(ecu.driver.get_binary_valve(EcuBinaryOutput::FuelVentValve));
+
+        step(&mut ecu, 1.9e6, 0.01);
+        assert!(!ecu.driver.get_binary_valve(EcuBinaryOutput::FuelVentValve));
+        assert!(!ecu.driver.get_binary_valve(EcuBinaryOutput::FuelPressValve));
+        assert!(!is_condition_set(
+            ecu.alert_manager.get_condition_bitmask(),
+            EcuAlert::FuelTankOverpressure.into()
+        ));
+        assert_eq!(ecu.fuel_tank_state(), Some(TankState::Pressurized));
+    }
+
+    #[test]
+    fn test_vents_when_pressure_reading_is_lost() {
+        let mut fixture = EcuFixture::new();
+        let mut ecu = fixture.ecu();
+
+        configure(&mut ecu);
+        ecu.enqueue_command(EcuCommand::ConfigureSensor {
+            sensor: EcuSensor::FuelTankPressure,
+            config: SENSOR_CONFIG,
+        });
+        ecu.update(0.001);
+
+        pressurize(&mut ecu, 0.5e6);
+        assert!(ecu.driver.get_binary_valve(EcuBinaryOutput::FuelPressValve));
+
+        // The sensor stops reporting with the press valve open
+        for _ in 0..20 {
+            ecu.update(0.001);
+        }
+        assert!(!ecu.driver.get_binary_valve(EcuBinaryOutput::FuelPressValve));
+        assert!(ecu.driver.get_binary_valve(EcuBinaryOutput::FuelVentValve));
+        assert_eq!(ecu.fuel_tank_state(), Some(TankState::Venting));
+        assert!(is_condition_set(
+            ecu.alert_manager.get_condition_bitmask(),
+            EcuAlert::FuelTankPressureLost.into()
+        ));
+
+        // Latched until it's commanded again, even once the reading is back
+        step(&mut ecu, 0.5e6, 0.01);
+        assert_eq!(ecu.fuel_tank_state(), Some(TankState::Venting));
+
+        pressurize(&mut ecu, 0.5e6);
+        assert!(ecu.driver.get_binary_valve(EcuBinaryOutput::FuelPressValve));
+        assert!(!is_condition_set(
+            ecu.alert_manager.get_condition_bitmask(),
+            EcuAlert::FuelTankPressureLost.into()
+        ));
+    }
+}
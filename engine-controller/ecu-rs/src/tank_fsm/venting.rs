@@ -1,6 +1,6 @@
 use crate::Ecu;
 use shared::{
-    comms_hal::{NetworkAddress, Packet},
+    comms_hal::{CommandResult, NetworkAddress, Packet},
     ecu_hal::{EcuBinaryOutput, EcuCommand},
     ControllerState,
 };
@@ -18,11 +18,11 @@ pub struct Venting {
 impl<'f> ControllerState<TankFsm, Ecu<'f>> for Venting {
     fn update<'a>(
         &mut self,
-        _ecu: &mut Ecu,
+        ecu: &mut Ecu,
         _dt: f32,
         packets: &[(NetworkAddress, Packet)],
     ) -> Option<TankFsm> {
-        if let Some(new_state) = self.should_transition_state(packets) {
+        if let Some(new_state) = self.should_transition_state(ecu, packets) {
             return Some(new_state);
         }
 
@@ -63,11 +63,16 @@ impl Venting {
         })
     }
 
-    fn should_transition_state(&self, packets: &[(NetworkAddress, Packet)]) -> Option<TankFsm> {
-        for (_address, packet) in packets {
+    fn should_transition_state(
+        &self,
+        ecu: &mut Ecu,
+        packets: &[(NetworkAddress, Packet)],
+    ) -> Option<TankFsm> {
+        for (index, (_address, packet)) in packets.iter().enumerate() {
             if let Packet::EcuCommand(command) = packet {
                 if let EcuCommand::SetTankState((tank, new_state)) = command {
                     if *tank == self.tank_type {
+                        ecu.acknowledge_command(index, CommandResult::Accepted);
                         return Some(new_state_from_command(
                             *new_state,
                             self.tank_type,
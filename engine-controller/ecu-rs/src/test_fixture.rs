@@ -0,0 +1,35 @@
+use big_brother::BigBrother;
+use shared::{comms_hal::NetworkAddress, ecu_mock::EcuDriverMock};
+
+use crate::{ecu::EcuBigBrother, Ecu};
+
+/// Owns the mock driver and an unconnected comms stack for an ECU under test
+pub(crate) struct EcuFixture<'a> {
+    driver: EcuDriverMock,
+    comms: EcuBigBrother<'a>,
+}
+
+impl<'a> EcuFixture<'a> {
+    pub fn new() -> Self {
+        Self {
+            driver: EcuDriverMock::new(),
+            comms: BigBrother::new(
+                NetworkAddress::EngineController(0),
+                1,
+                NetworkAddress::Broadcast,
+                [None, None],
+            ),
+        }
+    }
+
+    pub fn ecu(&'a mut self) -> Ecu<'a> {
+        Ecu::new(&mut self.driver, &mut self.comms)
+    }
+}
+
+/// Steps the ECU at 1 kHz for the given duration
+pub(crate) fn run(ecu: &mut Ecu, duration_s: f32) {
+    for _ in 0..(duration_s * 1e3).round() as usize {
+        ecu.update(0.001);
+    }
+}
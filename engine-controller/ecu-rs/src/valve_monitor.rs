@@ -0,0 +1,161 @@
+use shared::ecu_hal::{BinaryOutputMask, EcuAlert, EcuBinaryOutput};
+use strum::{EnumCount, IntoEnumIterator};
+
+use crate::{silprintln, Ecu};
+
+/// Compares each valve's position feedback with what it was commanded to
+pub struct ValveMonitor {
+    time_mismatched_s: [f32; EcuBinaryOutput::COUNT],
+    faulted: BinaryOutputMask,
+}
+
+impl ValveMonitor {
+    pub const fn new() -> Self {
+        Self {
+            time_mismatched_s: [0.0; EcuBinaryOutput::COUNT],
+            faulted: BinaryOutputMask::empty(),
+        }
+    }
+
+    pub fn faulted(&self) -> BinaryOutputMask {
+        self.faulted
+    }
+}
+
+impl Default for ValveMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<'a> Ecu<'a> {
+    /// A valve is faulted once its feedback has disagreed for longer than the travel time, and
+    /// recovers as soon as it agrees again
+    pub(crate) fn update_valve_monitor(&mut self, dt: f32) {
+        let config = match self.config.valve_monitor_config {
+            Some(config) => config,
+            None => return,
+        };
+
+        let mut new_fault = false;
+        for valve in EcuBinaryOutput::iter() {
+            let index = valve.index();
+            let mismatched = self
+                .driver
+                .get_binary_valve_feedback(valve)
+                .is_some_and(|open| open != self.driver.get_binary_valve(valve));
+
+            if mismatched {
+                self.valve_monitor.time_mismatched_s[index] += dt;
+            } else {
+                self.valve_monitor.time_mismatched_s[index] = 0.0;
+            }
+
+            let faulted = self.valve_monitor.time_mismatched_s[index] > config.travel_time_s;
+            if faulted && !self.valve_monitor.faulted.contains(valve) {
+                silprintln!("{:?} didn't reach its commanded position", valve);
+                new_fault = true;
+            }
+
+            self.valve_monitor.faulted = if faulted {
+                self.valve_monitor.faulted.with(valve)
+            } else {
+                self.valve_monitor.faulted.without(valve)
+            };
+            self.alert_manager
+                .assign_condition(EcuAlert::valve_fault(valve), faulted);
+        }
+
+        if new_fault && config.abort_sequence_on_fault && self.sequence.is_active() {
+            self.abort_sequence();
+        }
+    }
+
+    /// Called on reconfiguration, as the travel time may have changed or monitoring turned off
+    pub(crate) fn reset_valve_monitor(&mut self) {
+        self.valve_monitor = ValveMonitor::new();
+
+        for valve in EcuBinaryOutput::iter() {
+            self.alert_manager
+                .clear_condition(EcuAlert::valve_fault(valve));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::{
+        alerts::is_condition_set,
+        ecu_hal::{
+            BinaryOutputMask, EcuAlert, EcuBinaryOutput, EcuCommand, EcuConfig, EngineState,
+            SequenceAction, SequenceCondition, SequenceState, SequenceStep, ValveMonitorConfig,
+        },
+        ecu_mock::EcuDriverMock,
+    };
+
+    use crate::{
+        test_fixture::{run, EcuFixture},
+        Ecu,
+    };
+
+    fn stick_valve(ecu: &mut Ecu, valve: EcuBinaryOutput, position: Option<bool>) {
+        ecu.driver
+            .as_mut_any()
+            .downcast_mut::<EcuDriverMock>()
+            .unwrap()
+            .set_stuck_valve(valve, position);
+    }
+
+    fn fault_set(ecu: &mut Ecu, valve: EcuBinaryOutput) -> bool {
+        is_condition_set(
+            ecu.alert_manager.get_condition_bitmask(),
+            EcuAlert::valve_fault(valve).into(),
+        )
+    }
+
+    #[test]
+    fn test_stuck_valve_aborts_sequence() {
+        let mut fixture = EcuFixture::new();
+        let mut ecu = fixture.ecu();
+        let mut config = EcuConfig::default();
+        config.valve_monitor_config = Some(ValveMonitorConfig {
+            travel_time_s: 0.1,
+            abort_sequence_on_fault: true,
+        });
+        ecu.configure_ecu(config);
+
+        ecu.enqueue_command(EcuCommand::SetSequenceStep {
+            index: 0,
+            step: Some(SequenceStep {
+                delay_s: 0.0,
+                action: SequenceAction::WaitUntil {
+                    condition: SequenceCondition::EngineState(EngineState::Firing),
+                    timeout_s: 10.0,
+                },
+            }),
+        });
+        ecu.enqueue_command(EcuCommand::StartSequence);
+        run(&mut ecu, 0.01);
+        assert_eq!(ecu.sequence.state(), SequenceState::Running);
+
+        // Closed valves are commanded closed, so one stuck open is mismatched straight away
+        stick_valve(&mut ecu, EcuBinaryOutput::FuelVentValve, Some(true));
+        run(&mut ecu, 0.05);
+        assert!(!fault_set(&mut ecu, EcuBinaryOutput::FuelVentValve));
+        assert_eq!(ecu.sequence.state(), SequenceState::Running);
+
+        run(&mut ecu, 0.1);
+        assert!(fault_set(&mut ecu, EcuBinaryOutput::FuelVentValve));
+        assert!(!fault_set(&mut ecu, EcuBinaryOutput::FuelPressValve));
+        assert_eq!(
+            ecu.valve_monitor.faulted(),
+            BinaryOutputMask::empty().with(EcuBinaryOutput::FuelVentValve)
+        );
+        assert_eq!(ecu.sequence.state(), SequenceState::Aborted);
+
+        stick_valve(&mut ecu, EcuBinaryOutput::FuelVentValve, None);
+        run(&mut ecu, 0.01);
+        assert!(!fault_set(&mut ecu, EcuBinaryOutput::FuelVentValve));
+        assert_eq!(ecu.valve_monitor.faulted(), BinaryOutputMask::empty());
+    }
+}
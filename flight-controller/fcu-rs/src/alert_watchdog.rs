@@ -13,5 +13,20 @@ impl<'a> Fcu<'a> {
                 .driver
                 .get_output_channel_continuity(OutputChannel::SolidMotorIgniter),
         );
+
+        let (drogue_continuity, main_continuity) = match self.config.recovery_config {
+            Some(config) => (
+                self.driver
+                    .get_output_channel_continuity(config.drogue_channel),
+                self.driver
+                    .get_output_channel_continuity(config.main_channel),
+            ),
+            None => (true, true),
+        };
+
+        self.alert_manager
+            .assign_condition(FcuAlertCondition::NoDrogueContinuity, !drogue_continuity);
+        self.alert_manager
+            .assign_condition(FcuAlertCondition::NoMainContinuity, !main_continuity);
     }
 }
@@ -0,0 +1,127 @@
+use nalgebra::Vector3;
+use shared::{fcu_hal::FcuConfig, standard_atmosphere::air_density, GRAVITY};
+
+use crate::Fcu;
+
+#[allow(unused_imports)]
+use num_traits::Float;
+
+const STEP_COUNT: u32 = 50;
+
+impl<'a> Fcu<'a> {
+    pub(crate) fn update_apogee(&mut self) {
+        let position = self.state_vector.get_position();
+        self.apogee = self.apogee.max(position.y);
+
+        // The barometer calibration takes the pad's altitude back out
+        let ground_altitude_m = -self.state_vector.sensor_calibration.barometeric_altitude;
+
+        let predicted_apogee = predict_apogee(
+            &self.config,
+            ground_altitude_m,
+            position.y,
+            &self.state_vector.get_velocity(),
+        );
+        self.predicted_apogee = predicted_apogee.max(self.apogee);
+    }
+
+    /// Only holds once the motor has burned out, as it's the coast that's modelled
+    pub fn get_predicted_apogee(&self) -> f32 {
+        self.predicted_apogee
+    }
+}
+
+/// Altitude above the pad the vehicle coasts up to under gravity and drag. Any vertical
+/// velocity has to be gone by the drag-free time to apogee, so that bounds the steps taken
+pub fn predict_apogee(
+    config: &FcuConfig,
+    ground_altitude_m: f32,
+    altitude_m: f32,
+    velocity: &Vector3<f32>,
+) -> f32 {
+    if velocity.y <= 0.0 {
+        return altitude_m;
+    }
+
+    let drag_factor =
+        0.5 * config.drag_coefficient * config.drag_reference_area_m2 / config.dry_mass_kg;
+
+    // Vertical and horizontal deceleration, drag opposing the whole of the velocity
+    let acceleration = |altitude: f32, vertical: f32, horizontal: f32| {
+        let speed = (vertical * vertical + horizontal * horizontal).sqrt();
+        let drag = drag_factor * air_density(ground_altitude_m + altitude) * speed;
+
+        (-GRAVITY - drag * vertical, -drag * horizontal)
+    };
+
+    let dt = velocity.y / GRAVITY / STEP_COUNT as f32;
+    let mut altitude = altitude_m;
+    let mut vertical = velocity.y;
+    let mut horizontal = velocity.xz().norm();
+
+    for _ in 0..STEP_COUNT {
+        let (vertical_accel, horizontal_accel) = acceleration(altitude, vertical, horizontal);
+
+        // Midpoint method
+        let mid_vertical = vertical + 0.5 * dt * vertical_accel;
+        let mid_horizontal = horizontal + 0.5 * dt * horizontal_accel;
+        let (mid_vertical_accel, mid_horizontal_accel) =
+            acceleration(altitude + 0.5 * dt * vertical, mid_vertical, mid_horizontal);
+
+        // Finish the climb at the current deceleration rather than overshoot it
+        if vertical + dt * mid_vertical_accel <= 0.0 {
+            return altitude + vertical * vertical / (-2.0 * mid_vertical_accel);
+        }
+
+        altitude += dt * mid_vertical;
+        vertical += dt * mid_vertical_accel;
+        horizontal += dt * mid_horizontal_accel;
+    }
+
+    altitude
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_drag_free_apogee() {
+        let mut config = FcuConfig::default();
+        config.drag_coefficient = 0.0;
+
+        // v^2 / 2g
+        let apogee = predict_apogee(&config, 0.0, 100.0, &Vector3::new(30.0, 98.0665, 0.0));
+        assert!((apogee - 590.0).abs() < 1.0);
+
+        let apogee = predict_apogee(&config, 0.0, 100.0, &Vector3::new(0.0, -5.0, 0.0));
+        assert_eq!(apogee, 100.0);
+    }
+
+    #[test]
+    fn test_drag_lowers_apogee() {
+        let config = FcuConfig::default();
+        let velocity = Vector3::new(0.0, 200.0, 0.0);
+
+        let apogee = predict_apogee(&config, 0.0, 0.0, &velocity);
+        assert!(apogee < 200.0 * 200.0 / (2.0 * GRAVITY));
+
+        // Thinner air at a high launch site carries it further
+        let high_site_apogee = predict_apogee(&config, 1400.0, 0.0, &velocity);
+        assert!(high_site_apogee > apogee);
+
+        // Brute force Euler steps as the reference
+        let drag_factor =
+            0.5 * config.drag_coefficient * config.drag_reference_area_m2 / config.dry_mass_kg;
+        let dt = 1e-4;
+        let mut altitude = 0.0;
+        let mut vertical = velocity.y;
+        while vertical > 0.0 {
+            let drag = drag_factor * air_density(altitude) * vertical * vertical;
+            vertical -= (GRAVITY + drag) * dt;
+            altitude += vertical * dt;
+        }
+
+        assert!((apogee - altitude).abs() < 0.01 * altitude);
+    }
+}
@@ -0,0 +1,124 @@
+use shared::{
+    comms_hal::{CommandAck, CommandRejection, CommandResult, NetworkAddress, Packet},
+    fcu_hal::{self, OutputChannel, VehicleCommand, VehicleState},
+};
+
+use crate::Fcu;
+
+impl<'a> Fcu<'a> {
+    /// Answers a tracked command and unwraps it, so it's handled like any other command
+    pub(crate) fn acknowledge_tracked_command(
+        &mut self,
+        packet: Packet,
+        source: NetworkAddress,
+    ) -> Packet {
+        match packet {
+            Packet::TrackedVehicleCommand { id, command } => {
+                let igniter_continuity = self
+                    .driver
+                    .get_output_channel_continuity(OutputChannel::SolidMotorIgniter);
+                let result = check_command(
+                    &command,
+                    self.vehicle_state,
+                    igniter_continuity,
+                    self.recovery_has_continuity(),
+                );
+
+                self.send_packet(source, Packet::CommandAck(CommandAck { id, result }));
+                Packet::VehicleCommand(command)
+            }
+            packet => packet,
+        }
+    }
+}
+
+/// What the FCU will do with a command, using the same checks as the vehicle FSM
+pub fn check_command(
+    command: &VehicleCommand,
+    vehicle_state: VehicleState,
+    igniter_continuity: bool,
+    recovery_continuity: bool,
+) -> CommandResult {
+    match command {
+        VehicleCommand::Configure(_) | VehicleCommand::SetOutputChannel { .. } => {
+            CommandResult::Accepted
+        }
+        VehicleCommand::StartCalibration { .. } => match vehicle_state {
+            VehicleState::Idle => CommandResult::Accepted,
+            _ => CommandResult::NotApplicable,
+        },
+        VehicleCommand::Arm { magic_number } => match vehicle_state {
+            VehicleState::Idle if *magic_number != fcu_hal::ARMING_MAGIC_NUMBER => {
+                CommandResult::Rejected(CommandRejection::BadMagicNumber)
+            }
+            VehicleState::Idle if !recovery_continuity => {
+                CommandResult::Rejected(CommandRejection::NoContinuity)
+            }
+            VehicleState::Idle => CommandResult::Accepted,
+            _ => CommandResult::NotApplicable,
+        },
+        VehicleCommand::IgniteSolidMotor { magic_number } => match vehicle_state {
+            VehicleState::Armed if *magic_number != fcu_hal::IGNITION_MAGIC_NUMBER => {
+                CommandResult::Rejected(CommandRejection::BadMagicNumber)
+            }
+            VehicleState::Armed if !igniter_continuity => {
+                CommandResult::Rejected(CommandRejection::NoContinuity)
+            }
+            VehicleState::Armed => CommandResult::Accepted,
+            _ => CommandResult::NotApplicable,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::{
+        comms_hal::{CommandRejection, CommandResult},
+        fcu_hal::{self, VehicleCommand, VehicleState},
+    };
+
+    use super::check_command;
+
+    #[test]
+    fn test_ignition_ack() {
+        let ignite = VehicleCommand::IgniteSolidMotor {
+            magic_number: fcu_hal::IGNITION_MAGIC_NUMBER,
+        };
+        let bad_ignite = VehicleCommand::IgniteSolidMotor {
+            magic_number: 0xdeadbeef,
+        };
+
+        assert_eq!(
+            check_command(&ignite, VehicleState::Idle, true, true),
+            CommandResult::NotApplicable
+        );
+        assert_eq!(
+            check_command(&bad_ignite, VehicleState::Armed, true, true),
+            CommandResult::Rejected(CommandRejection::BadMagicNumber)
+        );
+        assert_eq!(
+            check_command(&ignite, VehicleState::Armed, false, true),
+            CommandResult::Rejected(CommandRejection::NoContinuity)
+        );
+        assert_eq!(
+            check_command(&ignite, VehicleState::Armed, true, true),
+            CommandResult::Accepted
+        );
+    }
+
+    #[test]
+    fn test_arming_needs_recovery_continuity() {
+        let arm = VehicleCommand::Arm {
+            magic_number: fcu_hal::ARMING_MAGIC_NUMBER,
+        };
+
+        assert_eq!(
+            check_command(&arm, VehicleState::Idle, true, false),
+            CommandResult::Rejected(CommandRejection::NoContinuity)
+        );
+        assert_eq!(
+            check_command(&arm, VehicleState::Idle, false, true),
+            CommandResult::Accepted
+        );
+    }
+}
@@ -44,6 +44,7 @@ impl<'a> Fcu<'a> {
             FcuDebugInfoVariant::Stats => FcuDebugInfo::Stats {
                 timestamp,
                 apogee: self.apogee,
+                predicted_apogee: self.predicted_apogee,
                 data_logged_bytes: self.data_logger.get_bytes_logged(),
                 cpu_utilization: self.driver.hardware_data().cpu_utilization as u32,
             },
@@ -0,0 +1,273 @@
+use nalgebra::Vector3;
+use shared::fcu_hal::FcuConfig;
+
+#[allow(unused_imports)]
+use num_traits::Float;
+
+// Takes the edge off barometer noise without lagging the events much
+const BAROMETER_FILTER_TIME_CONSTANT_S: f32 = 0.25;
+// Long enough for the barometer noise to wash out of the climb rate
+const LANDING_CLIMB_RATE_INTERVAL_S: f32 = 0.5;
+
+#[derive(Debug)]
+struct BarometerFilter {
+    altitude: f32,
+}
+
+impl BarometerFilter {
+    fn new(altitude: f32) -> Self {
+        Self { altitude }
+    }
+
+    fn update(&mut self, dt: f32, altitude: f32) -> f32 {
+        self.altitude += (altitude - self.altitude) * dt / (BAROMETER_FILTER_TIME_CONSTANT_S + dt);
+        self.altitude
+    }
+}
+
+/// Acceleration held long enough to not be a knock on the pad, and the barometer agreeing
+/// that the vehicle has left it
+#[derive(Debug)]
+pub struct LaunchDetector {
+    pad_altitude: f32,
+    barometer: BarometerFilter,
+    time_accelerating_s: f32,
+}
+
+impl LaunchDetector {
+    pub fn new(barometric_altitude: f32) -> Self {
+        Self {
+            pad_altitude: barometric_altitude,
+            barometer: BarometerFilter::new(barometric_altitude),
+            time_accelerating_s: 0.0,
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        config: &FcuConfig,
+        dt: f32,
+        acceleration: &Vector3<f32>,
+        barometric_altitude: f32,
+    ) -> bool {
+        let altitude = self.barometer.update(dt, barometric_altitude);
+
+        if acceleration.magnitude() > config.startup_acceleration_threshold {
+            self.time_accelerating_s += dt;
+        } else {
+            self.time_accelerating_s = 0.0;
+        }
+
+        self.time_accelerating_s >= config.launch_acceleration_duration_s
+            && altitude - self.pad_altitude >= config.launch_altitude_gain_m
+    }
+
+    pub fn accelerating(&self) -> bool {
+        self.time_accelerating_s > 0.0
+    }
+}
+
+/// Velocity turning downwards, confirmed by the barometer dropping from its peak. Both are
+/// ignored near Mach, where shocks across the static ports throw the barometer off
+#[derive(Debug)]
+pub struct ApogeeDetector {
+    barometer: BarometerFilter,
+    peak_altitude: f32,
+}
+
+impl ApogeeDetector {
+    pub fn new(barometric_altitude: f32) -> Self {
+        Self {
+            barometer: BarometerFilter::new(barometric_altitude),
+            peak_altitude: barometric_altitude,
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        config: &FcuConfig,
+        dt: f32,
+        velocity: &Vector3<f32>,
+        barometric_altitude: f32,
+    ) -> bool {
+        let altitude = self.barometer.update(dt, barometric_altitude);
+
+        if velocity.magnitude() > config.apogee_mach_lockout_speed {
+            return false;
+        }
+
+        self.peak_altitude = self.peak_altitude.max(altitude);
+
+        velocity.y < 0.0 && self.peak_altitude - altitude >= config.apogee_barometric_descent_m
+    }
+}
+
+/// Altitude that has stopped changing and a climb rate that has stopped moving about, held for
+/// long enough that it isn't just a slow part of the descent. Both come from the barometer, as
+/// the estimator has no idea of the ground until it's told it has landed
+#[derive(Debug, Default)]
+pub struct LandingDetector {
+    barometer: Option<BarometerFilter>,
+    reference_altitude: f32,
+    stable_time_s: f32,
+    climb_rate_altitude: f32,
+    climb_rate_time_s: f32,
+    // Welford's running variance
+    velocity_count: u32,
+    velocity_mean: f32,
+    velocity_sum_of_squares: f32,
+}
+
+impl LandingDetector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn update(&mut self, config: &FcuConfig, dt: f32, barometric_altitude: f32) -> bool {
+        let altitude = match &mut self.barometer {
+            Some(barometer) => barometer.update(dt, barometric_altitude),
+            None => {
+                self.barometer = Some(BarometerFilter::new(barometric_altitude));
+                self.restart(barometric_altitude);
+                return false;
+            }
+        };
+
+        self.climb_rate_time_s += dt;
+        if self.climb_rate_time_s >= LANDING_CLIMB_RATE_INTERVAL_S {
+            let climb_rate = (altitude - self.climb_rate_altitude) / self.climb_rate_time_s;
+            self.climb_rate_altitude = altitude;
+            self.climb_rate_time_s = 0.0;
+
+            self.velocity_count += 1;
+            let delta = climb_rate - self.velocity_mean;
+            self.velocity_mean += delta / self.velocity_count as f32;
+            self.velocity_sum_of_squares += delta * (climb_rate - self.velocity_mean);
+        }
+
+        if (altitude - self.reference_altitude).abs() > config.landing_altitude_tolerance_m
+            || self.velocity_variance() > config.landing_velocity_variance
+        {
+            self.restart(altitude);
+            return false;
+        }
+
+        self.stable_time_s += dt;
+        self.stable_time_s >= config.landing_stable_duration_s
+    }
+
+    fn velocity_variance(&self) -> f32 {
+        if self.velocity_count < 2 {
+            return 0.0;
+        }
+
+        self.velocity_sum_of_squares / (self.velocity_count - 1) as f32
+    }
+
+    fn restart(&mut self, altitude: f32) {
+        self.reference_altitude = altitude;
+        self.stable_time_s = 0.0;
+        self.climb_rate_altitude = altitude;
+        self.climb_rate_time_s = 0.0;
+        self.velocity_count = 0;
+        self.velocity_mean = 0.0;
+        self.velocity_sum_of_squares = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DT: f32 = 0.01;
+
+    // Deterministic stand-in for sensor noise, within +-amplitude
+    fn noise(step: u32, amplitude: f32) -> f32 {
+        let hash = step.wrapping_mul(2654435761) >> 16;
+        ((hash % 2001) as f32 / 1000.0 - 1.0) * amplitude
+    }
+
+    #[test]
+    fn test_launch_needs_sustained_acceleration_and_altitude() {
+        let config = FcuConfig::default();
+
+        // A knock on the pad
+        let mut detector = LaunchDetector::new(0.0);
+        for step in 0..100 {
+            let acceleration = if step < 3 { 30.0 } else { 0.0 };
+            let altitude = noise(step, 0.5);
+
+            assert!(!detector.update(&config, DT, &Vector3::new(0.0, acceleration, 0.0), altitude));
+        }
+
+        // Motor lit, but held down on the pad
+        let mut detector = LaunchDetector::new(0.0);
+        for step in 0..100 {
+            let altitude = noise(step, 0.5);
+            assert!(!detector.update(&config, DT, &Vector3::new(0.0, 30.0, 0.0), altitude));
+        }
+
+        let mut detector = LaunchDetector::new(0.0);
+        let launch_step = (0..200).find(|step| {
+            let t = *step as f32 * DT;
+            let altitude = 0.5 * 30.0 * t * t + noise(*step, 0.5);
+
+            detector.update(&config, DT, &Vector3::new(0.0, 30.0, 0.0), altitude)
+        });
+        assert!(launch_step.is_some());
+    }
+
+    #[test]
+    fn test_apogee_confirmed_by_barometer() {
+        let config = FcuConfig::default();
+        let mut detector = ApogeeDetector::new(0.0);
+
+        // Slowing to apogee at 500 m, with the velocity estimate briefly dipping below zero
+        for step in 0..300 {
+            let t = step as f32 * DT;
+            let velocity = if step == 150 { -1.0 } else { 30.0 - 9.8 * t };
+            let altitude = 500.0 - 0.5 * 9.8 * (30.0 / 9.8 - t).powi(2) + noise(step, 0.5);
+
+            assert!(!detector.update(&config, DT, &Vector3::new(0.0, velocity, 0.0), altitude));
+        }
+
+        let apogee_step = (300..500).find(|step| {
+            let t = *step as f32 * DT;
+            let velocity = 30.0 - 9.8 * t;
+            let altitude = 500.0 - 0.5 * 9.8 * (30.0 / 9.8 - t).powi(2) + noise(*step, 0.5);
+
+            detector.update(&config, DT, &Vector3::new(0.0, velocity, 0.0), altitude)
+        });
+        assert!(apogee_step.is_some());
+    }
+
+    #[test]
+    fn test_apogee_mach_lockout() {
+        let config = FcuConfig::default();
+        let mut detector = ApogeeDetector::new(1000.0);
+
+        // Transonic pressure spike reading as a sudden drop, with the velocity estimate pulled along
+        for step in 0..100 {
+            let velocity = Vector3::new(0.0, -5.0, config.apogee_mach_lockout_speed + 10.0);
+            assert!(!detector.update(&config, DT, &velocity, 900.0 + noise(step, 0.5)));
+        }
+    }
+
+    #[test]
+    fn test_landing_needs_stable_altitude() {
+        let config = FcuConfig::default();
+        let mut detector = LandingDetector::new();
+
+        // Steady descent under the main, where the climb rate variance alone would pass
+        for step in 0..1000 {
+            let altitude = 50.0 - 5.0 * step as f32 * DT + noise(step, 0.5);
+            assert!(!detector.update(&config, DT, altitude));
+        }
+
+        let steps_to_land = (0..1000).find(|step| detector.update(&config, DT, noise(*step, 0.5)));
+        let time_to_land = steps_to_land.unwrap() as f32 * DT;
+        assert!(time_to_land >= config.landing_stable_duration_s);
+        // Less the time for the filtered barometer to settle from the descent
+        assert!(time_to_land < config.landing_stable_duration_s + 1.5);
+    }
+}
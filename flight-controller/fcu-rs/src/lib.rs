@@ -15,20 +15,25 @@ macro_rules! silprintln {
 }
 
 mod alert_watchdog;
+pub mod apogee;
+pub mod command_ack;
 pub mod debug_info;
 mod dev_stats;
+pub mod flight_events;
+pub mod recovery;
 pub mod state_vector;
 pub mod vehicle_fsm;
 
 use big_brother::BigBrother;
 use dev_stats::DevStatsCollector;
-use mint::Vector3;
+use mint::{ColumnMatrix3, Vector3};
 use shared::{
     alerts::AlertManager,
     comms_hal::{NetworkAddress, Packet},
     fcu_hal::{
         FcuAlertCondition, FcuConfig, FcuDebugInfoVariant, FcuDriver, FcuSensorData,
         FcuTelemetryFrame, OutputChannel, PwmChannel, VehicleCommand, VehicleState,
+        OUTPUT_CHANNEL_COUNT,
     },
     DataPointLogger, COMMS_NETWORK_MAP_SIZE,
 };
@@ -56,6 +61,8 @@ pub struct Fcu<'a> {
     time_since_last_telemetry: f32,
     time_since_last_heartbeat: f32,
     apogee: f32,
+    predicted_apogee: f32,
+    pyro_pulse_remaining_s: [f32; OUTPUT_CHANNEL_COUNT],
 }
 
 impl<'a> Fcu<'a> {
@@ -69,6 +76,13 @@ impl<'a> Fcu<'a> {
             startup_acceleration_threshold: 0.1,
             startup_acceleration_timeout: 5.0,
             calibration_duration: 5.0,
+            launch_acceleration_duration_s: 0.1,
+            launch_altitude_gain_m: 3.0,
+            apogee_barometric_descent_m: 1.0,
+            apogee_mach_lockout_speed: 270.0,
+            landing_altitude_tolerance_m: 2.0,
+            landing_velocity_variance: 0.25,
+            landing_stable_duration_s: 3.0,
             kalman_process_variance: 1e-1,
             accelerometer_noise_std_dev: Vector3 {
                 x: 0.01,
@@ -81,11 +95,46 @@ impl<'a> Fcu<'a> {
                 y: 3.0,
                 z: 1.5,
             },
+            gps_velocity_noise_std_dev: Vector3 {
+                x: 0.2,
+                y: 0.4,
+                z: 0.2,
+            },
+            gps_min_satellites: 6,
+            gps_max_hdop: 5.0,
             gyro_noise_std_dev: Vector3 {
                 x: 0.1,
                 y: 0.1,
                 z: 0.1,
             },
+            magnetometer_hard_iron_offset: Vector3 {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+            },
+            magnetometer_soft_iron_matrix: ColumnMatrix3 {
+                x: Vector3 {
+                    x: 1.0,
+                    y: 0.0,
+                    z: 0.0,
+                },
+                y: Vector3 {
+                    x: 0.0,
+                    y: 1.0,
+                    z: 0.0,
+                },
+                z: Vector3 {
+                    x: 0.0,
+                    y: 0.0,
+                    z: 1.0,
+                },
+            },
+            magnetometer_heading_noise_std_dev: 0.05,
+            magnetometer_disabled_during_boost: true,
+            drag_coefficient: 0.5,
+            drag_reference_area_m2: 8e-3,
+            dry_mass_kg: 10.0,
+            recovery_config: None,
         };
 
         let state_vector = StateVector::new(&default_fcu_config);
@@ -105,6 +154,8 @@ impl<'a> Fcu<'a> {
             time_since_last_telemetry: 0.0,
             time_since_last_heartbeat: 0.0,
             apogee: 0.0,
+            predicted_apogee: 0.0,
+            pyro_pulse_remaining_s: [0.0; OUTPUT_CHANNEL_COUNT],
         };
         fcu.init_vehicle_fsm();
         let _ = fcu
@@ -122,6 +173,7 @@ impl<'a> Fcu<'a> {
         let mut packets = empty_packet_array();
         let mut num_packets = 0;
         while let Some((packet, source)) = self.comms.recv_packet().ok().flatten() {
+            let packet = self.acknowledge_tracked_command(packet, source);
             packets[num_packets] = (source, packet);
             num_packets += 1;
         }
@@ -131,7 +183,7 @@ impl<'a> Fcu<'a> {
             .log_update_start(timestamp, packets.len() as u32, 0.0);
         self.state_vector.predict(dt);
 
-        self.apogee = self.apogee.max(self.state_vector.get_position().y);
+        self.update_apogee();
 
         self.time_since_last_telemetry += dt;
         self.time_since_last_heartbeat += dt;
@@ -174,6 +226,7 @@ impl<'a> Fcu<'a> {
             self.handle_packet(*source, packet);
         }
 
+        self.update_pyro_pulses(dt);
         self.update_vehicle_fsm(dt, packets);
         self.dev_stats.log_update_end(self.driver.timestamp());
     }
@@ -234,6 +287,7 @@ impl<'a> Fcu<'a> {
             output_channels_continuity_bitmask: self.get_output_channels_continuity_bitmask(),
             pwm_channels: [0.0; PwmChannel::COUNT],
             apogee: self.apogee,
+            predicted_apogee: self.predicted_apogee,
             battery_voltage: 11.1169875,
             data_logged_bytes: self.data_logger.get_bytes_logged(),
         }
@@ -264,11 +318,10 @@ impl<'a> Fcu<'a> {
     fn get_output_channels_continuity_bitmask(&self) -> u16 {
         let mut bitmask = 0;
 
-        if self
-            .driver
-            .get_output_channel_continuity(OutputChannel::SolidMotorIgniter)
-        {
-            bitmask |= 1 << OutputChannel::SolidMotorIgniter.index();
+        for channel in OutputChannel::all() {
+            if self.driver.get_output_channel_continuity(channel) {
+                bitmask |= 1 << channel.index();
+            }
         }
 
         bitmask
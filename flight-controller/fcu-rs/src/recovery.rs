@@ -0,0 +1,72 @@
+use shared::fcu_hal::{OutputChannel, RecoveryConfig};
+
+use crate::Fcu;
+
+impl<'a> Fcu<'a> {
+    /// Holds the channel on for the fire pulse, update_pyro_pulses turns it back off
+    pub(crate) fn fire_pyro(&mut self, channel: OutputChannel, duration_s: f32) {
+        silprintln!("Firing {:?} for {}s", channel, duration_s);
+        self.driver.set_output_channel(channel, true);
+        self.pyro_pulse_remaining_s[channel.index()] = duration_s;
+    }
+
+    pub(crate) fn update_pyro_pulses(&mut self, dt: f32) {
+        for channel in OutputChannel::all() {
+            let remaining_s = &mut self.pyro_pulse_remaining_s[channel.index()];
+            if *remaining_s <= 0.0 {
+                continue;
+            }
+
+            *remaining_s -= dt;
+            if *remaining_s <= 0.0 {
+                self.driver.set_output_channel(channel, false);
+            }
+        }
+    }
+
+    /// True without a recovery config, as there's nothing to deploy
+    pub fn recovery_has_continuity(&self) -> bool {
+        match self.config.recovery_config {
+            Some(config) => {
+                self.driver
+                    .get_output_channel_continuity(config.drogue_channel)
+                    && self
+                        .driver
+                        .get_output_channel_continuity(config.main_channel)
+            }
+            None => true,
+        }
+    }
+}
+
+/// The delay after apogee, cut short by the backup timer if apogee came late or never did
+pub fn time_until_drogue_s(config: &RecoveryConfig, time_since_liftoff_s: f32) -> f32 {
+    config
+        .drogue_delay_s
+        .min(config.drogue_backup_time_s - time_since_liftoff_s)
+        .max(0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use shared::fcu_hal::{OutputChannel, RecoveryConfig};
+
+    use super::time_until_drogue_s;
+
+    #[test]
+    fn test_drogue_backup_timer() {
+        let config = RecoveryConfig {
+            drogue_channel: OutputChannel::Extra { index: 0 },
+            drogue_delay_s: 2.0,
+            drogue_backup_time_s: 20.0,
+            main_channel: OutputChannel::Extra { index: 1 },
+            main_deploy_altitude_m: 150.0,
+            pyro_fire_duration_s: 1.0,
+        };
+
+        assert_eq!(time_until_drogue_s(&config, 10.0), 2.0);
+        assert_eq!(time_until_drogue_s(&config, 19.0), 1.0);
+        assert_eq!(time_until_drogue_s(&config, 20.0), 0.0);
+        assert_eq!(time_until_drogue_s(&config, 25.0), 0.0);
+    }
+}
@@ -1,22 +1,31 @@
 use nalgebra::{UnitQuaternion, Vector3};
 use serde::Serialize;
 use shared::{
-    fcu_hal::{FcuConfig, FcuSensorData},
+    fcu_hal::{FcuConfig, FcuSensorData, GpsFixType},
     GRAVITY,
 };
 
 use shared::standard_atmosphere::convert_pressure_to_altitude;
 
-use self::kalman::KalmanFilter;
+use self::{
+    gps::{geodetic_to_launch_frame, ned_to_launch_frame, GeodeticPosition, GpsGate},
+    kalman::KalmanFilter,
+    magnetometer::MagnetometerCalibration,
+};
 
+pub mod gps;
 pub mod kalman;
+pub mod magnetometer;
 
 #[derive(Debug, Clone, Serialize)]
 pub struct SensorCalibrationData {
     pub accelerometer: Vector3<f32>,
     pub gyroscope: Vector3<f32>,
-    pub magnetometer: Vector3<f32>,
+    /// Field in the launch frame on the pad, None if the magnetometer wasn't reporting
+    pub magnetic_field_reference: Option<Vector3<f32>>,
     pub barometeric_altitude: f32,
+    /// Origin of the launch frame, None if there was no usable fix while calibrating
+    pub gps_origin: Option<GeodeticPosition>,
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -25,12 +34,19 @@ pub struct SensorData {
     pub accelerometer_raw: Vector3<i16>,
     pub gyroscope: Vector3<f32>,
     pub gyroscope_raw: Vector3<i16>,
-    pub magnetometer: Vector3<f32>,
+    pub magnetometer: Vector3<f32>, // Hard and soft iron corrected
     pub magnetometer_raw: Vector3<i16>,
     pub barometer_pressure: f32,
     pub barometer_altitude: f32,
     pub barometer_raw: u32,
     pub barometer_temperature: f32,
+    /// Latest fix, None if it didn't pass the gate
+    pub gps_fix: Option<GeodeticPosition>,
+    pub gps_fix_type: GpsFixType,
+    pub gps_satellites: u8,
+    pub gps_hdop: f32,
+    pub gps_position: Vector3<f32>, // Launch frame
+    pub gps_velocity: Vector3<f32>, // Launch frame
 }
 
 #[derive(Debug, Clone, Serialize)]
@@ -38,6 +54,9 @@ pub struct StateVector {
     pub(crate) kalman: KalmanFilter,
     pub(crate) sensor_calibration: SensorCalibrationData,
     pub(crate) sensor_data: SensorData,
+    pub(crate) gps_gate: GpsGate,
+    pub(crate) magnetometer_calibration: MagnetometerCalibration,
+    pub magnetometer_enabled: bool,
     pub landed: bool,
 }
 
@@ -48,8 +67,9 @@ impl StateVector {
             sensor_calibration: SensorCalibrationData {
                 accelerometer: Vector3::new(0.0, 0.0, 0.0),
                 gyroscope: Vector3::new(0.0, 0.0, 0.0),
-                magnetometer: Vector3::new(0.0, 0.0, 0.0),
+                magnetic_field_reference: None,
                 barometeric_altitude: 0.0,
+                gps_origin: None,
             },
             sensor_data: SensorData {
                 accelerometer: Vector3::new(0.0, 0.0, 0.0),
@@ -62,7 +82,16 @@ impl StateVector {
                 barometer_altitude: 0.0,
                 barometer_raw: 0,
                 barometer_temperature: 0.0,
+                gps_fix: None,
+                gps_fix_type: GpsFixType::NoFix,
+                gps_satellites: 0,
+                gps_hdop: 0.0,
+                gps_position: Vector3::new(0.0, 0.0, 0.0),
+                gps_velocity: Vector3::new(0.0, 0.0, 0.0),
             },
+            gps_gate: GpsGate::new(config),
+            magnetometer_calibration: MagnetometerCalibration::new(config),
+            magnetometer_enabled: true,
             landed: true,
         }
     }
@@ -73,6 +102,8 @@ impl StateVector {
 
     pub fn update_config(&mut self, config: &FcuConfig) {
         self.kalman.update_config(config);
+        self.gps_gate = GpsGate::new(config);
+        self.magnetometer_calibration = MagnetometerCalibration::new(config);
     }
 
     pub fn update_calibration(&mut self, sensor_calibration: SensorCalibrationData) {
@@ -114,14 +145,23 @@ impl StateVector {
                 magnetic_field,
                 raw_data,
             } => {
-                // self.sensor_data.magnetometer = magnetic_field.into();
-                // self.sensor_data.magnetometer_raw = raw_data.into();
+                self.sensor_data.magnetometer =
+                    self.magnetometer_calibration.apply(&magnetic_field.into());
+                self.sensor_data.magnetometer_raw = raw_data.into();
 
-                // let mut magnetic_field = magnetic_field.into();
-                // magnetic_field += self.sensor_calibration.magnetometer;
+                if !self.magnetometer_enabled {
+                    return;
+                }
 
-                // self.kalman
-                //     .update_magnetic_field(magnetic_field);
+                // Heading is relative to the pad, so there's nothing to correct against until
+                // calibration has captured the reference
+                let reference_field = match self.sensor_calibration.magnetic_field_reference {
+                    Some(reference_field) => reference_field,
+                    None => return,
+                };
+
+                self.kalman
+                    .update_magnetic_field(self.sensor_data.magnetometer, reference_field);
             }
             FcuSensorData::Barometer {
                 pressure,
@@ -135,11 +175,46 @@ impl StateVector {
                 self.sensor_data.barometer_altitude =
                     convert_pressure_to_altitude(pressure, temperature);
 
-                let altitude = self.sensor_data.barometer_altitude
-                    + self.sensor_calibration.barometeric_altitude;
-
+                let altitude = self.get_barometric_altitude();
                 self.kalman.update_barometric_pressure(altitude);
             }
+            FcuSensorData::Gps {
+                latitude,
+                longitude,
+                altitude,
+                velocity_ned,
+                fix_type,
+                satellites,
+                hdop,
+            } => {
+                self.sensor_data.gps_fix_type = fix_type;
+                self.sensor_data.gps_satellites = satellites;
+                self.sensor_data.gps_hdop = hdop;
+
+                if !self.gps_gate.accepts(fix_type, satellites, hdop) {
+                    self.sensor_data.gps_fix = None;
+                    return;
+                }
+
+                let fix = GeodeticPosition {
+                    latitude,
+                    longitude,
+                    altitude,
+                };
+                self.sensor_data.gps_fix = Some(fix);
+                self.sensor_data.gps_velocity = ned_to_launch_frame(&velocity_ned.into());
+
+                // Without an origin there's no launch frame to put the fix in yet
+                let origin = match self.sensor_calibration.gps_origin {
+                    Some(origin) => origin,
+                    None => return,
+                };
+
+                self.sensor_data.gps_position = geodetic_to_launch_frame(&origin, &fix);
+
+                self.kalman
+                    .update_gps(self.sensor_data.gps_position, self.sensor_data.gps_velocity);
+            }
         }
     }
 
@@ -159,6 +234,11 @@ impl StateVector {
         self.kalman.velocity_std_dev
     }
 
+    /// Straight from the last barometer reading, above the pad
+    pub fn get_barometric_altitude(&self) -> f32 {
+        self.sensor_data.barometer_altitude + self.sensor_calibration.barometeric_altitude
+    }
+
     pub fn get_acceleration(&self) -> Vector3<f32> {
         self.kalman.acceleration
     }
@@ -190,4 +270,147 @@ impl StateVector {
     pub fn get_landed(&self) -> bool {
         self.landed
     }
+
+    pub fn set_magnetometer_enabled(&mut self, enabled: bool) {
+        self.magnetometer_enabled = enabled;
+    }
+
+    pub fn get_magnetometer_enabled(&self) -> bool {
+        self.magnetometer_enabled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nalgebra::Vector3;
+    use shared::fcu_hal::{FcuConfig, FcuSensorData, GpsFixType};
+
+    use super::{
+        gps::{launch_frame_to_geodetic, GeodeticPosition},
+        StateVector,
+    };
+
+    const ORIGIN: GeodeticPosition = GeodeticPosition {
+        latitude: 32.990254,
+        longitude: -106.974998,
+        altitude: 1401.0,
+    };
+
+    fn gps_fix(position: Vector3<f32>, fix_type: GpsFixType) -> FcuSensorData {
+        let fix = launch_frame_to_geodetic(&ORIGIN, &position);
+
+        FcuSensorData::Gps {
+            latitude: fix.latitude,
+            longitude: fix.longitude,
+            altitude: fix.altitude,
+            velocity_ned: mint::Vector3 {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+            },
+            fix_type,
+            satellites: 9,
+            hdop: 1.1,
+        }
+    }
+
+    #[test]
+    fn test_gps_fix_is_fused() {
+        // Process noise the FCU boots with, the default is slow to pull in a 100 m offset
+        let mut config = FcuConfig::default();
+        config.kalman_process_variance = 1e-1;
+        let mut state_vector = StateVector::new(&config);
+        let position = Vector3::new(0.0, 0.0, 100.0);
+
+        // Nothing to fuse against before calibration has captured an origin
+        state_vector.update_sensor_data(&gps_fix(position, GpsFixType::Fix3D));
+        state_vector.predict(0.01);
+        assert!(state_vector.sensor_data.gps_fix.is_some());
+        assert!(state_vector.get_position().norm() < 1e-3);
+
+        let mut sensor_calibration = state_vector.sensor_calibration.clone();
+        sensor_calibration.gps_origin = Some(ORIGIN);
+        state_vector.update_calibration(sensor_calibration);
+
+        // Without a 3D fix it's still ignored
+        state_vector.update_sensor_data(&gps_fix(position, GpsFixType::Fix2D));
+        state_vector.predict(0.01);
+        assert!(state_vector.sensor_data.gps_fix.is_none());
+        assert!(state_vector.get_position().norm() < 1e-3);
+
+        for _ in 0..1000 {
+            state_vector.update_sensor_data(&gps_fix(position, GpsFixType::Fix3D));
+            state_vector.predict(0.01);
+        }
+
+        assert!((state_vector.sensor_data.gps_position - position).norm() < 1e-2);
+        assert!(
+            (state_vector.get_position() - position).norm() < 10.0,
+            "{:?}",
+            state_vector.get_position()
+        );
+    }
+
+    /// Heading of the vehicle's body z axis, which starts out pointing north
+    fn heading(state_vector: &StateVector) -> f32 {
+        let nose = state_vector
+            .get_orientation()
+            .transform_vector(&Vector3::z());
+
+        nose.x.atan2(nose.z)
+    }
+
+    #[test]
+    fn test_magnetometer_limits_heading_drift() {
+        // Down and to the north, in microtesla
+        let field = Vector3::new(0.0, -40.0, 25.0);
+        let gyroscope_bias = Vector3::new(0.0, 0.1, 0.0);
+
+        let mut config = FcuConfig::default();
+        config.magnetometer_hard_iron_offset = Vector3::new(5.0, 0.0, 0.0).into();
+
+        let mut gyro_only = StateVector::new(&config);
+        let mut with_magnetometer = StateVector::new(&config);
+
+        let mut sensor_calibration = with_magnetometer.sensor_calibration.clone();
+        sensor_calibration.magnetic_field_reference = Some(field);
+        with_magnetometer.update_calibration(sensor_calibration);
+
+        let gyroscope = FcuSensorData::Gyroscope {
+            angular_velocity: gyroscope_bias.into(),
+            raw_data: Vector3::new(0, 0, 0).into(),
+        };
+        let magnetometer = FcuSensorData::Magnetometer {
+            magnetic_field: (field + Vector3::new(5.0, 0.0, 0.0)).into(),
+            raw_data: Vector3::new(0, 0, 0).into(),
+        };
+
+        for _ in 0..400 {
+            gyro_only.update_sensor_data(&gyroscope);
+            gyro_only.update_sensor_data(&magnetometer);
+            gyro_only.predict(0.05);
+
+            with_magnetometer.update_sensor_data(&gyroscope);
+            with_magnetometer.update_sensor_data(&magnetometer);
+            with_magnetometer.predict(0.05);
+        }
+
+        assert_eq!(with_magnetometer.sensor_data.magnetometer, field);
+        assert!(heading(&gyro_only).abs() > 1.0, "{}", heading(&gyro_only));
+        assert!(
+            heading(&with_magnetometer).abs() < 0.2,
+            "{}",
+            heading(&with_magnetometer)
+        );
+
+        // Not fused while the motor is burning
+        with_magnetometer.set_magnetometer_enabled(false);
+        for _ in 0..100 {
+            with_magnetometer.update_sensor_data(&gyroscope);
+            with_magnetometer.update_sensor_data(&magnetometer);
+            with_magnetometer.predict(0.05);
+        }
+
+        assert!(heading(&with_magnetometer).abs() > 0.3);
+    }
 }
@@ -0,0 +1,134 @@
+use nalgebra::Vector3;
+use serde::Serialize;
+use shared::fcu_hal::{FcuConfig, GpsFixType};
+
+#[allow(unused_imports)]
+use num_traits::Float;
+
+// WGS84 ellipsoid
+const EARTH_SEMI_MAJOR_AXIS_M: f64 = 6378137.0;
+const EARTH_ECCENTRICITY_SQUARED: f64 = 6.69437999014e-3;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize)]
+pub struct GeodeticPosition {
+    pub latitude: f64,  // Degrees
+    pub longitude: f64, // Degrees
+    pub altitude: f32,  // Meters above mean sea level
+}
+
+/// Which fixes are good enough to fuse
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct GpsGate {
+    min_satellites: u8,
+    max_hdop: f32,
+}
+
+impl GpsGate {
+    pub fn new(config: &FcuConfig) -> Self {
+        Self {
+            min_satellites: config.gps_min_satellites,
+            max_hdop: config.gps_max_hdop,
+        }
+    }
+
+    pub fn accepts(&self, fix_type: GpsFixType, satellites: u8, hdop: f32) -> bool {
+        fix_type == GpsFixType::Fix3D && satellites >= self.min_satellites && hdop <= self.max_hdop
+    }
+}
+
+/// Position in the launch frame ([east, up, north] from the origin). Flat earth around the
+/// origin, which is plenty over the range of a flight
+pub fn geodetic_to_launch_frame(
+    origin: &GeodeticPosition,
+    position: &GeodeticPosition,
+) -> Vector3<f32> {
+    let (north_radius_m, east_radius_m) = earth_radii_m(origin);
+
+    let north = (position.latitude - origin.latitude).to_radians() * north_radius_m;
+    let east = (position.longitude - origin.longitude).to_radians() * east_radius_m;
+    let up = position.altitude - origin.altitude;
+
+    Vector3::new(east as f32, up, north as f32)
+}
+
+/// Inverse of geodetic_to_launch_frame
+pub fn launch_frame_to_geodetic(
+    origin: &GeodeticPosition,
+    position: &Vector3<f32>,
+) -> GeodeticPosition {
+    let (north_radius_m, east_radius_m) = earth_radii_m(origin);
+
+    GeodeticPosition {
+        latitude: origin.latitude + (position.z as f64 / north_radius_m).to_degrees(),
+        longitude: origin.longitude + (position.x as f64 / east_radius_m).to_degrees(),
+        altitude: origin.altitude + position.y,
+    }
+}
+
+pub fn ned_to_launch_frame(velocity_ned: &Vector3<f32>) -> Vector3<f32> {
+    Vector3::new(velocity_ned.y, -velocity_ned.z, velocity_ned.x)
+}
+
+pub fn launch_frame_to_ned(velocity: &Vector3<f32>) -> Vector3<f32> {
+    Vector3::new(velocity.z, velocity.x, -velocity.y)
+}
+
+/// Meters per radian of latitude and of longitude at the origin
+fn earth_radii_m(origin: &GeodeticPosition) -> (f64, f64) {
+    let latitude = origin.latitude.to_radians();
+    let altitude = origin.altitude as f64;
+    let denominator = 1.0 - EARTH_ECCENTRICITY_SQUARED * latitude.sin().powi(2);
+
+    let meridian_radius_m =
+        EARTH_SEMI_MAJOR_AXIS_M * (1.0 - EARTH_ECCENTRICITY_SQUARED) / denominator.powf(1.5);
+    let prime_vertical_radius_m = EARTH_SEMI_MAJOR_AXIS_M / denominator.sqrt();
+
+    (
+        meridian_radius_m + altitude,
+        (prime_vertical_radius_m + altitude) * latitude.cos(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ORIGIN: GeodeticPosition = GeodeticPosition {
+        latitude: 32.990254,
+        longitude: -106.974998,
+        altitude: 1401.0,
+    };
+
+    #[test]
+    fn test_launch_frame_round_trip() {
+        let position = Vector3::new(-350.0, 3000.0, 1200.0);
+        let geodetic = launch_frame_to_geodetic(&ORIGIN, &position);
+
+        // A degree of latitude is ~111 km
+        assert!((geodetic.latitude - ORIGIN.latitude - 1200.0 / 111e3).abs() < 1e-4);
+        assert!(geodetic.longitude < ORIGIN.longitude);
+
+        let round_trip = geodetic_to_launch_frame(&ORIGIN, &geodetic);
+        assert!((round_trip - position).norm() < 1e-2);
+
+        let velocity_ned = Vector3::new(1.0, 2.0, -3.0);
+        assert_eq!(
+            ned_to_launch_frame(&velocity_ned),
+            Vector3::new(2.0, 3.0, 1.0)
+        );
+        assert_eq!(
+            launch_frame_to_ned(&ned_to_launch_frame(&velocity_ned)),
+            velocity_ned
+        );
+    }
+
+    #[test]
+    fn test_gps_gate() {
+        let gate = GpsGate::new(&FcuConfig::default());
+
+        assert!(gate.accepts(GpsFixType::Fix3D, 8, 1.2));
+        assert!(!gate.accepts(GpsFixType::Fix2D, 8, 1.2));
+        assert!(!gate.accepts(GpsFixType::Fix3D, 3, 1.2));
+        assert!(!gate.accepts(GpsFixType::Fix3D, 8, 20.0));
+    }
+}
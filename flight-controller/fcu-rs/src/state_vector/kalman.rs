@@ -3,10 +3,10 @@ use num_traits::Float;
 use serde::{Deserialize, Serialize};
 use shared::fcu_hal::FcuConfig;
 // state_vector = [x, y, z, vx, vy, vz, ax, ay, az, w, i, j, k, avx, avy, avz]
-// measure = [x, y, z, by, ax, ay, az, avx, avy, avz]
+// measure = [x, y, z, by, ax, ay, az, avx, avy, avz, vx, vy, vz, heading_error]
 
 pub(super) const STATE_LEN: usize = 16;
-pub(super) const MEASURE_LEN: usize = 10;
+pub(super) const MEASURE_LEN: usize = 14;
 pub(super) const SIGMA_BASE: usize = STATE_LEN;
 pub(super) const SIGMA_LEN: usize = 2 * SIGMA_BASE + 1;
 pub(super) const SIGMA_LEN_MINUS_1: usize = SIGMA_LEN - 1;
@@ -41,18 +41,6 @@ impl KalmanFilter {
         let mut initial_state = SVector::<f32, STATE_LEN>::zeros();
         initial_state[9] = 1.0;
 
-        let mut measurement_noise_cov = SMatrix::<f32, MEASURE_LEN, MEASURE_LEN>::zeros();
-        measurement_noise_cov[(0, 0)] = config.gps_noise_std_dev.x.powi(2); // x
-        measurement_noise_cov[(1, 1)] = config.gps_noise_std_dev.y.powi(2); // y
-        measurement_noise_cov[(2, 2)] = config.gps_noise_std_dev.z.powi(2); // z
-        measurement_noise_cov[(3, 3)] = config.barometer_noise_std_dev.powi(2); // baro
-        measurement_noise_cov[(4, 4)] = config.accelerometer_noise_std_dev.x.powi(2); // ax
-        measurement_noise_cov[(5, 5)] = config.accelerometer_noise_std_dev.y.powi(2); // ay
-        measurement_noise_cov[(6, 6)] = config.accelerometer_noise_std_dev.z.powi(2); // az
-        measurement_noise_cov[(7, 7)] = config.gyro_noise_std_dev.x.powi(2); // avx
-        measurement_noise_cov[(8, 8)] = config.gyro_noise_std_dev.y.powi(2); // avy
-        measurement_noise_cov[(9, 9)] = config.gyro_noise_std_dev.z.powi(2); // avz
-
         let mut wm = SVector::<f32, SIGMA_LEN>::zeros();
         let mut wc = SVector::<f32, SIGMA_LEN>::zeros();
 
@@ -79,7 +67,7 @@ impl KalmanFilter {
             state_cov: SMatrix::<f32, STATE_LEN, STATE_LEN>::identity() * 1e-1,
             process_noise_cov: SMatrix::<f32, STATE_LEN, STATE_LEN>::identity()
                 * config.kalman_process_variance,
-            measurement_noise_cov,
+            measurement_noise_cov: measurement_noise_cov(config),
             wm,
             wc,
             sigma_scaling: ALPHA.powi(2) * ((STATE_LEN as f32) + KAPPA),
@@ -160,6 +148,16 @@ impl KalmanFilter {
         &mut self,
         measurement: &SVector<f32, MEASURE_LEN>,
         measurement_model: &SMatrix<f32, MEASURE_LEN, STATE_LEN>,
+    ) {
+        self.update_nonlinear(measurement, |state| measurement_model * state);
+    }
+
+    /// Update for measurements that aren't a linear function of the state, the measurement
+    /// function is applied to each sigma point
+    pub fn update_nonlinear(
+        &mut self,
+        measurement: &SVector<f32, MEASURE_LEN>,
+        measurement_fn: impl Fn(&SVector<f32, STATE_LEN>) -> SVector<f32, MEASURE_LEN>,
     ) {
         // println!("kalman.update()");
         // println!("\t{:?}", self.state);
@@ -171,7 +169,7 @@ impl KalmanFilter {
         // Apply measurement model to the sigma points to get predicted measurements at k given k-1
         let mut y_k_km1 = [SVector::<f32, MEASURE_LEN>::zeros(); SIGMA_LEN];
         for i in 0..SIGMA_LEN {
-            y_k_km1[i] = measurement_model * &sp_k_km1[i];
+            y_k_km1[i] = measurement_fn(&sp_k_km1[i]);
         }
 
         // Combine predicted sigma points to get predicted measurement at k
@@ -250,17 +248,7 @@ impl KalmanFilter {
         silprintln!("Updating kalman config");
         self.process_noise_cov =
             SMatrix::<f32, STATE_LEN, STATE_LEN>::identity() * config.kalman_process_variance;
-        self.measurement_noise_cov[(0, 0)] = config.gps_noise_std_dev.x.powi(2); // x
-        self.measurement_noise_cov[(1, 1)] = config.gps_noise_std_dev.y.powi(2); // y
-        self.measurement_noise_cov[(2, 2)] = config.gps_noise_std_dev.z.powi(2); // z
-        self.measurement_noise_cov[(3, 3)] = config.barometer_noise_std_dev.powi(2); // baro
-        self.measurement_noise_cov[(4, 4)] = config.accelerometer_noise_std_dev.x.powi(2); // ax
-        self.measurement_noise_cov[(5, 5)] = config.accelerometer_noise_std_dev.y.powi(2); // ay
-        self.measurement_noise_cov[(6, 6)] = config.accelerometer_noise_std_dev.z.powi(2); // az
-        self.measurement_noise_cov[(7, 7)] = config.gps_noise_std_dev.x.powi(2); // avx
-        self.measurement_noise_cov[(8, 8)] = config.gps_noise_std_dev.y.powi(2); // avy
-        self.measurement_noise_cov[(9, 9)] = config.gps_noise_std_dev.z.powi(2);
-        // avz
+        self.measurement_noise_cov = measurement_noise_cov(config);
     }
 
     pub fn update_acceleration(&mut self, acceleration: Vector3<f32>) {
@@ -287,19 +275,52 @@ impl KalmanFilter {
         self.update(&measurement, &measurement_matrix);
     }
 
-    pub fn update_gps(&mut self, gps: Vector3<f32>) {
-        // println!("kalman.update_gps({:?})", gps);
+    pub fn update_gps(&mut self, position: Vector3<f32>, velocity: Vector3<f32>) {
+        // println!("kalman.update_gps({:?}, {:?})", position, velocity);
         let mut measurement = SVector::<f32, MEASURE_LEN>::zeros();
-        measurement.fixed_rows_mut::<3>(0).copy_from(&gps);
+        measurement.fixed_rows_mut::<3>(0).copy_from(&position);
+        measurement.fixed_rows_mut::<3>(10).copy_from(&velocity);
 
         let mut measurement_matrix = SMatrix::<f32, MEASURE_LEN, STATE_LEN>::zeros();
         measurement_matrix[(0, 0)] = 1.0;
         measurement_matrix[(1, 1)] = 1.0;
         measurement_matrix[(2, 2)] = 1.0;
+        measurement_matrix[(10, 3)] = 1.0;
+        measurement_matrix[(11, 4)] = 1.0;
+        measurement_matrix[(12, 5)] = 1.0;
 
         self.update(&measurement, &measurement_matrix);
     }
 
+    /// Corrects heading from a calibrated body frame field, by how far the estimated orientation
+    /// turns it from the reference field captured on the pad
+    pub fn update_magnetic_field(
+        &mut self,
+        magnetic_field: Vector3<f32>,
+        reference_field: Vector3<f32>,
+    ) {
+        // println!("kalman.update_magnetic_field({:?})", magnetic_field);
+        let orientation = state_orientation(&self.state);
+        let heading_error = wrap_angle(
+            horizontal_heading(&orientation.transform_vector(&magnetic_field))
+                - horizontal_heading(&reference_field),
+        );
+
+        // The error rather than the heading itself is measured so sigma points never straddle
+        // the wrap around at +-pi
+        let measurement = SVector::<f32, MEASURE_LEN>::zeros();
+
+        self.update_nonlinear(&measurement, |state| {
+            // Sigma points only differ by their turn about up, otherwise the field's inclination
+            // lets a heading error be corrected by tilting the vehicle
+            let turn = state_orientation(state) * orientation.inverse();
+
+            let mut predicted = SVector::<f32, MEASURE_LEN>::zeros();
+            predicted[13] = wrap_angle(heading_error + 2.0 * turn.j.atan2(turn.w));
+            predicted
+        });
+    }
+
     pub fn update_gyroscope(&mut self, angular_velocity: Vector3<f32>) {
         let mut measurement = SVector::<f32, MEASURE_LEN>::zeros();
         measurement
@@ -383,6 +404,40 @@ impl KalmanFilter {
     }
 }
 
+fn measurement_noise_cov(config: &FcuConfig) -> SMatrix<f32, MEASURE_LEN, MEASURE_LEN> {
+    let mut measurement_noise_cov = SMatrix::<f32, MEASURE_LEN, MEASURE_LEN>::zeros();
+    measurement_noise_cov[(0, 0)] = config.gps_noise_std_dev.x.powi(2); // x
+    measurement_noise_cov[(1, 1)] = config.gps_noise_std_dev.y.powi(2); // y
+    measurement_noise_cov[(2, 2)] = config.gps_noise_std_dev.z.powi(2); // z
+    measurement_noise_cov[(3, 3)] = config.barometer_noise_std_dev.powi(2); // baro
+    measurement_noise_cov[(4, 4)] = config.accelerometer_noise_std_dev.x.powi(2); // ax
+    measurement_noise_cov[(5, 5)] = config.accelerometer_noise_std_dev.y.powi(2); // ay
+    measurement_noise_cov[(6, 6)] = config.accelerometer_noise_std_dev.z.powi(2); // az
+    measurement_noise_cov[(7, 7)] = config.gyro_noise_std_dev.x.powi(2); // avx
+    measurement_noise_cov[(8, 8)] = config.gyro_noise_std_dev.y.powi(2); // avy
+    measurement_noise_cov[(9, 9)] = config.gyro_noise_std_dev.z.powi(2); // avz
+    measurement_noise_cov[(10, 10)] = config.gps_velocity_noise_std_dev.x.powi(2); // vx
+    measurement_noise_cov[(11, 11)] = config.gps_velocity_noise_std_dev.y.powi(2); // vy
+    measurement_noise_cov[(12, 12)] = config.gps_velocity_noise_std_dev.z.powi(2); // vz
+    measurement_noise_cov[(13, 13)] = config.magnetometer_heading_noise_std_dev.powi(2); // heading
+
+    measurement_noise_cov
+}
+
+fn state_orientation(state: &SVector<f32, STATE_LEN>) -> UnitQuaternion<f32> {
+    UnitQuaternion::from_quaternion(Quaternion::new(state[9], state[10], state[11], state[12]))
+}
+
+/// Angle of the field's horizontal part clockwise from north, in the [east, up, north] launch frame
+fn horizontal_heading(field: &Vector3<f32>) -> f32 {
+    field.x.atan2(field.z)
+}
+
+/// Into (-pi, pi]
+fn wrap_angle(angle: f32) -> f32 {
+    angle.sin().atan2(angle.cos())
+}
+
 fn integrate_angular_velocity_rk4(
     quat: UnitQuaternion<f32>,
     ang_vel: Vector3<f32>,
@@ -0,0 +1,47 @@
+use nalgebra::{Matrix3, Vector3};
+use serde::Serialize;
+use shared::fcu_hal::FcuConfig;
+
+/// Hard and soft iron correction, fit on the ground for the assembled vehicle
+#[derive(Debug, Clone, Copy, Serialize)]
+pub struct MagnetometerCalibration {
+    hard_iron_offset: Vector3<f32>,
+    soft_iron_matrix: Matrix3<f32>,
+}
+
+impl MagnetometerCalibration {
+    pub fn new(config: &FcuConfig) -> Self {
+        Self {
+            hard_iron_offset: config.magnetometer_hard_iron_offset.into(),
+            soft_iron_matrix: config.magnetometer_soft_iron_matrix.into(),
+        }
+    }
+
+    pub fn apply(&self, magnetic_field: &Vector3<f32>) -> Vector3<f32> {
+        self.soft_iron_matrix * (magnetic_field - self.hard_iron_offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hard_and_soft_iron_correction() {
+        let mut config = FcuConfig::default();
+        assert_eq!(
+            MagnetometerCalibration::new(&config).apply(&Vector3::new(1.0, 2.0, 3.0)),
+            Vector3::new(1.0, 2.0, 3.0)
+        );
+
+        // Offset by 10 in x, and squashed to half in z
+        config.magnetometer_hard_iron_offset = Vector3::new(10.0, 0.0, 0.0).into();
+        config.magnetometer_soft_iron_matrix =
+            Matrix3::from_diagonal(&Vector3::new(1.0, 1.0, 2.0)).into();
+
+        assert_eq!(
+            MagnetometerCalibration::new(&config).apply(&Vector3::new(30.0, -20.0, 15.0)),
+            Vector3::new(20.0, -20.0, 30.0)
+        );
+    }
+}
@@ -1,4 +1,7 @@
-use crate::Fcu;
+use crate::{
+    flight_events::{ApogeeDetector, LandingDetector, LaunchDetector},
+    Fcu,
+};
 use nalgebra::Vector3;
 use shared::{
     comms_hal::{NetworkAddress, Packet},
@@ -10,6 +13,8 @@ mod armed;
 mod ascent;
 mod calibrating;
 mod descent;
+mod descent_drogue_parachute;
+mod descent_main_parachute;
 mod idle;
 mod ignition;
 mod landed;
@@ -25,6 +30,10 @@ pub struct Calibrating {
     magnetometer: Vector3<f32>,
     barometric_altitude: f32,
     data_count: u32,
+    gps_latitude: f64,
+    gps_longitude: f64,
+    gps_altitude: f64,
+    gps_count: u32,
     zero: bool,
 }
 
@@ -34,15 +43,30 @@ pub struct Armed;
 #[derive(Debug)]
 pub struct Ignition {
     time_since_state_entry: f32,
+    launch_detector: LaunchDetector,
 }
 
 #[derive(Debug)]
 pub struct Ascent {
     time_since_state_entry: f32,
+    apogee_detector: ApogeeDetector,
+}
+
+#[derive(Debug)]
+pub struct Descent {
+    time_until_drogue_s: f32,
+    landing_detector: LandingDetector,
 }
 
 #[derive(Debug)]
-pub struct Descent;
+pub struct DescentDrogueParachute {
+    landing_detector: LandingDetector,
+}
+
+#[derive(Debug)]
+pub struct DescentMainParachute {
+    landing_detector: LandingDetector,
+}
 
 #[derive(Debug)]
 pub struct Landed;
@@ -55,6 +79,8 @@ pub enum FsmState {
     Ignition(Ignition),
     Ascent(Ascent),
     Descent(Descent),
+    DescentDrogueParachute(DescentDrogueParachute),
+    DescentMainParachute(DescentMainParachute),
     Landed(Landed),
 }
 
@@ -67,6 +93,8 @@ impl FsmState {
             FsmState::Ignition(state) => state,
             FsmState::Ascent(state) => state,
             FsmState::Descent(state) => state,
+            FsmState::DescentDrogueParachute(state) => state,
+            FsmState::DescentMainParachute(state) => state,
             FsmState::Landed(state) => state,
         }
     }
@@ -79,6 +107,8 @@ impl FsmState {
             FsmState::Ignition(_) => VehicleState::Ignition,
             FsmState::Ascent(_) => VehicleState::Ascent,
             FsmState::Descent(_) => VehicleState::Descent,
+            FsmState::DescentDrogueParachute(_) => VehicleState::DescentDrogueParachute,
+            FsmState::DescentMainParachute(_) => VehicleState::DescentMainParachute,
             FsmState::Landed(_) => VehicleState::Landed,
         }
     }
@@ -14,7 +14,7 @@ impl<'f> ControllerState<FsmState, Fcu<'f>> for Armed {
         packets: &[(NetworkAddress, Packet)],
     ) -> Option<FsmState> {
         if self.received_ignition_command(packets) && self.igniter_has_continuity(fcu) {
-            return Some(Ignition::new());
+            return Some(Ignition::new(fcu));
         }
 
         None
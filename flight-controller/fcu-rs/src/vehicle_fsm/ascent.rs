@@ -1,5 +1,5 @@
 use super::{Ascent, Descent, FsmState};
-use crate::Fcu;
+use crate::{flight_events::ApogeeDetector, Fcu};
 use shared::{
     comms_hal::{NetworkAddress, Packet},
     ControllerState,
@@ -14,8 +14,16 @@ impl<'f> ControllerState<FsmState, Fcu<'f>> for Ascent {
     ) -> Option<FsmState> {
         self.time_since_state_entry += dt;
 
-        if self.begun_falling(fcu) {
-            return Some(Descent::new());
+        if !fcu.state_vector.get_magnetometer_enabled() && self.burned_out(fcu) {
+            silprintln!("Burnout, fusing magnetometer again");
+            fcu.state_vector.set_magnetometer_enabled(true);
+        }
+
+        if self.reached_apogee(fcu, dt) {
+            return Some(Descent::new(fcu, self.time_since_state_entry));
+        } else if self.drogue_backup_timer_expired(fcu) {
+            silprintln!("Apogee not detected, deploying drogue on the backup timer");
+            return Some(Descent::new(fcu, self.time_since_state_entry));
         }
 
         None
@@ -31,21 +39,30 @@ impl<'f> ControllerState<FsmState, Fcu<'f>> for Ascent {
 }
 
 impl Ascent {
-    pub fn new() -> FsmState {
+    pub fn new(fcu: &Fcu) -> FsmState {
         FsmState::Ascent(Self {
             time_since_state_entry: 0.0,
+            apogee_detector: ApogeeDetector::new(fcu.state_vector.get_barometric_altitude()),
         })
     }
 
-    fn begun_falling(&mut self, fcu: &mut Fcu) -> bool {
-        if fcu.state_vector.get_position().y < 5.0 {
-            return false;
-        }
+    /// Once the motor stops pushing, drag is all that's left and it pulls down
+    fn burned_out(&self, fcu: &mut Fcu) -> bool {
+        fcu.state_vector.get_acceleration().y < 0.0
+    }
 
-        if fcu.state_vector.get_velocity().y < 0.0 {
-            return true;
+    fn drogue_backup_timer_expired(&self, fcu: &mut Fcu) -> bool {
+        match fcu.config.recovery_config {
+            Some(config) => self.time_since_state_entry >= config.drogue_backup_time_s,
+            None => false,
         }
+    }
+
+    fn reached_apogee(&mut self, fcu: &mut Fcu, dt: f32) -> bool {
+        let velocity = fcu.state_vector.get_velocity();
+        let barometric_altitude = fcu.state_vector.get_barometric_altitude();
 
-        false
+        self.apogee_detector
+            .update(&fcu.config, dt, &velocity, barometric_altitude)
     }
 }
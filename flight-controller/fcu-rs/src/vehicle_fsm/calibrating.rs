@@ -1,5 +1,8 @@
 use super::{Calibrating, FsmState, Idle};
-use crate::{state_vector::SensorCalibrationData, Fcu};
+use crate::{
+    state_vector::{gps::GeodeticPosition, SensorCalibrationData},
+    Fcu,
+};
 use nalgebra::{UnitQuaternion, UnitVector3, Vector3};
 use shared::{
     comms_hal::{NetworkAddress, Packet},
@@ -38,15 +41,6 @@ impl<'f> ControllerState<FsmState, Fcu<'f>> for Calibrating {
 
         silprintln!("Accel calib: {:?}", accelerometer_avg);
 
-        let sensor_calibration = SensorCalibrationData {
-            accelerometer: -accelerometer_avg,
-            gyroscope: -self.gyroscope / (self.data_count as f32),
-            magnetometer: -self.magnetometer / (self.data_count as f32),
-            barometeric_altitude: -self.barometric_altitude / (self.data_count as f32),
-        };
-
-        fcu.state_vector.update_calibration(sensor_calibration);
-
         if self.zero {
             let up = UnitVector3::new_normalize(Vector3::new(0.0, 1.0, 0.0).normalize());
             let measured_up = -UnitVector3::new_normalize(down);
@@ -66,6 +60,29 @@ impl<'f> ControllerState<FsmState, Fcu<'f>> for Calibrating {
                 fcu.state_vector.kalman.zero(zeroed_orientation);
             }
         }
+
+        // Taken once the orientation has been zeroed, as it's what brings the field into the
+        // launch frame
+        let magnetometer_avg = self.magnetometer / (self.data_count as f32);
+        let magnetic_field_reference = (magnetometer_avg.norm() > 0.0).then(|| {
+            fcu.state_vector
+                .get_orientation()
+                .transform_vector(&magnetometer_avg)
+        });
+
+        let sensor_calibration = SensorCalibrationData {
+            accelerometer: -accelerometer_avg,
+            gyroscope: -self.gyroscope / (self.data_count as f32),
+            magnetic_field_reference,
+            barometeric_altitude: -self.barometric_altitude / (self.data_count as f32),
+            gps_origin: (self.gps_count > 0).then(|| GeodeticPosition {
+                latitude: self.gps_latitude / (self.gps_count as f64),
+                longitude: self.gps_longitude / (self.gps_count as f64),
+                altitude: (self.gps_altitude / (self.gps_count as f64)) as f32,
+            }),
+        };
+
+        fcu.state_vector.update_calibration(sensor_calibration);
     }
 }
 
@@ -78,6 +95,10 @@ impl Calibrating {
             magnetometer: Vector3::zeros(),
             barometric_altitude: 0.0,
             data_count: 0,
+            gps_latitude: 0.0,
+            gps_longitude: 0.0,
+            gps_altitude: 0.0,
+            gps_count: 0,
             zero,
         })
     }
@@ -100,5 +121,13 @@ impl Calibrating {
         self.barometric_altitude += baro_altitude;
 
         self.data_count += 1;
+
+        // Only fixes that passed the gate are averaged into the origin
+        if let Some(gps_fix) = fcu.state_vector.sensor_data.gps_fix {
+            self.gps_latitude += gps_fix.latitude;
+            self.gps_longitude += gps_fix.longitude;
+            self.gps_altitude += gps_fix.altitude as f64;
+            self.gps_count += 1;
+        }
     }
 }
@@ -1,5 +1,5 @@
-use super::{Descent, FsmState, Landed};
-use crate::Fcu;
+use super::{Descent, DescentDrogueParachute, FsmState, Landed};
+use crate::{flight_events::LandingDetector, recovery, Fcu};
 use shared::{
     comms_hal::{NetworkAddress, Packet},
     ControllerState,
@@ -9,13 +9,21 @@ impl<'f> ControllerState<FsmState, Fcu<'f>> for Descent {
     fn update(
         &mut self,
         fcu: &mut Fcu,
-        _dt: f32,
+        dt: f32,
         _packets: &[(NetworkAddress, Packet)],
     ) -> Option<FsmState> {
-        if self.has_landed(fcu) {
+        if has_landed(&mut self.landing_detector, fcu, dt) {
             return Some(Landed::new());
         }
 
+        // Without recovery hardware there's nothing to do but fall
+        if fcu.config.recovery_config.is_some() {
+            self.time_until_drogue_s -= dt;
+            if self.time_until_drogue_s <= 0.0 {
+                return Some(DescentDrogueParachute::new());
+            }
+        }
+
         None
     }
 
@@ -29,11 +37,21 @@ impl<'f> ControllerState<FsmState, Fcu<'f>> for Descent {
 }
 
 impl Descent {
-    pub fn new() -> FsmState {
-        FsmState::Descent(Self {})
-    }
+    pub fn new(fcu: &Fcu, time_since_liftoff_s: f32) -> FsmState {
+        let time_until_drogue_s = match fcu.config.recovery_config {
+            Some(config) => recovery::time_until_drogue_s(&config, time_since_liftoff_s),
+            None => 0.0,
+        };
 
-    fn has_landed(&mut self, fcu: &mut Fcu) -> bool {
-        fcu.state_vector.get_position().y < 1e-3
+        FsmState::Descent(Self {
+            time_until_drogue_s,
+            landing_detector: LandingDetector::new(),
+        })
     }
 }
+
+pub(super) fn has_landed(landing_detector: &mut LandingDetector, fcu: &Fcu, dt: f32) -> bool {
+    let barometric_altitude = fcu.state_vector.get_barometric_altitude();
+
+    landing_detector.update(&fcu.config, dt, barometric_altitude)
+}
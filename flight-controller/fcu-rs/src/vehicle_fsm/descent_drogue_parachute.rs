@@ -0,0 +1,50 @@
+use super::{descent::has_landed, DescentDrogueParachute, DescentMainParachute, FsmState, Landed};
+use crate::{flight_events::LandingDetector, Fcu};
+use shared::{
+    comms_hal::{NetworkAddress, Packet},
+    ControllerState,
+};
+
+impl<'f> ControllerState<FsmState, Fcu<'f>> for DescentDrogueParachute {
+    fn update(
+        &mut self,
+        fcu: &mut Fcu,
+        dt: f32,
+        _packets: &[(NetworkAddress, Packet)],
+    ) -> Option<FsmState> {
+        if has_landed(&mut self.landing_detector, fcu, dt) {
+            return Some(Landed::new());
+        }
+
+        if self.reached_main_deploy_altitude(fcu) {
+            return Some(DescentMainParachute::new());
+        }
+
+        None
+    }
+
+    fn enter_state(&mut self, fcu: &mut Fcu) {
+        if let Some(config) = fcu.config.recovery_config {
+            fcu.fire_pyro(config.drogue_channel, config.pyro_fire_duration_s);
+        }
+    }
+
+    fn exit_state(&mut self, _fcu: &mut Fcu) {
+        // Nothing
+    }
+}
+
+impl DescentDrogueParachute {
+    pub fn new() -> FsmState {
+        FsmState::DescentDrogueParachute(Self {
+            landing_detector: LandingDetector::new(),
+        })
+    }
+
+    fn reached_main_deploy_altitude(&self, fcu: &mut Fcu) -> bool {
+        match fcu.config.recovery_config {
+            Some(config) => fcu.state_vector.get_position().y <= config.main_deploy_altitude_m,
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use super::{descent::has_landed, DescentMainParachute, FsmState, Landed};
+use crate::{flight_events::LandingDetector, Fcu};
+use shared::{
+    comms_hal::{NetworkAddress, Packet},
+    ControllerState,
+};
+
+impl<'f> ControllerState<FsmState, Fcu<'f>> for DescentMainParachute {
+    fn update(
+        &mut self,
+        fcu: &mut Fcu,
+        dt: f32,
+        _packets: &[(NetworkAddress, Packet)],
+    ) -> Option<FsmState> {
+        if has_landed(&mut self.landing_detector, fcu, dt) {
+            return Some(Landed::new());
+        }
+
+        None
+    }
+
+    fn enter_state(&mut self, fcu: &mut Fcu) {
+        if let Some(config) = fcu.config.recovery_config {
+            fcu.fire_pyro(config.main_channel, config.pyro_fire_duration_s);
+        }
+    }
+
+    fn exit_state(&mut self, _fcu: &mut Fcu) {
+        // Nothing
+    }
+}
+
+impl DescentMainParachute {
+    pub fn new() -> FsmState {
+        FsmState::DescentMainParachute(Self {
+            landing_detector: LandingDetector::new(),
+        })
+    }
+}
@@ -15,7 +15,7 @@ impl<'f> ControllerState<FsmState, Fcu<'f>> for Idle {
     ) -> Option<FsmState> {
         if let Some(zero) = self.received_start_calibration(packets) {
             return Some(Calibrating::new(fcu, zero));
-        } else if self.received_arming_command(packets) {
+        } else if self.received_arming_command(packets) && fcu.recovery_has_continuity() {
             return Some(Armed::new());
         }
 
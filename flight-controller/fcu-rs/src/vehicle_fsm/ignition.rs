@@ -1,9 +1,9 @@
 use super::{Ascent, FsmState, Idle, Ignition};
-use crate::Fcu;
+use crate::{flight_events::LaunchDetector, Fcu};
 use shared::{
     comms_hal::{NetworkAddress, Packet},
     fcu_hal::OutputChannel,
-    ControllerState,
+    ControllerState, GRAVITY,
 };
 
 impl<'f> ControllerState<FsmState, Fcu<'f>> for Ignition {
@@ -13,10 +13,18 @@ impl<'f> ControllerState<FsmState, Fcu<'f>> for Ignition {
         dt: f32,
         _packets: &[(NetworkAddress, Packet)],
     ) -> Option<FsmState> {
-        if self.begun_accelerating(fcu) {
+        let launched = self.launched(fcu, dt);
+
+        // Holding the estimate to the pad until the launch is confirmed would skew the velocity
+        if self.launch_detector.accelerating() {
             fcu.state_vector.set_landed(false);
-            return Some(Ascent::new());
+        }
+
+        if launched {
+            return Some(Ascent::new(fcu));
         } else if self.timed_out(fcu) {
+            fcu.state_vector.set_landed(true);
+            fcu.state_vector.set_magnetometer_enabled(true);
             return Some(Idle::new());
         }
 
@@ -28,6 +36,11 @@ impl<'f> ControllerState<FsmState, Fcu<'f>> for Ignition {
     fn enter_state(&mut self, fcu: &mut Fcu) {
         fcu.driver
             .set_output_channel(OutputChannel::SolidMotorIgniter, true);
+
+        // Back on at burnout, see Ascent
+        if fcu.config.magnetometer_disabled_during_boost {
+            fcu.state_vector.set_magnetometer_enabled(false);
+        }
     }
 
     fn exit_state(&mut self, fcu: &mut Fcu) {
@@ -37,20 +50,24 @@ impl<'f> ControllerState<FsmState, Fcu<'f>> for Ignition {
 }
 
 impl Ignition {
-    pub fn new() -> FsmState {
+    pub fn new(fcu: &Fcu) -> FsmState {
         FsmState::Ignition(Ignition {
             time_since_state_entry: 0.0,
+            launch_detector: LaunchDetector::new(fcu.state_vector.get_barometric_altitude()),
         })
     }
 
-    fn begun_accelerating(&self, fcu: &mut Fcu) -> bool {
-        let acceleration = fcu.state_vector.get_acceleration().magnitude();
-        // silprintln!("Idle: Time {} - landed? {} - accel {:?} - accel sense {:?}", fcu.driver.timestamp(), fcu.state_vector.get_landed(), fcu.state_vector.get_acceleration(), fcu.state_vector.sensor_data.accelerometer);
-        if acceleration > fcu.config.startup_acceleration_threshold {
-            return true;
+    fn launched(&mut self, fcu: &mut Fcu, dt: f32) -> bool {
+        // Measured from rest on the pad, whether or not the estimate is still held there
+        let mut acceleration = fcu.state_vector.get_acceleration();
+        if !fcu.state_vector.get_landed() {
+            acceleration.y += GRAVITY;
         }
 
-        false
+        let barometric_altitude = fcu.state_vector.get_barometric_altitude();
+
+        self.launch_detector
+            .update(&fcu.config, dt, &acceleration, barometric_altitude)
     }
 
     fn timed_out(&self, fcu: &mut Fcu) -> bool {
@@ -1,8 +1,21 @@
 use pyo3::prelude::*;
-use shared::ecu_hal::EcuCommand;
+use shared::{
+    comms_hal::{NetworkAddress, Packet},
+    ecu_hal::{
+        BinaryOutputMask, EcuBinaryOutput, EcuCommand, EcuConfig, EcuResponse, EcuSensor,
+        EngineState, EngineStateMask, RedlineAction, RedlineConfig, RedlineInput, SensorVoteConfig,
+        SequenceStep, ThrottlePoint, ValveInterlock, MAX_INTERLOCKS, MAX_REDLINES,
+        MAX_SEQUENCE_STEPS, MAX_THROTTLE_PROFILE_POINTS,
+    },
+    SensorConfig,
+};
+use strum::IntoEnumIterator;
 
 use crate::CommandHandler;
 
+/// (input, min, max, persistence_s, engine_states, action)
+type RedlineArgs = (String, Option<f32>, Option<f32>, f32, Vec<String>, String);
+
 #[pyclass]
 pub struct Engine {
     #[pyo3(get, set)]
@@ -20,9 +33,306 @@ impl Engine {
         }
     }
 
-    pub fn fire(&mut self, py: Python) -> PyResult<()> {
+    /// Replaces the whole ECU config, given as JSON. Raises if the ECU rejects it
+    pub fn configure(&mut self, py: Python, config_json: &str) -> PyResult<()> {
+        let config: EcuConfig = serde_json::from_str(config_json)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{:?}", e)))?;
+
+        send_config_command(
+            &self.command_handler.borrow(py),
+            self.ecu_index,
+            EcuCommand::ConfigureEcu(config),
+        )
+    }
+
+    /// Has the ECU convert the sensor's raw ADC counts itself, with the config given as JSON
+    pub fn configure_sensor(
+        &mut self,
+        py: Python,
+        sensor: String,
+        config_json: &str,
+    ) -> PyResult<()> {
+        let config: SensorConfig = serde_json::from_str(config_json)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{:?}", e)))?;
+
+        send_config_command(
+            &self.command_handler.borrow(py),
+            self.ecu_index,
+            EcuCommand::ConfigureSensor {
+                sensor: parse_variant::<EcuSensor>(&sensor)?,
+                config,
+            },
+        )
+    }
+
+    pub fn fire(&mut self, py: Python) -> PyResult<u16> {
         self.command_handler
             .borrow(py)
             .send_ecu_command(self.ecu_index, EcuCommand::FireEngine)
     }
+
+    /// Points are (time_s, chamber_pressure_pa, pump_duty), sorted by time
+    pub fn set_throttle_profile(
+        &mut self,
+        py: Python,
+        points: Vec<(f32, f32, Option<f32>)>,
+    ) -> PyResult<()> {
+        if points.len() > MAX_THROTTLE_PROFILE_POINTS {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Throttle profile can have at most {} points",
+                MAX_THROTTLE_PROFILE_POINTS
+            )));
+        }
+
+        let command_handler = self.command_handler.borrow(py);
+        command_handler.send_ecu_command(self.ecu_index, EcuCommand::ClearThrottleProfile)?;
+
+        for (index, (time_s, chamber_pressure_pa, pump_duty)) in points.into_iter().enumerate() {
+            command_handler.send_ecu_command(
+                self.ecu_index,
+                EcuCommand::SetThrottleProfilePoint {
+                    index: index as u8,
+                    point: Some(ThrottlePoint {
+                        time_s,
+                        chamber_pressure_pa,
+                        pump_duty,
+                    }),
+                },
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Replaces all the redlines. Each is (input, min, max, persistence_s, engine_states,
+    /// action), with the input, engine states and action given by name, e.g.
+    /// ("EngineChamberPressure", None, 5e6, 0.05, ["Firing"], "ShutdownEngine"). The input is
+    /// a sensor, or "MixtureRatio" for the estimated engine mixture ratio
+    pub fn set_redlines(&mut self, py: Python, redlines: Vec<RedlineArgs>) -> PyResult<()> {
+        if redlines.len() > MAX_REDLINES {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Can have at most {} redlines",
+                MAX_REDLINES
+            )));
+        }
+
+        let command_handler = self.command_handler.borrow(py);
+        command_handler.send_ecu_command(self.ecu_index, EcuCommand::ClearRedlines)?;
+
+        for (index, (input, min, max, persistence_s, engine_states, action)) in
+            redlines.into_iter().enumerate()
+        {
+            let mut engine_state_mask = EngineStateMask::empty();
+            for engine_state in engine_states {
+                engine_state_mask =
+                    engine_state_mask.with(parse_variant::<EngineState>(&engine_state)?);
+            }
+
+            let input = match input.as_str() {
+                "MixtureRatio" => RedlineInput::MixtureRatio,
+                sensor => RedlineInput::Sensor(parse_variant::<EcuSensor>(sensor)?),
+            };
+
+            let action = match action.as_str() {
+                "Alert" => RedlineAction::Alert,
+                "ShutdownEngine" => RedlineAction::ShutdownEngine,
+                "SafeEcu" => RedlineAction::SafeEcu,
+                _ => {
+                    return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                        "Unknown redline action {}",
+                        action
+                    )))
+                }
+            };
+
+            command_handler.send_ecu_command(
+                self.ecu_index,
+                EcuCommand::SetRedline {
+                    index: index as u8,
+                    redline: Some(RedlineConfig {
+                        input,
+                        min,
+                        max,
+                        persistence_s,
+                        engine_states: engine_state_mask,
+                        action,
+                    }),
+                },
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Hands the outputs over to direct commands until exited or no command arrives for
+    /// timeout_s. Only works while every FSM is idle
+    pub fn enter_manual_mode(&mut self, py: Python, timeout_s: f32) -> PyResult<u16> {
+        self.command_handler
+            .borrow(py)
+            .send_ecu_command(self.ecu_index, EcuCommand::EnterManualMode { timeout_s })
+    }
+
+    pub fn exit_manual_mode(&mut self, py: Python) -> PyResult<u16> {
+        self.command_handler
+            .borrow(py)
+            .send_ecu_command(self.ecu_index, EcuCommand::ExitManualMode)
+    }
+
+    /// Replaces all the manual mode interlocks. Each is (valves, allowed_while_sparking), with
+    /// the valves given by name, e.g. (["EngineFuelValve", "EngineOxidizerValve"], True)
+    pub fn set_interlocks(
+        &mut self,
+        py: Python,
+        interlocks: Vec<(Vec<String>, bool)>,
+    ) -> PyResult<()> {
+        if interlocks.len() > MAX_INTERLOCKS {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Can have at most {} interlocks",
+                MAX_INTERLOCKS
+            )));
+        }
+
+        let command_handler = self.command_handler.borrow(py);
+        command_handler.send_ecu_command(self.ecu_index, EcuCommand::ClearInterlocks)?;
+
+        for (index, (valves, allowed_while_sparking)) in interlocks.into_iter().enumerate() {
+            let mut valve_mask = BinaryOutputMask::empty();
+            for valve in valves {
+                valve_mask = valve_mask.with(parse_variant::<EcuBinaryOutput>(&valve)?);
+            }
+
+            command_handler.send_ecu_command(
+                self.ecu_index,
+                EcuCommand::SetInterlock {
+                    index: index as u8,
+                    interlock: Some(ValveInterlock {
+                        valves: valve_mask,
+                        allowed_while_sparking,
+                    }),
+                },
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Replaces the on-board sequence with a JSON list of steps. The ECU rejects invalid steps
+    /// and any change while the sequence is running
+    pub fn set_sequence(&mut self, py: Python, steps_json: &str) -> PyResult<()> {
+        let steps: Vec<SequenceStep> = serde_json::from_str(steps_json)
+            .map_err(|e| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("{:?}", e)))?;
+
+        if steps.len() > MAX_SEQUENCE_STEPS {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(format!(
+                "Sequence can have at most {} steps",
+                MAX_SEQUENCE_STEPS
+            )));
+        }
+
+        let command_handler = self.command_handler.borrow(py);
+        command_handler.send_ecu_command(self.ecu_index, EcuCommand::ClearSequence)?;
+
+        for (index, step) in steps.into_iter().enumerate() {
+            command_handler.send_ecu_command(
+                self.ecu_index,
+                EcuCommand::SetSequenceStep {
+                    index: index as u8,
+                    step: Some(step),
+                },
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Starts the sequence, or resumes it if it's holding
+    pub fn start_sequence(&mut self, py: Python) -> PyResult<u16> {
+        self.command_handler
+            .borrow(py)
+            .send_ecu_command(self.ecu_index, EcuCommand::StartSequence)
+    }
+
+    pub fn hold_sequence(&mut self, py: Python) -> PyResult<u16> {
+        self.command_handler
+            .borrow(py)
+            .send_ecu_command(self.ecu_index, EcuCommand::HoldSequence)
+    }
+
+    /// Stops the sequence and safes the ECU
+    pub fn abort_sequence(&mut self, py: Python) -> PyResult<u16> {
+        self.command_handler
+            .borrow(py)
+            .send_ecu_command(self.ecu_index, EcuCommand::AbortSequence)
+    }
+
+    /// Votes the sensor with up to two redundant sensors. No redundant sensors turns voting off
+    pub fn set_sensor_vote(
+        &mut self,
+        py: Python,
+        sensor: String,
+        redundant_sensors: Vec<String>,
+        max_disagreement: f32,
+    ) -> PyResult<()> {
+        if redundant_sensors.len() > 2 {
+            return Err(PyErr::new::<pyo3::exceptions::PyValueError, _>(
+                "Can vote with at most 2 redundant sensors",
+            ));
+        }
+
+        let mut vote = SensorVoteConfig {
+            redundant_sensors: [None, None],
+            max_disagreement,
+        };
+        for (index, redundant_sensor) in redundant_sensors.iter().enumerate() {
+            vote.redundant_sensors[index] = Some(parse_variant::<EcuSensor>(redundant_sensor)?);
+        }
+
+        let command_handler = self.command_handler.borrow(py);
+        command_handler.send_ecu_command(
+            self.ecu_index,
+            EcuCommand::ConfigureSensorVote {
+                sensor: parse_variant::<EcuSensor>(&sensor)?,
+                vote: (!redundant_sensors.is_empty()).then_some(vote),
+            },
+        )?;
+
+        Ok(())
+    }
+}
+
+/// Sends a config change and waits for the ECU to accept or reject it
+fn send_config_command(
+    command_handler: &CommandHandler,
+    ecu_index: u8,
+    command: EcuCommand,
+) -> PyResult<()> {
+    let response = command_handler.send_packet_and_receive_response(
+        Packet::EcuCommand(command),
+        NetworkAddress::EngineController(ecu_index),
+        |packet: &Packet| {
+            matches!(
+                packet,
+                Packet::EcuResponse(EcuResponse::ConfigAccepted | EcuResponse::ConfigRejected(_))
+            )
+        },
+    )?;
+
+    match response {
+        Packet::EcuResponse(EcuResponse::ConfigAccepted) => Ok(()),
+        Packet::EcuResponse(EcuResponse::ConfigRejected(error)) => {
+            Err(PyErr::new::<pyo3::exceptions::PyException, _>(format!(
+                "ECU rejected config: {:?}",
+                error
+            )))
+        }
+        _ => Err(PyErr::new::<pyo3::exceptions::PyException, _>(
+            "Failed to configure ECU",
+        )),
+    }
+}
+
+fn parse_variant<T: IntoEnumIterator + core::fmt::Debug>(name: &str) -> PyResult<T> {
+    T::iter()
+        .find(|variant| format!("{:?}", variant) == name)
+        .ok_or_else(|| PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Unknown {}", name)))
 }
@@ -24,7 +24,7 @@ impl Igniter {
         }
     }
 
-    pub fn fire(&mut self, py: Python) -> PyResult<()> {
+    pub fn fire(&mut self, py: Python) -> PyResult<u16> {
         self.command_handler
             .borrow(py)
             .send_ecu_command(self.ecu_index, EcuCommand::FireIgniter)
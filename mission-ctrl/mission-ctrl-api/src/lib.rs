@@ -3,7 +3,16 @@ pub mod igniter;
 pub mod pump;
 pub mod tank;
 
-use std::{cell::RefCell, error::Error, net::TcpStream, rc::Rc, sync::Mutex};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    error::Error,
+    net::TcpStream,
+    rc::Rc,
+    sync::Mutex,
+    thread,
+    time::{Duration, Instant},
+};
 
 use big_brother::BigBrother;
 use pyo3::{
@@ -12,12 +21,16 @@ use pyo3::{
 };
 use serde::Serialize;
 use shared::{
-    comms_hal::{NetworkAddress, Packet},
+    comms_hal::{CommandResult, NetworkAddress, Packet},
     ecu_hal::EcuCommand,
+    fcu_hal::VehicleCommand,
     COMMS_NETWORK_MAP_SIZE,
 };
 use tungstenite::{connect, stream::MaybeTlsStream, WebSocket};
 
+// How long to wait before checking for an ack again when nothing has been received
+const ACK_POLL_INTERVAL: Duration = Duration::from_millis(1);
+
 pub type CommandHandlerBigBrother =
     Rc<RefCell<BigBrother<'static, COMMS_NETWORK_MAP_SIZE, Packet, NetworkAddress>>>;
 
@@ -29,6 +42,13 @@ enum CommandHandlerBackend {
 #[pyclass(unsendable)]
 pub struct CommandHandler {
     backend: Mutex<CommandHandlerBackend>,
+    /// Whether commands block until they're acknowledged, raising if they're rejected
+    #[pyo3(get, set)]
+    pub wait_for_acks: bool,
+    #[pyo3(get, set)]
+    pub ack_timeout_s: f64,
+    next_command_id: Cell<u16>,
+    command_results: RefCell<HashMap<u16, CommandResult>>,
 }
 
 #[pymethods]
@@ -40,16 +60,79 @@ impl CommandHandler {
 
         println!("Websocket HTTP code: {}", response.status());
 
-        Ok(Self {
-            backend: Mutex::new(CommandHandlerBackend::Websocket(websocket)),
-        })
+        Ok(Self::with_backend(
+            CommandHandlerBackend::Websocket(websocket),
+            true,
+        ))
+    }
+
+    /// The result of an earlier command as e.g. "Accepted" or "Rejected(TanksNotPressurized)",
+    /// or None if its ack hasn't been received yet
+    pub fn command_result(&self, id: u16) -> Option<String> {
+        self.command_results
+            .borrow()
+            .get(&id)
+            .map(|result| format!("{:?}", result))
+    }
+
+    /// Blocks until the command is acknowledged and returns its result like command_result.
+    /// Raises if it was rejected or the ack didn't arrive within timeout_s. NotApplicable is
+    /// returned rather than raised, so repeating a command that's already in effect is fine
+    pub fn wait_for_ack(&self, id: u16, timeout_s: f64) -> PyResult<String> {
+        let deadline = Instant::now() + Duration::from_secs_f64(timeout_s);
+
+        let result = loop {
+            if let Some(result) = self.command_results.borrow().get(&id).copied() {
+                break result;
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                return Err(PyErr::new::<pyo3::exceptions::PyTimeoutError, _>(format!(
+                    "No ack received for command {}",
+                    id
+                )));
+            }
+
+            let packet_with_address = self
+                .backend
+                .lock()
+                .map_err(|e| PyErr::new::<pyo3::exceptions::PyException, _>(format!("{:?}", e)))?
+                .recv_packet_timeout(deadline - now);
+
+            match packet_with_address {
+                Ok(packet_with_address) => self.record_packet(&packet_with_address.packet),
+                // Let go of the backend for a moment so the ack has a chance to arrive
+                Err(_) => thread::sleep(ACK_POLL_INTERVAL),
+            }
+        };
+
+        match result {
+            CommandResult::Rejected(rejection) => {
+                Err(PyErr::new::<pyo3::exceptions::PyException, _>(format!(
+                    "Command {} rejected: {:?}",
+                    id, rejection
+                )))
+            }
+            result => Ok(format!("{:?}", result)),
+        }
     }
 }
 
 impl CommandHandler {
+    /// Acks are only recorded as packets are received, so commands don't wait for them here.
+    /// The simulation would otherwise stall, as it can't step while a command is blocking
     pub fn from_big_brother(big_brother: CommandHandlerBigBrother) -> Self {
+        Self::with_backend(CommandHandlerBackend::BigBrother(big_brother), false)
+    }
+
+    fn with_backend(backend: CommandHandlerBackend, wait_for_acks: bool) -> Self {
         Self {
-            backend: Mutex::new(CommandHandlerBackend::BigBrother(big_brother)),
+            backend: Mutex::new(backend),
+            wait_for_acks,
+            ack_timeout_s: 1.0,
+            next_command_id: Cell::new(0),
+            command_results: RefCell::new(HashMap::new()),
         }
     }
 
@@ -84,6 +167,8 @@ impl CommandHandler {
 
             match packet_with_address {
                 Ok(packet_with_address) => {
+                    self.record_packet(&packet_with_address.packet);
+
                     if packet_with_address.address == destination {
                         if callback_fn(&packet_with_address.packet) {
                             return Ok(packet_with_address.packet);
@@ -97,12 +182,53 @@ impl CommandHandler {
         }
     }
 
-    pub fn send_ecu_command(&self, ecu_index: u8, command: EcuCommand) -> PyResult<()> {
-        self.send_packet(
-            Packet::EcuCommand(command),
+    /// Returns the command's id, which its ack is matched against
+    pub fn send_ecu_command(&self, ecu_index: u8, command: EcuCommand) -> PyResult<u16> {
+        let id = self.next_command_id();
+        self.send_tracked_packet(
+            id,
+            Packet::TrackedEcuCommand { id, command },
             NetworkAddress::EngineController(ecu_index),
         )
     }
+
+    pub fn send_vehicle_command(&self, command: VehicleCommand) -> PyResult<u16> {
+        let id = self.next_command_id();
+        self.send_tracked_packet(
+            id,
+            Packet::TrackedVehicleCommand { id, command },
+            NetworkAddress::FlightController,
+        )
+    }
+
+    /// Keeps the results of any acks, for packets received outside of the handler
+    pub fn record_packet(&self, packet: &Packet) {
+        if let Packet::CommandAck(ack) = packet {
+            self.command_results.borrow_mut().insert(ack.id, ack.result);
+        }
+    }
+
+    fn send_tracked_packet(
+        &self,
+        id: u16,
+        packet: Packet,
+        destination: NetworkAddress,
+    ) -> PyResult<u16> {
+        self.command_results.borrow_mut().remove(&id);
+        self.send_packet(packet, destination)?;
+
+        if self.wait_for_acks {
+            self.wait_for_ack(id, self.ack_timeout_s)?;
+        }
+
+        Ok(id)
+    }
+
+    fn next_command_id(&self) -> u16 {
+        let id = self.next_command_id.get();
+        self.next_command_id.set(id.wrapping_add(1));
+        id
+    }
 }
 
 impl CommandHandlerBackend {
@@ -140,6 +266,29 @@ impl CommandHandlerBackend {
         Ok(())
     }
 
+    /// Like recv_packet, but the websocket gives up after timeout rather than blocking until
+    /// something arrives. Big brother never blocks
+    fn recv_packet_timeout(
+        &mut self,
+        timeout: Duration,
+    ) -> Result<shared::comms_hal::PacketWithAddress, Box<dyn Error>> {
+        self.set_read_timeout(Some(timeout))?;
+        let packet_with_address = self.recv_packet();
+        self.set_read_timeout(None)?;
+
+        packet_with_address
+    }
+
+    fn set_read_timeout(&mut self, timeout: Option<Duration>) -> Result<(), Box<dyn Error>> {
+        if let Self::Websocket(web_socket) = self {
+            if let MaybeTlsStream::Plain(stream) = web_socket.get_mut() {
+                stream.set_read_timeout(timeout)?;
+            }
+        }
+
+        Ok(())
+    }
+
     fn recv_packet(&mut self) -> Result<shared::comms_hal::PacketWithAddress, Box<dyn Error>> {
         match self {
             Self::Websocket(web_socket) => loop {
@@ -26,28 +26,28 @@ impl Tank {
         }
     }
 
-    pub fn press(&mut self, py: Python) -> PyResult<()> {
+    pub fn press(&mut self, py: Python) -> PyResult<u16> {
         self.command_handler.borrow(py).send_ecu_command(
             self.ecu_index,
             EcuCommand::SetTankState((self.tank_type, TankState::Pressurized)),
         )
     }
 
-    pub fn idle(&mut self, py: Python) -> PyResult<()> {
+    pub fn idle(&mut self, py: Python) -> PyResult<u16> {
         self.command_handler.borrow(py).send_ecu_command(
             self.ecu_index,
             EcuCommand::SetTankState((self.tank_type, TankState::Idle)),
         )
     }
 
-    pub fn vent(&mut self, py: Python) -> PyResult<()> {
+    pub fn vent(&mut self, py: Python) -> PyResult<u16> {
         self.command_handler.borrow(py).send_ecu_command(
             self.ecu_index,
             EcuCommand::SetTankState((self.tank_type, TankState::Venting)),
         )
     }
 
-    pub fn fill(&mut self, py: Python) -> PyResult<()> {
+    pub fn fill(&mut self, py: Python) -> PyResult<u16> {
         self.command_handler.borrow(py).send_ecu_command(
             self.ecu_index,
             EcuCommand::SetTankState((self.tank_type, TankState::Filling)),
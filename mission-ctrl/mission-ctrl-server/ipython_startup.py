@@ -19,8 +19,8 @@ class _Routines:
         ox_tank.press()
 
     def depress_tanks(self):
-        fuel_tank.depress()
-        ox_tank.depress()
+        fuel_tank.vent()
+        ox_tank.vent()
 
     def idle_tanks(self):
         fuel_tank.idle()
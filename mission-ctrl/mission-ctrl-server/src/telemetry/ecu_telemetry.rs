@@ -9,7 +9,7 @@ use rocket::serde::json::{json, serde_json::Map};
 use rocket::State;
 use shared::alerts::{self, AlertBitmaskType};
 use shared::comms_hal::{NetworkAddress, Packet};
-use shared::ecu_hal::{EcuAlert, EcuTelemetry};
+use shared::ecu_hal::{EcuAlert, EcuBinaryOutput, EcuLinearOutput, EcuOutputState, EcuTelemetry};
 
 use strum::{EnumProperty, IntoEnumIterator};
 
@@ -115,9 +115,13 @@ impl EcuTelemetryHandler {
 
                 match packet {
                     Packet::EcuTelemetry(EcuTelemetry::Telemetry(frame)) => {
-                        let telemetry_value = rocket::serde::json::to_value(&frame)
+                        let mut telemetry_value = rocket::serde::json::to_value(&frame)
                             .expect("Failed to convert telemetry frame to serde value");
 
+                        // The bitmask and duty array are keyed by name so the UI doesn't have
+                        // to know the output indices
+                        telemetry_value["outputs"] = output_state_json(&frame.outputs);
+
                         ecu_data.insert(String::from("telemetry"), telemetry_value);
 
                         self.telemetry_counter += 1;
@@ -232,6 +236,30 @@ impl EcuTelemetryHandler {
     }
 }
 
+fn output_state_json(outputs: &EcuOutputState) -> Value {
+    let mut binary_valves = Map::new();
+    for valve in EcuBinaryOutput::iter() {
+        binary_valves.insert(
+            format!("{:?}", valve),
+            json!(outputs.binary_valves.contains(valve)),
+        );
+    }
+
+    let mut linear_outputs = Map::new();
+    for output in EcuLinearOutput::iter() {
+        linear_outputs.insert(
+            format!("{:?}", output),
+            json!(outputs.linear_outputs[output.index()]),
+        );
+    }
+
+    json!({
+        "binary_valves": binary_valves,
+        "sparking": outputs.sparking,
+        "linear_outputs": linear_outputs,
+    })
+}
+
 pub fn telemetry_thread(observer_handler: Arc<ObserverHandler>) {
     observer_handler.register_observer_thread();
 
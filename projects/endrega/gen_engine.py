@@ -92,6 +92,16 @@ def generate_config():
                 "engine_startup_timeout_s": 1.0,
                 "engine_firing_duration_s": 10.0,
                 "engine_shutdown_duration_s": 0.5,
+                "pump_pressure_pid": {
+                    "kp": 1.0 / (500.0 * 6894.76), # Full duty for 500 PSI of error
+                    "ki": 0.5 / (500.0 * 6894.76),
+                    "kd": 0.0,
+                    "output_min": 0.0,
+                    "output_max": 1.0,
+                    "max_output_rate_per_s": 2.0,
+                },
+                "purge": None,
+                "performance": None,
             },
             "igniter_config": {
                 "startup_timeout_s": 1.0,
@@ -100,8 +110,10 @@ def generate_config():
                 "test_firing_duration_s": 0.75,
                 "shutdown_duration_s": 0.5,
                 "max_throat_temp_k": 500.0,
+                "purge": None,
             },
             "telemetry_rate_s": 0.02,
+            "safe_tank_action": "Vent",
         }
     }
 
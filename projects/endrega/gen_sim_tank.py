@@ -58,6 +58,8 @@ def generate_config():
                 'fill_valve': "FuelFillValve",
                 'press_min_threshold_pa': 500.0 * 6894.76, # PSI to Pascals
                 'press_max_threshold_pa': 900.0 * 6894.76, # PSI to Pascals
+                'press_relief_threshold_pa': 1000.0 * 6894.76, # PSI to Pascals
+                'press_valve_min_cycle_time_s': 0.1,
             },
             "oxidizer_tank_config": {
                 'press_valve': None,
@@ -65,6 +67,8 @@ def generate_config():
                 'fill_valve': "OxidizerFillValve",
                 'press_min_threshold_pa': 500.0 * 6894.76, # PSI to Pascals
                 'press_max_threshold_pa': 900.0 * 6894.76, # PSI to Pascals
+                'press_relief_threshold_pa': 1000.0 * 6894.76, # PSI to Pascals
+                'press_valve_min_cycle_time_s': 0.1,
             },
         }
     }
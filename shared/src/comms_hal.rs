@@ -3,7 +3,7 @@ use serde::{Deserialize, Serialize};
 
 use crate::{
     alerts,
-    ecu_hal::{EcuCommand, EcuResponse, EcuTelemetry, EcuTelemetryFrame},
+    ecu_hal::{EcuCommand, EcuConfigError, EcuResponse, EcuTelemetry, EcuTelemetryFrame},
     fcu_hal::{FcuDebugInfo, FcuSensorData, FcuTelemetryFrame, VehicleCommand},
     streamish_hal::StreamishCommand,
     SensorConfig,
@@ -52,8 +52,18 @@ pub enum Packet {
     VehicleCommand(VehicleCommand),
     EcuCommand(EcuCommand),
     StreamishCommand(StreamishCommand),
+    /// Handled like the untracked commands, but answered with a CommandAck carrying the same id
+    TrackedVehicleCommand {
+        id: u16,
+        command: VehicleCommand,
+    },
+    TrackedEcuCommand {
+        id: u16,
+        command: EcuCommand,
+    },
 
     // -- Data -- //
+    CommandAck(CommandAck),
     EcuTelemetry(EcuTelemetry),
     EcuResponse(EcuResponse),
     FcuTelemetry(FcuTelemetryFrame),
@@ -68,6 +78,40 @@ pub enum Packet {
     DoNothing,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommandAck {
+    pub id: u16,
+    pub result: CommandResult,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CommandResult {
+    Accepted,
+    Rejected(CommandRejection),
+    // The command is valid but does nothing in the current state, like shutting down an idle engine
+    NotApplicable,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CommandRejection {
+    // The command needs the targeted FSMs to be idle
+    NotIdle,
+    // Firing needs every configured tank to be pressurized
+    TanksNotPressurized,
+    // The FSMs are paused while outputs are driven directly
+    ManualModeActive,
+    // The sequence owns the ECU until it completes or is aborted
+    SequenceActive,
+    InterlockBlocked,
+    // An index or value in the command is out of range
+    InvalidArgument,
+    InvalidConfig(EcuConfigError),
+    // The config has no engine, igniter, tank or pump for the command to act on
+    NotConfigured,
+    BadMagicNumber,
+    NoContinuity,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PacketWithAddress {
     pub address: NetworkAddress,
@@ -78,8 +122,9 @@ pub mod tests_data {
     use super::*;
     use crate::{
         ecu_hal::{
-            self, EcuBinaryOutput, EcuConfig, EngineConfig, EngineState, IgniterConfig,
-            IgniterState, TankConfig,
+            self, CommsWatchdogConfig, EcuBinaryOutput, EcuConfig, EngineConfig, EngineState,
+            IgniterConfig, IgniterState, PidConfig, PumpCavitationConfig, PurgeConfig,
+            RedlineAction, SafeTankAction, TankConfig, ValveMonitorConfig,
         },
         fcu_hal, SensorCalibration, RESET_MAGIC_NUMBER,
     };
@@ -99,6 +144,8 @@ pub mod tests_data {
         postmin: 10.0,
         postmax: 99.9,
         calibration: Some(SENSOR_CALIBRATION),
+        max_rate_per_s: Some(12.5),
+        stale_timeout_s: None,
     };
 
     pub const ADDRESS_TEST_DEFAULTS: [NetworkAddress; 8] = [
@@ -123,6 +170,25 @@ pub mod tests_data {
         }),
         Packet::EcuCommand(EcuCommand::SetSparking(true)),
         Packet::StreamishCommand(StreamishCommand::StartCameraStream { port: 25565 }),
+        Packet::TrackedVehicleCommand {
+            id: 0xFFFF,
+            command: VehicleCommand::Arm {
+                magic_number: fcu_hal::ARMING_MAGIC_NUMBER,
+            },
+        },
+        Packet::TrackedEcuCommand {
+            id: 513,
+            command: EcuCommand::SetTankState((
+                ecu_hal::TankType::OxidizerMain,
+                ecu_hal::TankState::Pressurized,
+            )),
+        },
+        Packet::CommandAck(CommandAck {
+            id: 513,
+            result: CommandResult::Rejected(CommandRejection::InvalidConfig(
+                ecu_hal::EcuConfigError::TankThresholdsOutOfOrder(ecu_hal::TankType::FuelMain),
+            )),
+        }),
         Packet::FcuTelemetry(FcuTelemetryFrame::default()),
         Packet::EcuTelemetry(EcuTelemetry::Telemetry(EcuTelemetryFrame {
             timestamp: 0xABAD_1234_FEDC_DEAD,
@@ -136,6 +202,24 @@ pub mod tests_data {
             oxidizer_pump_state: ecu_hal::PumpState::Idle,
             fuel_pump_outlet_pressure_pa: 19522.4,
             oxidizer_pump_outlet_pressure_pa: 96420.425,
+            faulted_sensors: 0b1010_0001,
+            manual_mode: true,
+            sequence_state: ecu_hal::SequenceState::Holding,
+            sequence_step: 3,
+            outputs: ecu_hal::EcuOutputState {
+                binary_valves: ecu_hal::BinaryOutputMask::empty()
+                    .with(EcuBinaryOutput::IgniterFuelValve)
+                    .with(EcuBinaryOutput::OxidizerVentValve),
+                sparking: true,
+                linear_outputs: [0.75, 1.0],
+            },
+            engine_performance: Some(ecu_hal::EnginePerformance {
+                fuel_mass_flow_kg_s: 0.412,
+                oxidizer_mass_flow_kg_s: 0.923,
+                mixture_ratio: Some(2.24),
+                c_star_m_s: None,
+                thrust_n: 1534.2,
+            }),
         })),
         Packet::EcuResponse(EcuResponse::Config(EcuConfig {
             engine_config: Some(EngineConfig {
@@ -153,6 +237,27 @@ pub mod tests_data {
                 engine_startup_timeout_s: 0.14962,
                 engine_firing_duration_s: Some(79.21968),
                 engine_shutdown_duration_s: 0.14962,
+                pump_pressure_pid: PidConfig {
+                    kp: 0.14962,
+                    ki: 1234.567,
+                    kd: 0.0,
+                    output_min: -749.248,
+                    output_max: 749.248,
+                    max_output_rate_per_s: 79.21968,
+                },
+                purge: Some(PurgeConfig {
+                    lead_s: Some(0.14962),
+                    delay_s: 0.0,
+                    duration_s: 1234.567,
+                }),
+                performance: Some(ecu_hal::EnginePerformanceConfig {
+                    fuel_injector_cda_m2: 8.2e-6,
+                    oxidizer_injector_cda_m2: 8.5e-6,
+                    fuel_density_kg_m3: 846.0,
+                    oxidizer_density_kg_m3: 1220.0,
+                    throat_area_m2: 7.07e-4,
+                    thrust_coefficient: 1.4,
+                }),
             }),
             igniter_config: Some(IgniterConfig {
                 startup_timeout_s: 749.248,
@@ -161,6 +266,7 @@ pub mod tests_data {
                 test_firing_duration_s: 749.248,
                 shutdown_duration_s: 749.248,
                 max_throat_temp_k: 749.248,
+                purge: None,
             }),
             fuel_tank_config: Some(TankConfig {
                 press_valve: None,
@@ -168,24 +274,43 @@ pub mod tests_data {
                 vent_valve: Some(EcuBinaryOutput::FuelVentValve),
                 press_min_threshold_pa: 0.14962,
                 press_max_threshold_pa: 1542.012,
+                press_relief_threshold_pa: Some(2000.5),
+                press_valve_min_cycle_time_s: 0.25,
             }),
             oxidizer_tank_config: None,
             telemetry_rate_s: 0.945218,
+            comms_watchdog_config: Some(CommsWatchdogConfig {
+                timeout_s: 0.4821,
+                tank_action: SafeTankAction::Idle,
+            }),
+            safe_tank_action: SafeTankAction::Vent,
+            valve_monitor_config: Some(ValveMonitorConfig {
+                travel_time_s: 0.25,
+                abort_sequence_on_fault: true,
+            }),
+            pump_cavitation_config: Some(PumpCavitationConfig {
+                fuel_vapor_pressure_pa: 4.1,
+                oxidizer_vapor_pressure_pa: 5.2e6,
+                min_npsh_margin_pa: 1.5e5,
+                persistence_s: 0.05,
+                action: RedlineAction::ShutdownEngine,
+            }),
         })),
         Packet::AlertBitmask(0xAAAA_AAAA),
         Packet::EnableDebugInfo(true),
         Packet::FcuDebugInfo(FcuDebugInfo::default()),
-        Packet::FcuDebugSensorMeasurement(FcuSensorData::Accelerometer {
-            acceleration: Vector3 {
-                x: 0.1,
-                y: 0.2,
-                z: 0.3,
-            },
-            raw_data: Vector3 {
-                x: 14,
-                y: -72,
-                z: 19852,
+        Packet::FcuDebugSensorMeasurement(FcuSensorData::Gps {
+            latitude: 32.990254,
+            longitude: -106.974998,
+            altitude: 1401.0,
+            velocity_ned: Vector3 {
+                x: 0.5,
+                y: -1.2,
+                z: -250.0,
             },
+            fix_type: fcu_hal::GpsFixType::Fix3D,
+            satellites: 11,
+            hdop: 0.9,
         }),
         Packet::Heartbeat,
         Packet::DoNothing,
@@ -1,7 +1,7 @@
 use core::any::Any;
 
 use serde::{Deserialize, Serialize};
-use strum::EnumProperty;
+use strum::{EnumCount, EnumProperty};
 use strum_macros::{EnumCount as EnumCountMacro, EnumDiscriminants, EnumIter};
 
 use crate::{SensorConfig, SensorData};
@@ -38,13 +38,30 @@ pub enum PumpState {
     Pumping,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, EnumIter)]
+pub enum SequenceState {
+    Idle,
+    Running,
+    Holding,
+    Complete,
+    Aborted,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PumpControlMode {
+    // Duty cycle is set directly by SetPumpDuty
+    OpenLoop,
+    // Duty cycle is regulated to hold the pump outlet at the injector pressure setpoint
+    ClosedLoop,
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TankType {
     FuelMain,
     OxidizerMain,
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, EnumCountMacro, EnumIter)]
 pub enum PumpType {
     FuelMain,
     OxidizerMain,
@@ -75,6 +92,8 @@ pub enum EcuSensor {
     OxidizerPumpInducerPressure,
 }
 
+// Carries a whole EcuConfig, which can't be boxed without an allocator
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum EcuCommand {
     SetBinaryValve {
@@ -82,15 +101,59 @@ pub enum EcuCommand {
         state: bool,
     },
     SetSparking(bool),
+    /// Like SetBinaryValve and SetSparking, only acted on in manual mode
+    SetLinearOutput {
+        output: EcuLinearOutput,
+        value: f32,
+    },
+    /// Leaves manual mode if no manual command arrives for timeout_s
+    EnterManualMode {
+        timeout_s: f32,
+    },
+    ExitManualMode,
+    /// Interlocks can only be changed outside of manual mode
+    SetInterlock {
+        index: u8,
+        interlock: Option<ValveInterlock>,
+    },
+    ClearInterlocks,
+    /// The sequence can only be changed while it isn't running or holding
+    SetSequenceStep {
+        index: u8,
+        step: Option<SequenceStep>,
+    },
+    ClearSequence,
+    /// Starts the sequence from the first step, or resumes it if it's holding
+    StartSequence,
+    HoldSequence,
+    /// Stops the sequence and safes the ECU
+    AbortSequence,
     FireIgniter,
     FireEngine,
     ShutdownEngine,
     SetTankState((TankType, TankState)),
     SetPumpDuty((PumpType, f32)),
+    SetPumpControlMode((PumpType, PumpControlMode)),
+    /// Uploaded a point at a time while the engine is idle, so the packets stay small
+    SetThrottleProfilePoint {
+        index: u8,
+        point: Option<ThrottlePoint>,
+    },
+    ClearThrottleProfile,
+    /// Like the throttle profile, redlines can only be changed while the engine is idle
+    SetRedline {
+        index: u8,
+        redline: Option<RedlineConfig>,
+    },
+    ClearRedlines,
     ConfigureSensor {
         sensor: EcuSensor,
         config: SensorConfig,
     },
+    ConfigureSensorVote {
+        sensor: EcuSensor,
+        vote: Option<SensorVoteConfig>,
+    },
     ConfigureEcu(EcuConfig),
     GetConfig,
 }
@@ -103,9 +166,38 @@ pub enum EcuTelemetry {
     DebugSensorMeasurement((EcuSensor, SensorData)),
 }
 
+// Carries a whole EcuConfig, which can't be boxed without an allocator
+#[allow(clippy::large_enum_variant)]
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum EcuResponse {
     Config(EcuConfig),
+    /// Reply to ConfigureEcu and ConfigureSensor
+    ConfigAccepted,
+    ConfigRejected(EcuConfigError),
+    /// Sent after Config for every sensor the ECU calibrates itself
+    ActiveSensorConfig {
+        sensor: EcuSensor,
+        config: SensorConfig,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EcuConfigError {
+    // Config can only be changed while every FSM is idle
+    NotIdle,
+    // A timeout or duration is zero or negative
+    InvalidDuration,
+    // Tank press thresholds must go min < max < relief
+    TankThresholdsOutOfOrder(TankType),
+    // Pump PID output_min must be below output_max
+    PumpOutputLimitsOutOfOrder,
+    // Valve is used by more than one tank, or by a tank and the engine/igniter
+    DuplicateValve(EcuBinaryOutput),
+    InvalidSensorConfig(EcuSensor),
+    // Injector areas, densities, throat area and thrust coefficient must all be positive
+    InvalidEnginePerformanceConfig,
+    // A number is NaN or infinite, which would slip past the range checks
+    NonFiniteValue,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, EnumCountMacro, EnumIter)]
@@ -141,6 +233,38 @@ impl EcuLinearOutput {
     }
 }
 
+impl EcuSensor {
+    pub fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, EnumIter)]
+pub enum SensorHealth {
+    NoData,
+    Ok,
+    // No reading within the sensor's stale timeout
+    Stale,
+    // Reading outside the sensor's calibrated range, e.g. disconnected or pinned to a rail
+    OutOfRange,
+    // Reading changed faster than the sensor's max rate
+    ExcessiveRate,
+}
+
+impl SensorHealth {
+    pub fn is_faulted(&self) -> bool {
+        !matches!(self, Self::NoData | Self::Ok)
+    }
+}
+
+/// Redundant sensors that are voted with a sensor when its value is read. The median of
+/// three healthy sensors is used, and two healthy sensors are averaged unless they disagree
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SensorVoteConfig {
+    pub redundant_sensors: [Option<EcuSensor>; 2],
+    pub max_disagreement: f32,
+}
+
 #[derive(Debug, Clone, Copy, EnumIter, EnumProperty, PartialEq, Eq)]
 pub enum EcuAlert {
     // Debug mode enabled for clarity while testing
@@ -186,6 +310,229 @@ pub enum EcuAlert {
     // Engine shut down because of a set timer rather than by command
     #[strum(props(severity = "0"))]
     EngineShutdownTimerExpired,
+
+    // Lost comms with mission control, so the engine, igniter and tanks were safed
+    #[strum(props(severity = "1"))]
+    MissionControlCommsLost,
+
+    // Fuel tank pressure went past the relief limit, so the tank is being vented
+    #[strum(props(severity = "1"))]
+    FuelTankOverpressure,
+
+    // Oxidizer tank pressure went past the relief limit, so the tank is being vented
+    #[strum(props(severity = "1"))]
+    OxidizerTankOverpressure,
+
+    // Fuel tank pressure reading was lost while pressurized, so the tank was vented
+    #[strum(props(severity = "1"))]
+    FuelTankPressureLost,
+
+    // Oxidizer tank pressure reading was lost while pressurized, so the tank was vented
+    #[strum(props(severity = "1"))]
+    OxidizerTankPressureLost,
+
+    // Fuel tank pressure went outside its redline
+    #[strum(props(severity = "1"))]
+    RedlineFuelTankPressure,
+
+    // Oxidizer tank pressure went outside its redline
+    #[strum(props(severity = "1"))]
+    RedlineOxidizerTankPressure,
+
+    // Igniter chamber pressure went outside its redline
+    #[strum(props(severity = "1"))]
+    RedlineIgniterChamberPressure,
+
+    // Igniter fuel injector pressure went outside its redline
+    #[strum(props(severity = "1"))]
+    RedlineIgniterFuelInjectorPressure,
+
+    // Igniter oxidizer injector pressure went outside its redline
+    #[strum(props(severity = "1"))]
+    RedlineIgniterOxidizerInjectorPressure,
+
+    // Igniter throat temperature went outside its redline
+    #[strum(props(severity = "1"))]
+    RedlineIgniterThroatTemperature,
+
+    // Engine chamber pressure went outside its redline
+    #[strum(props(severity = "1"))]
+    RedlineEngineChamberPressure,
+
+    // Engine fuel injector pressure went outside its redline
+    #[strum(props(severity = "1"))]
+    RedlineEngineFuelInjectorPressure,
+
+    // Engine oxidizer injector pressure went outside its redline
+    #[strum(props(severity = "1"))]
+    RedlineEngineOxidizerInjectorPressure,
+
+    // Engine throat temperature went outside its redline
+    #[strum(props(severity = "1"))]
+    RedlineEngineThroatTemperature,
+
+    // Fuel pump outlet pressure went outside its redline
+    #[strum(props(severity = "1"))]
+    RedlineFuelPumpOutletPressure,
+
+    // Fuel pump inlet pressure went outside its redline
+    #[strum(props(severity = "1"))]
+    RedlineFuelPumpInletPressure,
+
+    // Fuel pump inducer pressure went outside its redline
+    #[strum(props(severity = "1"))]
+    RedlineFuelPumpInducerPressure,
+
+    // Oxidizer pump outlet pressure went outside its redline
+    #[strum(props(severity = "1"))]
+    RedlineOxidizerPumpOutletPressure,
+
+    // Oxidizer pump inlet pressure went outside its redline
+    #[strum(props(severity = "1"))]
+    RedlineOxidizerPumpInletPressure,
+
+    // Oxidizer pump inducer pressure went outside its redline
+    #[strum(props(severity = "1"))]
+    RedlineOxidizerPumpInducerPressure,
+
+    // Estimated engine mixture ratio went outside its redline
+    #[strum(props(severity = "1"))]
+    RedlineMixtureRatio,
+
+    // A sensor stopped reporting
+    #[strum(props(severity = "1"))]
+    SensorStale,
+
+    // A sensor reading is outside its calibrated range
+    #[strum(props(severity = "1"))]
+    SensorOutOfRange,
+
+    // A sensor reading changed faster than is physically plausible
+    #[strum(props(severity = "1"))]
+    SensorExcessiveRate,
+
+    // Redundant sensors disagree with each other
+    #[strum(props(severity = "1"))]
+    SensorDisagreement,
+
+    // Outputs are being driven by hand rather than by the FSMs
+    #[strum(props(severity = "0"))]
+    ManualModeEnabled,
+
+    // A manual command was refused because it would break an interlock
+    #[strum(props(severity = "0"))]
+    ManualInterlockBlocked,
+
+    // The sequence was aborted by command or a hold timing out, so the ECU was safed
+    #[strum(props(severity = "1"))]
+    SequenceAborted,
+
+    // Igniter fuel valve didn't reach its commanded position in time
+    #[strum(props(severity = "1"))]
+    IgniterFuelValveFault,
+
+    // Igniter oxidizer valve didn't reach its commanded position in time
+    #[strum(props(severity = "1"))]
+    IgniterOxidizerValveFault,
+
+    // Fuel press valve didn't reach its commanded position in time
+    #[strum(props(severity = "1"))]
+    FuelPressValveFault,
+
+    // Fuel vent valve didn't reach its commanded position in time
+    #[strum(props(severity = "1"))]
+    FuelVentValveFault,
+
+    // Fuel fill valve didn't reach its commanded position in time
+    #[strum(props(severity = "1"))]
+    FuelFillValveFault,
+
+    // Oxidizer press valve didn't reach its commanded position in time
+    #[strum(props(severity = "1"))]
+    OxidizerPressValveFault,
+
+    // Oxidizer vent valve didn't reach its commanded position in time
+    #[strum(props(severity = "1"))]
+    OxidizerVentValveFault,
+
+    // Oxidizer fill valve didn't reach its commanded position in time
+    #[strum(props(severity = "1"))]
+    OxidizerFillValveFault,
+
+    // Engine fuel valve didn't reach its commanded position in time
+    #[strum(props(severity = "1"))]
+    EngineFuelValveFault,
+
+    // Engine oxidizer valve didn't reach its commanded position in time
+    #[strum(props(severity = "1"))]
+    EngineOxidizerValveFault,
+
+    // Fuel purge valve didn't reach its commanded position in time
+    #[strum(props(severity = "1"))]
+    FuelPurgeValveFault,
+
+    // Fuel pump inlet pressure stayed too close to the fuel's vapor pressure while pumping
+    #[strum(props(severity = "1"))]
+    FuelPumpCavitation,
+
+    // Oxidizer pump inlet pressure stayed too close to the oxidizer's vapor pressure while pumping
+    #[strum(props(severity = "1"))]
+    OxidizerPumpCavitation,
+}
+
+impl EcuAlert {
+    pub fn redline(input: RedlineInput) -> Self {
+        let sensor = match input {
+            RedlineInput::Sensor(sensor) => sensor,
+            RedlineInput::MixtureRatio => return Self::RedlineMixtureRatio,
+        };
+
+        match sensor {
+            EcuSensor::FuelTankPressure => Self::RedlineFuelTankPressure,
+            EcuSensor::OxidizerTankPressure => Self::RedlineOxidizerTankPressure,
+            EcuSensor::IgniterChamberPressure => Self::RedlineIgniterChamberPressure,
+            EcuSensor::IgniterFuelInjectorPressure => Self::RedlineIgniterFuelInjectorPressure,
+            EcuSensor::IgniterOxidizerInjectorPressure => {
+                Self::RedlineIgniterOxidizerInjectorPressure
+            }
+            EcuSensor::IgniterThroatTemperature => Self::RedlineIgniterThroatTemperature,
+            EcuSensor::EngineChamberPressure => Self::RedlineEngineChamberPressure,
+            EcuSensor::EngineFuelInjectorPressure => Self::RedlineEngineFuelInjectorPressure,
+            EcuSensor::EngineOxidizerInjectorPressure => {
+                Self::RedlineEngineOxidizerInjectorPressure
+            }
+            EcuSensor::EngineThroatTemperature => Self::RedlineEngineThroatTemperature,
+            EcuSensor::FuelPumpOutletPressure => Self::RedlineFuelPumpOutletPressure,
+            EcuSensor::FuelPumpInletPressure => Self::RedlineFuelPumpInletPressure,
+            EcuSensor::FuelPumpInducerPressure => Self::RedlineFuelPumpInducerPressure,
+            EcuSensor::OxidizerPumpOutletPressure => Self::RedlineOxidizerPumpOutletPressure,
+            EcuSensor::OxidizerPumpInletPressure => Self::RedlineOxidizerPumpInletPressure,
+            EcuSensor::OxidizerPumpInducerPressure => Self::RedlineOxidizerPumpInducerPressure,
+        }
+    }
+
+    pub fn valve_fault(valve: EcuBinaryOutput) -> Self {
+        match valve {
+            EcuBinaryOutput::IgniterFuelValve => Self::IgniterFuelValveFault,
+            EcuBinaryOutput::IgniterOxidizerValve => Self::IgniterOxidizerValveFault,
+            EcuBinaryOutput::FuelPressValve => Self::FuelPressValveFault,
+            EcuBinaryOutput::FuelVentValve => Self::FuelVentValveFault,
+            EcuBinaryOutput::FuelFillValve => Self::FuelFillValveFault,
+            EcuBinaryOutput::OxidizerPressValve => Self::OxidizerPressValveFault,
+            EcuBinaryOutput::OxidizerVentValve => Self::OxidizerVentValveFault,
+            EcuBinaryOutput::OxidizerFillValve => Self::OxidizerFillValveFault,
+            EcuBinaryOutput::EngineFuelValve => Self::EngineFuelValveFault,
+            EcuBinaryOutput::EngineOxidizerValve => Self::EngineOxidizerValveFault,
+            EcuBinaryOutput::FuelPurgeValve => Self::FuelPurgeValveFault,
+        }
+    }
+
+    pub fn pump_cavitation(pump: PumpType) -> Self {
+        match pump {
+            PumpType::FuelMain => Self::FuelPumpCavitation,
+            PumpType::OxidizerMain => Self::OxidizerPumpCavitation,
+        }
+    }
 }
 
 impl From<EcuAlert> for u128 {
@@ -207,6 +554,41 @@ pub struct EcuTelemetryFrame {
     pub igniter_chamber_pressure_pa: f32,
     pub fuel_pump_outlet_pressure_pa: f32,
     pub oxidizer_pump_outlet_pressure_pa: f32,
+    /// Bit per EcuSensor index, set while the sensor is faulted
+    pub faulted_sensors: u32,
+    pub manual_mode: bool,
+    pub sequence_state: SequenceState,
+    /// Index of the step being waited on, only meaningful while running or holding
+    pub sequence_step: u8,
+    pub outputs: EcuOutputState,
+    /// None unless the engine config has a performance config
+    pub engine_performance: Option<EnginePerformance>,
+}
+
+/// Mixture ratio with oxidizer flowing but no fuel. Finite rather than infinite, as JSON has no
+/// infinity and would report it as null like no flow at all, but still above any max redline
+pub const FUEL_STARVED_MIXTURE_RATIO: f32 = f32::MAX;
+
+/// Estimated from the injector and chamber pressures
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EnginePerformance {
+    pub fuel_mass_flow_kg_s: f32,
+    pub oxidizer_mass_flow_kg_s: f32,
+    /// Oxidizer over fuel mass flow, FUEL_STARVED_MIXTURE_RATIO with only oxidizer flowing and
+    /// None without any flow
+    pub mixture_ratio: Option<f32>,
+    /// Characteristic velocity, None without any flow
+    pub c_star_m_s: Option<f32>,
+    pub thrust_n: f32,
+}
+
+/// What the outputs are commanded to, whichever FSM or manual command set them
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EcuOutputState {
+    pub binary_valves: BinaryOutputMask,
+    pub sparking: bool,
+    /// Duty per EcuLinearOutput index
+    pub linear_outputs: [f32; EcuLinearOutput::COUNT],
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -254,6 +636,11 @@ pub struct EcuConfig {
     pub fuel_tank_config: Option<TankConfig>,
     pub oxidizer_tank_config: Option<TankConfig>,
     pub telemetry_rate_s: f32,
+    pub comms_watchdog_config: Option<CommsWatchdogConfig>,
+    /// What redlines and sequence aborts do with the tanks when they safe the ECU
+    pub safe_tank_action: SafeTankAction,
+    pub valve_monitor_config: Option<ValveMonitorConfig>,
+    pub pump_cavitation_config: Option<PumpCavitationConfig>,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -272,6 +659,133 @@ pub struct EngineConfig {
     pub engine_startup_timeout_s: f32,
     pub engine_firing_duration_s: Option<f32>,
     pub engine_shutdown_duration_s: f32,
+    pub pump_pressure_pid: PidConfig,
+    pub purge: Option<PurgeConfig>,
+    pub performance: Option<EnginePerformanceConfig>,
+}
+
+/// What the ECU needs to estimate flow and thrust from its pressure sensors
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct EnginePerformanceConfig {
+    /// Injector discharge coefficient times orifice area
+    pub fuel_injector_cda_m2: f32,
+    pub oxidizer_injector_cda_m2: f32,
+    pub fuel_density_kg_m3: f32,
+    pub oxidizer_density_kg_m3: f32,
+    pub throat_area_m2: f32,
+    /// Thrust over chamber pressure times throat area
+    pub thrust_coefficient: f32,
+}
+
+// Gains are in output units (e.g. duty cycle) per unit of error (e.g. Pascals)
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PidConfig {
+    pub kp: f32,
+    pub ki: f32,
+    pub kd: f32,
+    pub output_min: f32,
+    pub output_max: f32,
+    pub max_output_rate_per_s: f32,
+}
+
+pub const MAX_THROTTLE_PROFILE_POINTS: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ThrottlePoint {
+    /// Time since the engine entered the firing state
+    pub time_s: f32,
+    pub chamber_pressure_pa: f32,
+    /// Open loop duty for both pumps, None leaves the pumps on their own control
+    pub pump_duty: Option<f32>,
+}
+
+/// Setpoints to follow while firing. An empty profile holds the engine at the target
+/// combustion pressure
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ThrottleProfile {
+    /// Sorted by time, with any unused points left as None at the end
+    pub points: [Option<ThrottlePoint>; MAX_THROTTLE_PROFILE_POINTS],
+}
+
+pub const MAX_REDLINES: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RedlineConfig {
+    pub input: RedlineInput,
+    pub min: Option<f32>,
+    pub max: Option<f32>,
+    /// How long the sensor has to stay outside its limits before the redline trips
+    pub persistence_s: f32,
+    pub engine_states: EngineStateMask,
+    pub action: RedlineAction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RedlineInput {
+    Sensor(EcuSensor),
+    /// Estimated engine mixture ratio, only available with an engine performance config
+    MixtureRatio,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum RedlineAction {
+    Alert,
+    ShutdownEngine,
+    /// Shuts down the engine and igniter, and puts the tanks into the configured safe state
+    SafeEcu,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EngineStateMask(pub u8);
+
+pub const MAX_INTERLOCKS: usize = 8;
+
+/// Stops a set of valves from all being open at the same time in manual mode
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ValveInterlock {
+    pub valves: BinaryOutputMask,
+    /// Lets the valves all be open while there's an ignition source
+    pub allowed_while_sparking: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BinaryOutputMask(pub u16);
+
+pub const MAX_SEQUENCE_STEPS: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct SequenceStep {
+    /// Time after the previous step finished
+    pub delay_s: f32,
+    pub action: SequenceAction,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SequenceAction {
+    Command(SequenceCommand),
+    /// Holds the sequence until the condition is met, aborting after timeout_s
+    WaitUntil {
+        condition: SequenceCondition,
+        timeout_s: f32,
+    },
+}
+
+/// The subset of EcuCommand a sequence is allowed to issue
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum SequenceCommand {
+    FireIgniter,
+    FireEngine,
+    ShutdownEngine,
+    SetTankState((TankType, TankState)),
+    SetPumpDuty((PumpType, f32)),
+    SetPumpControlMode((PumpType, PumpControlMode)),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SequenceCondition {
+    TankState((TankType, TankState)),
+    EngineState(EngineState),
+    IgniterState(IgniterState),
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -282,6 +796,17 @@ pub struct IgniterConfig {
     pub test_firing_duration_s: f32,
     pub shutdown_duration_s: f32,
     pub max_throat_temp_k: f32,
+    pub purge: Option<PurgeConfig>,
+}
+
+/// Flushes the propellant out with FuelPurgeValve on shutdown
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PurgeConfig {
+    /// Opens the purge valve this long before the propellant valves close, instead of after
+    pub lead_s: Option<f32>,
+    /// Time after the propellant valves close before the purge valve opens
+    pub delay_s: f32,
+    pub duration_s: f32,
 }
 
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
@@ -291,6 +816,45 @@ pub struct TankConfig {
     pub vent_valve: Option<EcuBinaryOutput>,
     pub press_min_threshold_pa: f32,
     pub press_max_threshold_pa: f32,
+    /// Vents the tank above this pressure, regardless of the press valve
+    pub press_relief_threshold_pa: Option<f32>,
+    /// Minimum time between press valve changes while regulating
+    pub press_valve_min_cycle_time_s: f32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CommsWatchdogConfig {
+    pub timeout_s: f32,
+    pub tank_action: SafeTankAction,
+}
+
+/// Only valves whose driver reports position feedback are monitored
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ValveMonitorConfig {
+    /// How long a valve can take to reach its commanded position before it's considered stuck
+    pub travel_time_s: f32,
+    pub abort_sequence_on_fault: bool,
+}
+
+/// A running pump is cavitating when its inlet or inducer pressure gets within the minimum
+/// margin of the propellant's vapor pressure
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PumpCavitationConfig {
+    pub fuel_vapor_pressure_pa: f32,
+    pub oxidizer_vapor_pressure_pa: f32,
+    /// Net positive suction head the pumps need, as a pressure
+    pub min_npsh_margin_pa: f32,
+    /// How long a pump has to cavitate before it trips
+    pub persistence_s: f32,
+    /// Anything but Alert also stops the cavitating pump
+    pub action: RedlineAction,
+}
+
+/// What happens to the tanks when the ECU is safed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SafeTankAction {
+    Idle,
+    Vent,
 }
 
 impl EcuConfig {
@@ -301,11 +865,163 @@ impl EcuConfig {
             fuel_tank_config: None,
             oxidizer_tank_config: None,
             telemetry_rate_s: 0.02,
+            comms_watchdog_config: Some(CommsWatchdogConfig::default()),
+            safe_tank_action: SafeTankAction::Vent,
+            valve_monitor_config: None,
+            pump_cavitation_config: None,
         }
     }
 }
 
+impl EcuConfig {
+    pub fn validate(&self) -> Result<(), EcuConfigError> {
+        if !self.telemetry_rate_s.is_finite()
+            || self
+                .comms_watchdog_config
+                .as_ref()
+                .is_some_and(|watchdog_config| !watchdog_config.timeout_s.is_finite())
+            || self
+                .valve_monitor_config
+                .is_some_and(|monitor_config| !monitor_config.travel_time_s.is_finite())
+            || self
+                .pump_cavitation_config
+                .is_some_and(|cavitation_config| {
+                    !all_finite([
+                        cavitation_config.fuel_vapor_pressure_pa,
+                        cavitation_config.oxidizer_vapor_pressure_pa,
+                        cavitation_config.min_npsh_margin_pa,
+                        cavitation_config.persistence_s,
+                    ])
+                })
+        {
+            return Err(EcuConfigError::NonFiniteValue);
+        }
+
+        if self.telemetry_rate_s <= 0.0 {
+            return Err(EcuConfigError::InvalidDuration);
+        }
+
+        let mut valve_used = [false; EcuBinaryOutput::COUNT];
+
+        if let Some(engine_config) = &self.engine_config {
+            engine_config.validate()?;
+            valve_used[EcuBinaryOutput::EngineFuelValve.index()] = true;
+            valve_used[EcuBinaryOutput::EngineOxidizerValve.index()] = true;
+        }
+
+        if let Some(igniter_config) = &self.igniter_config {
+            igniter_config.validate()?;
+            valve_used[EcuBinaryOutput::IgniterFuelValve.index()] = true;
+            valve_used[EcuBinaryOutput::IgniterOxidizerValve.index()] = true;
+        }
+
+        // The engine and igniter share the purge valve
+        if self
+            .engine_config
+            .as_ref()
+            .is_some_and(|config| config.purge.is_some())
+            || self
+                .igniter_config
+                .as_ref()
+                .is_some_and(|config| config.purge.is_some())
+        {
+            valve_used[EcuBinaryOutput::FuelPurgeValve.index()] = true;
+        }
+
+        for (tank_type, tank_config) in [
+            (TankType::FuelMain, &self.fuel_tank_config),
+            (TankType::OxidizerMain, &self.oxidizer_tank_config),
+        ] {
+            if let Some(tank_config) = tank_config {
+                tank_config.validate(tank_type)?;
+
+                for valve in [
+                    tank_config.press_valve,
+                    tank_config.fill_valve,
+                    tank_config.vent_valve,
+                ]
+                .into_iter()
+                .flatten()
+                {
+                    if valve_used[valve.index()] {
+                        return Err(EcuConfigError::DuplicateValve(valve));
+                    }
+
+                    valve_used[valve.index()] = true;
+                }
+            }
+        }
+
+        if self
+            .comms_watchdog_config
+            .as_ref()
+            .is_some_and(|watchdog_config| watchdog_config.timeout_s <= 0.0)
+            || self
+                .valve_monitor_config
+                .is_some_and(|monitor_config| monitor_config.travel_time_s <= 0.0)
+            || self
+                .pump_cavitation_config
+                .is_some_and(|cavitation_config| cavitation_config.persistence_s < 0.0)
+        {
+            return Err(EcuConfigError::InvalidDuration);
+        }
+
+        Ok(())
+    }
+}
+
 impl EngineConfig {
+    pub fn validate(&self) -> Result<(), EcuConfigError> {
+        if !all_finite([
+            self.fuel_injector_pressure_setpoint_pa,
+            self.fuel_injector_startup_pressure_tolerance_pa,
+            self.fuel_injector_running_pressure_tolerance_pa,
+            self.oxidizer_injector_pressure_setpoint_pa,
+            self.oxidizer_injector_startup_pressure_tolerance_pa,
+            self.oxidizer_injector_running_pressure_tolerance_pa,
+            self.engine_target_combustion_pressure_pa,
+            self.engine_combustion_pressure_tolerance_pa,
+            self.pump_startup_timeout_s,
+            self.igniter_startup_timeout_s,
+            self.engine_startup_timeout_s,
+            self.engine_firing_duration_s.unwrap_or(0.0),
+            self.engine_shutdown_duration_s,
+            self.pump_pressure_pid.kp,
+            self.pump_pressure_pid.ki,
+            self.pump_pressure_pid.kd,
+            self.pump_pressure_pid.output_min,
+            self.pump_pressure_pid.output_max,
+            self.pump_pressure_pid.max_output_rate_per_s,
+        ]) {
+            return Err(EcuConfigError::NonFiniteValue);
+        }
+
+        if self.pump_startup_timeout_s <= 0.0
+            || self.igniter_startup_timeout_s <= 0.0
+            || self.engine_startup_timeout_s <= 0.0
+            || self.engine_shutdown_duration_s <= 0.0
+            || self
+                .engine_firing_duration_s
+                .is_some_and(|duration_s| duration_s <= 0.0)
+            || self.purge.is_some_and(|purge| !purge.is_valid())
+        {
+            return Err(EcuConfigError::InvalidDuration);
+        }
+
+        if self.pump_pressure_pid.output_min >= self.pump_pressure_pid.output_max {
+            return Err(EcuConfigError::PumpOutputLimitsOutOfOrder);
+        }
+
+        if self
+            .performance
+            .is_some_and(|performance| !performance.is_valid())
+        {
+            return Err(EcuConfigError::InvalidEnginePerformanceConfig);
+        }
+
+        Ok(())
+    }
+
     pub fn default() -> Self {
         Self {
             use_pumps: true,
@@ -322,11 +1038,256 @@ impl EngineConfig {
             engine_startup_timeout_s: 1.0,
             engine_firing_duration_s: None,
             engine_shutdown_duration_s: 0.5,
+            pump_pressure_pid: PidConfig::default(),
+            purge: None,
+            performance: None,
+        }
+    }
+}
+
+impl EnginePerformanceConfig {
+    pub fn is_valid(&self) -> bool {
+        [
+            self.fuel_injector_cda_m2,
+            self.oxidizer_injector_cda_m2,
+            self.fuel_density_kg_m3,
+            self.oxidizer_density_kg_m3,
+            self.throat_area_m2,
+            self.thrust_coefficient,
+        ]
+        .iter()
+        .all(|value| value.is_finite() && *value > 0.0)
+    }
+}
+
+impl PidConfig {
+    pub fn default() -> Self {
+        Self {
+            kp: 1.0 / (500.0 * 6894.76), // Full duty for 500 PSI of error
+            ki: 0.5 / (500.0 * 6894.76),
+            kd: 0.0,
+            output_min: 0.0,
+            output_max: 1.0,
+            max_output_rate_per_s: 2.0,
+        }
+    }
+}
+
+impl ThrottleProfile {
+    pub const fn new() -> Self {
+        Self {
+            points: [None; MAX_THROTTLE_PROFILE_POINTS],
+        }
+    }
+
+    /// Linearly interpolates between the points either side of the given time, holding the
+    /// first and last points outside of the profile. Pump duty is only interpolated if both
+    /// points have one, otherwise it steps at the next point
+    pub fn setpoint(&self, time_s: f32) -> Option<ThrottlePoint> {
+        let mut previous: Option<ThrottlePoint> = None;
+
+        for point in self.points.iter().map_while(|point| *point) {
+            if time_s < point.time_s {
+                let previous = match previous {
+                    Some(previous) => previous,
+                    None => return Some(point),
+                };
+
+                let fraction = (time_s - previous.time_s) / (point.time_s - previous.time_s);
+                let lerp = |from: f32, to: f32| from + (to - from) * fraction;
+
+                return Some(ThrottlePoint {
+                    time_s,
+                    chamber_pressure_pa: lerp(
+                        previous.chamber_pressure_pa,
+                        point.chamber_pressure_pa,
+                    ),
+                    pump_duty: match (previous.pump_duty, point.pump_duty) {
+                        (Some(from), Some(to)) => Some(lerp(from, to)),
+                        (from, _) => from,
+                    },
+                });
+            }
+
+            previous = Some(point);
+        }
+
+        previous
+    }
+
+    /// Points have to be finite, in strictly increasing time order and packed at the start,
+    /// otherwise setpoint() would stop early or interpolate backwards
+    pub fn is_valid(&self) -> bool {
+        let used_points = self
+            .points
+            .iter()
+            .take_while(|point| point.is_some())
+            .count();
+        if self.points[used_points..].iter().any(Option::is_some) {
+            return false;
+        }
+
+        let mut previous_time_s: Option<f32> = None;
+        for point in self.points.iter().map_while(|point| *point) {
+            let finite = point.time_s.is_finite()
+                && point.chamber_pressure_pa.is_finite()
+                && point.pump_duty.is_none_or(f32::is_finite);
+            if !finite || previous_time_s.is_some_and(|previous| point.time_s <= previous) {
+                return false;
+            }
+
+            previous_time_s = Some(point.time_s);
+        }
+
+        true
+    }
+}
+
+impl BinaryOutputMask {
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    pub const fn with(self, output: EcuBinaryOutput) -> Self {
+        Self(self.0 | (1 << output as u16))
+    }
+
+    pub const fn without(self, output: EcuBinaryOutput) -> Self {
+        Self(self.0 & !(1 << output as u16))
+    }
+
+    pub const fn contains(&self, output: EcuBinaryOutput) -> bool {
+        self.0 & (1 << output as u16) != 0
+    }
+
+    pub const fn contains_all(&self, other: BinaryOutputMask) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl From<SequenceCommand> for EcuCommand {
+    fn from(command: SequenceCommand) -> Self {
+        match command {
+            SequenceCommand::FireIgniter => EcuCommand::FireIgniter,
+            SequenceCommand::FireEngine => EcuCommand::FireEngine,
+            SequenceCommand::ShutdownEngine => EcuCommand::ShutdownEngine,
+            SequenceCommand::SetTankState(state) => EcuCommand::SetTankState(state),
+            SequenceCommand::SetPumpDuty(duty) => EcuCommand::SetPumpDuty(duty),
+            SequenceCommand::SetPumpControlMode(mode) => EcuCommand::SetPumpControlMode(mode),
         }
     }
 }
 
+impl EngineStateMask {
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    pub const fn with(self, state: EngineState) -> Self {
+        Self(self.0 | (1 << state as u8))
+    }
+
+    pub const fn contains(&self, state: EngineState) -> bool {
+        self.0 & (1 << state as u8) != 0
+    }
+}
+
+impl Default for ThrottleProfile {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TankConfig {
+    pub fn validate(&self, tank_type: TankType) -> Result<(), EcuConfigError> {
+        if !all_finite([
+            self.press_min_threshold_pa,
+            self.press_max_threshold_pa,
+            self.press_relief_threshold_pa.unwrap_or(0.0),
+            self.press_valve_min_cycle_time_s,
+        ]) {
+            return Err(EcuConfigError::NonFiniteValue);
+        }
+
+        if self.press_min_threshold_pa >= self.press_max_threshold_pa
+            || self
+                .press_relief_threshold_pa
+                .is_some_and(|relief_pa| relief_pa <= self.press_max_threshold_pa)
+        {
+            return Err(EcuConfigError::TankThresholdsOutOfOrder(tank_type));
+        }
+
+        if self.press_valve_min_cycle_time_s < 0.0 {
+            return Err(EcuConfigError::InvalidDuration);
+        }
+
+        Ok(())
+    }
+}
+
+impl CommsWatchdogConfig {
+    pub fn default() -> Self {
+        Self {
+            timeout_s: 1.0,
+            tank_action: SafeTankAction::Vent,
+        }
+    }
+}
+
+impl PurgeConfig {
+    pub fn is_valid(&self) -> bool {
+        all_finite([self.lead_s.unwrap_or(0.0), self.delay_s, self.duration_s])
+            && self.lead_s.is_none_or(|lead_s| lead_s > 0.0)
+            && self.delay_s >= 0.0
+            && self.duration_s > 0.0
+    }
+
+    /// Time into the shutdown that the propellant valves close
+    pub fn propellant_close_time_s(&self) -> f32 {
+        self.lead_s.unwrap_or(0.0)
+    }
+
+    pub fn purge_open_time_s(&self) -> f32 {
+        match self.lead_s {
+            Some(_) => 0.0,
+            None => self.delay_s,
+        }
+    }
+
+    pub fn purge_close_time_s(&self) -> f32 {
+        self.purge_open_time_s() + self.duration_s
+    }
+
+    pub fn purge_open_at(&self, time_s: f32) -> bool {
+        time_s >= self.purge_open_time_s() && time_s < self.purge_close_time_s()
+    }
+}
+
 impl IgniterConfig {
+    pub fn validate(&self) -> Result<(), EcuConfigError> {
+        if !all_finite([
+            self.startup_timeout_s,
+            self.startup_pressure_threshold_pa,
+            self.startup_stable_time_s,
+            self.test_firing_duration_s,
+            self.shutdown_duration_s,
+            self.max_throat_temp_k,
+        ]) {
+            return Err(EcuConfigError::NonFiniteValue);
+        }
+
+        if self.startup_timeout_s <= 0.0
+            || self.startup_stable_time_s < 0.0
+            || self.test_firing_duration_s <= 0.0
+            || self.shutdown_duration_s <= 0.0
+            || self.purge.is_some_and(|purge| !purge.is_valid())
+        {
+            return Err(EcuConfigError::InvalidDuration);
+        }
+
+        Ok(())
+    }
+
     pub fn default() -> Self {
         Self {
             startup_timeout_s: 1.0,
@@ -335,10 +1296,16 @@ impl IgniterConfig {
             test_firing_duration_s: 0.75,
             shutdown_duration_s: 0.5,
             max_throat_temp_k: 500.0,
+            purge: None,
         }
     }
 }
 
+/// NaN fails every comparison, so range checks alone would let it through
+fn all_finite<const N: usize>(values: [f32; N]) -> bool {
+    values.iter().all(|value| value.is_finite())
+}
+
 pub trait EcuDriver {
     fn timestamp(&self) -> f32;
 
@@ -347,9 +1314,170 @@ pub trait EcuDriver {
 
     fn set_binary_valve(&mut self, valve: EcuBinaryOutput, state: bool);
     fn get_binary_valve(&self, valve: EcuBinaryOutput) -> bool;
+    /// Whether the valve is actually open, from limit switches or current sense. None if the
+    /// valve has no feedback, in which case get_binary_valve only echoes the commanded state
+    fn get_binary_valve_feedback(&self, _valve: EcuBinaryOutput) -> Option<bool> {
+        None
+    }
 
     fn set_linear_output(&mut self, output: EcuLinearOutput, value: f32);
     fn get_linear_output(&self, output: EcuLinearOutput) -> f32;
 
     fn as_mut_any(&mut self) -> &mut dyn Any;
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn tank_config(
+        press_valve: EcuBinaryOutput,
+        fill_valve: EcuBinaryOutput,
+        vent_valve: EcuBinaryOutput,
+    ) -> TankConfig {
+        TankConfig {
+            press_valve: Some(press_valve),
+            fill_valve: Some(fill_valve),
+            vent_valve: Some(vent_valve),
+            press_min_threshold_pa: 1e6,
+            press_max_threshold_pa: 2e6,
+            press_relief_threshold_pa: Some(3e6),
+            press_valve_min_cycle_time_s: 0.1,
+        }
+    }
+
+    #[test]
+    fn test_default_config_is_valid() {
+        let mut config = EcuConfig::default();
+        config.fuel_tank_config = Some(tank_config(
+            EcuBinaryOutput::FuelPressValve,
+            EcuBinaryOutput::FuelFillValve,
+            EcuBinaryOutput::FuelVentValve,
+        ));
+        config.oxidizer_tank_config = Some(tank_config(
+            EcuBinaryOutput::OxidizerPressValve,
+            EcuBinaryOutput::OxidizerFillValve,
+            EcuBinaryOutput::OxidizerVentValve,
+        ));
+
+        assert_eq!(config.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_config_validation() {
+        let mut config = EcuConfig::default();
+        config.fuel_tank_config = Some(tank_config(
+            EcuBinaryOutput::FuelPressValve,
+            EcuBinaryOutput::FuelFillValve,
+            EcuBinaryOutput::FuelVentValve,
+        ));
+        config.oxidizer_tank_config = Some(tank_config(
+            EcuBinaryOutput::OxidizerPressValve,
+            EcuBinaryOutput::OxidizerFillValve,
+            EcuBinaryOutput::FuelVentValve,
+        ));
+        assert_eq!(
+            config.validate(),
+            Err(EcuConfigError::DuplicateValve(
+                EcuBinaryOutput::FuelVentValve
+            ))
+        );
+
+        config.oxidizer_tank_config = Some(tank_config(
+            EcuBinaryOutput::OxidizerPressValve,
+            EcuBinaryOutput::OxidizerFillValve,
+            EcuBinaryOutput::EngineOxidizerValve,
+        ));
+        assert_eq!(
+            config.validate(),
+            Err(EcuConfigError::DuplicateValve(
+                EcuBinaryOutput::EngineOxidizerValve
+            ))
+        );
+        config.oxidizer_tank_config = None;
+
+        config
+            .fuel_tank_config
+            .as_mut()
+            .unwrap()
+            .press_relief_threshold_pa = Some(1.5e6);
+        assert_eq!(
+            config.validate(),
+            Err(EcuConfigError::TankThresholdsOutOfOrder(TankType::FuelMain))
+        );
+        config.fuel_tank_config = None;
+
+        config.igniter_config.as_mut().unwrap().startup_timeout_s = 0.0;
+        assert_eq!(config.validate(), Err(EcuConfigError::InvalidDuration));
+    }
+
+    #[test]
+    fn test_config_validation_rejects_nan() {
+        let fuel_tank_config = tank_config(
+            EcuBinaryOutput::FuelPressValve,
+            EcuBinaryOutput::FuelFillValve,
+            EcuBinaryOutput::FuelVentValve,
+        );
+
+        let mut config = EcuConfig::default();
+        config.telemetry_rate_s = f32::NAN;
+        assert_eq!(config.validate(), Err(EcuConfigError::NonFiniteValue));
+
+        let mut config = EcuConfig::default();
+        let mut tank_config = fuel_tank_config;
+        tank_config.press_max_threshold_pa = f32::NAN;
+        config.fuel_tank_config = Some(tank_config);
+        assert_eq!(config.validate(), Err(EcuConfigError::NonFiniteValue));
+
+        let mut config = EcuConfig::default();
+        config
+            .engine_config
+            .as_mut()
+            .unwrap()
+            .fuel_injector_pressure_setpoint_pa = f32::NAN;
+        assert_eq!(config.validate(), Err(EcuConfigError::NonFiniteValue));
+
+        let mut config = EcuConfig::default();
+        config.engine_config.as_mut().unwrap().pump_pressure_pid.kp = f32::NAN;
+        assert_eq!(config.validate(), Err(EcuConfigError::NonFiniteValue));
+
+        let mut config = EcuConfig::default();
+        config.igniter_config.as_mut().unwrap().max_throat_temp_k = f32::NAN;
+        assert_eq!(config.validate(), Err(EcuConfigError::NonFiniteValue));
+
+        let mut config = EcuConfig::default();
+        config.igniter_config.as_mut().unwrap().purge = Some(PurgeConfig {
+            lead_s: None,
+            delay_s: f32::NAN,
+            duration_s: 0.5,
+        });
+        assert_eq!(config.validate(), Err(EcuConfigError::InvalidDuration));
+
+        let mut config = EcuConfig::default();
+        config.valve_monitor_config = Some(ValveMonitorConfig {
+            travel_time_s: f32::NAN,
+            abort_sequence_on_fault: false,
+        });
+        assert_eq!(config.validate(), Err(EcuConfigError::NonFiniteValue));
+    }
+
+    #[test]
+    fn test_fuel_starved_mixture_ratio_serializes_as_a_number() {
+        let performance = EnginePerformance {
+            fuel_mass_flow_kg_s: 0.0,
+            oxidizer_mass_flow_kg_s: 0.5,
+            mixture_ratio: Some(FUEL_STARVED_MIXTURE_RATIO),
+            c_star_m_s: Some(1500.0),
+            thrust_n: 1500.0,
+        };
+
+        let json = serde_json::to_value(performance).unwrap();
+        assert_eq!(
+            json["mixture_ratio"].as_f64(),
+            Some(FUEL_STARVED_MIXTURE_RATIO as f64)
+        );
+
+        let deserialized: EnginePerformance = serde_json::from_value(json).unwrap();
+        assert_eq!(deserialized, performance);
+    }
+}
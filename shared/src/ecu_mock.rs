@@ -4,16 +4,17 @@ use crate::ecu_hal::{EcuBinaryOutput, EcuDriver, EcuLinearOutput, EcuSensor};
 use strum::EnumCount;
 
 pub struct EcuDriverMock {
-    start_timestamp: f64,
+    timestamp: f32,
     sparking: bool,
     binary_valves: [bool; EcuBinaryOutput::COUNT],
+    stuck_valves: [Option<bool>; EcuBinaryOutput::COUNT],
     linear_outputs: [f32; EcuLinearOutput::COUNT],
     sensors: [(f32, f32, f32); EcuSensor::COUNT],
 }
 
 impl EcuDriver for EcuDriverMock {
     fn timestamp(&self) -> f32 {
-        (get_timestamp() - self.start_timestamp) as f32
+        self.timestamp
     }
 
     fn set_sparking(&mut self, state: bool) {
@@ -28,6 +29,10 @@ impl EcuDriver for EcuDriverMock {
         self.binary_valves[valve.index()]
     }
 
+    fn get_binary_valve_feedback(&self, valve: EcuBinaryOutput) -> Option<bool> {
+        Some(self.stuck_valves[valve.index()].unwrap_or(self.binary_valves[valve.index()]))
+    }
+
     fn set_linear_output(&mut self, output: EcuLinearOutput, value: f32) {
         self.linear_outputs[output.index()] = value;
     }
@@ -48,23 +53,23 @@ impl EcuDriver for EcuDriverMock {
 impl EcuDriverMock {
     pub fn new() -> Self {
         Self {
-            start_timestamp: get_timestamp(),
+            timestamp: 0.0,
             sparking: false,
             binary_valves: [false; EcuBinaryOutput::COUNT],
+            stuck_valves: [None; EcuBinaryOutput::COUNT],
             linear_outputs: [0.0; EcuLinearOutput::COUNT],
             sensors: [(0_f32, 0_f32, 0_f32); EcuSensor::COUNT],
         }
     }
-}
 
-#[cfg(test)]
-fn get_timestamp() -> f64 {
-    let now = std::time::SystemTime::now();
-    let duration = now.duration_since(std::time::UNIX_EPOCH).unwrap();
-    duration.as_secs_f64()
-}
+    /// The mock's clock only moves when it's set, so tests can step through time
+    pub fn set_timestamp(&mut self, timestamp: f32) {
+        self.timestamp = timestamp;
+    }
 
-#[cfg(not(test))]
-fn get_timestamp() -> f64 {
-    panic!("ecu_mock.rs: get_timestamp() should only be called in tests")
+    /// Holds the valve's feedback at the given position whatever it's commanded to, or frees
+    /// it again with None
+    pub fn set_stuck_valve(&mut self, valve: EcuBinaryOutput, position: Option<bool>) {
+        self.stuck_valves[valve.index()] = position;
+    }
 }
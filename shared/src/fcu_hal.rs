@@ -1,6 +1,6 @@
 use core::any::Any;
 
-use mint::{Quaternion, Vector3};
+use mint::{ColumnMatrix3, Quaternion, Vector3};
 use serde::{Deserialize, Serialize};
 use strum::EnumCount;
 use strum_macros::{
@@ -20,8 +20,8 @@ pub enum VehicleState {
     Ignition,
     Ascent,
     Descent,
-    // DescentDrogueParachute,
-    // DescentMainParachute,
+    DescentDrogueParachute,
+    DescentMainParachute,
     Landed,
 }
 
@@ -43,6 +43,10 @@ pub enum VehicleCommand {
     },
 }
 
+// Extra channels on top of the igniter, which is what the drivers size their arrays by
+pub const EXTRA_OUTPUT_CHANNEL_COUNT: usize = 3;
+pub const OUTPUT_CHANNEL_COUNT: usize = 1 + EXTRA_OUTPUT_CHANNEL_COUNT;
+
 #[derive(
     Debug,
     Clone,
@@ -80,6 +84,10 @@ pub enum FcuAlertCondition {
     #[strum(props(severity = "1"))]
     NoIgniterContinuity,
     #[strum(props(severity = "1"))]
+    NoDrogueContinuity,
+    #[strum(props(severity = "1"))]
+    NoMainContinuity,
+    #[strum(props(severity = "1"))]
     BatteryVoltageLow,
 }
 
@@ -108,6 +116,23 @@ pub enum FcuSensorData {
         temperature: f32,
         raw_data: u32,
     },
+    Gps {
+        latitude: f64,              // Degrees
+        longitude: f64,             // Degrees
+        altitude: f32,              // Meters above mean sea level
+        velocity_ned: Vector3<f32>, // North, east, down
+        fix_type: GpsFixType,
+        satellites: u8,
+        hdop: f32,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, EnumIter, EnumString)]
+pub enum GpsFixType {
+    NoFix,
+    DeadReckoning,
+    Fix2D,
+    Fix3D,
 }
 
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
@@ -131,6 +156,7 @@ pub struct FcuTelemetryFrame {
     pub output_channels_continuity_bitmask: u16,
     pub pwm_channels: [f32; PwmChannel::COUNT],
     pub apogee: f32,
+    pub predicted_apogee: f32,
     pub battery_voltage: f32,
     pub data_logged_bytes: u32,
 }
@@ -169,6 +195,7 @@ pub enum FcuDebugInfo {
     Stats {
         timestamp: u64,
         apogee: f32,
+        predicted_apogee: f32,
         data_logged_bytes: u32,
         cpu_utilization: u32,
     },
@@ -191,16 +218,54 @@ pub struct FcuConfig {
     pub startup_acceleration_threshold: f32,
     pub startup_acceleration_timeout: f32, // Seconds
     pub calibration_duration: f32,
+    // Launch is the acceleration threshold held for the duration, once the barometer has
+    // climbed by the gain
+    pub launch_acceleration_duration_s: f32,
+    pub launch_altitude_gain_m: f32,
+    // Apogee is velocity turning down with the barometer this far below its peak, never above
+    // the lockout speed
+    pub apogee_barometric_descent_m: f32,
+    pub apogee_mach_lockout_speed: f32, // m/s
+    // Landing is the barometric altitude and climb rate settling for the duration
+    pub landing_altitude_tolerance_m: f32,
+    pub landing_velocity_variance: f32, // (m/s)^2
+    pub landing_stable_duration_s: f32,
     pub kalman_process_variance: f32,
     pub accelerometer_noise_std_dev: Vector3<f32>,
     pub barometer_noise_std_dev: f32,
     pub gps_noise_std_dev: Vector3<f32>,
+    pub gps_velocity_noise_std_dev: Vector3<f32>,
+    // Fixes with fewer satellites or a worse HDOP aren't fused
+    pub gps_min_satellites: u8,
+    pub gps_max_hdop: f32,
     pub gyro_noise_std_dev: Vector3<f32>,
+    // Calibrated field = soft iron matrix * (measured field - hard iron offset)
+    pub magnetometer_hard_iron_offset: Vector3<f32>,
+    pub magnetometer_soft_iron_matrix: ColumnMatrix3<f32>,
+    pub magnetometer_heading_noise_std_dev: f32, // Radians
+    // Motor currents corrupt the field, so heading can be left to the gyro until burnout
+    pub magnetometer_disabled_during_boost: bool,
+    // Coasting ballistics for apogee prediction, so the mass is without propellant
+    pub drag_coefficient: f32,
+    pub drag_reference_area_m2: f32,
+    pub dry_mass_kg: f32,
+    pub recovery_config: Option<RecoveryConfig>,
     // Add a bitfield to contain all of the eventual bool configs
     // pub log_dev_stats: bool,
     //
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RecoveryConfig {
+    pub drogue_channel: OutputChannel,
+    pub drogue_delay_s: f32, // After apogee
+    // Time from liftoff the drogue fires by even if apogee is never detected
+    pub drogue_backup_time_s: f32,
+    pub main_channel: OutputChannel,
+    pub main_deploy_altitude_m: f32, // Above ground level
+    pub pyro_fire_duration_s: f32,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FlightConfig {}
 
@@ -267,6 +332,7 @@ impl FcuTelemetryFrame {
             output_channels_continuity_bitmask: 0,
             pwm_channels: [0.0; PwmChannel::COUNT],
             apogee: 0.0,
+            predicted_apogee: 0.0,
             battery_voltage: 0.0,
             data_logged_bytes: 0,
         }
@@ -343,6 +409,13 @@ impl OutputChannel {
 
         channel_index
     }
+
+    /// Every channel on the board, where iter() only gives the first extra one
+    pub fn all() -> impl Iterator<Item = OutputChannel> {
+        core::iter::once(OutputChannel::SolidMotorIgniter).chain(
+            (0..EXTRA_OUTPUT_CHANNEL_COUNT as u8).map(|index| OutputChannel::Extra { index }),
+        )
+    }
 }
 
 impl FcuConfig {
@@ -352,6 +425,13 @@ impl FcuConfig {
             startup_acceleration_threshold: 0.1,
             startup_acceleration_timeout: 4.0,
             calibration_duration: 5.0,
+            launch_acceleration_duration_s: 0.1,
+            launch_altitude_gain_m: 3.0,
+            apogee_barometric_descent_m: 1.0,
+            apogee_mach_lockout_speed: 270.0,
+            landing_altitude_tolerance_m: 2.0,
+            landing_velocity_variance: 0.25,
+            landing_stable_duration_s: 3.0,
             kalman_process_variance: 1e-3,
             accelerometer_noise_std_dev: Vector3 {
                 x: 1e-2,
@@ -364,11 +444,46 @@ impl FcuConfig {
                 y: 10.0,
                 z: 5.0,
             },
+            gps_velocity_noise_std_dev: Vector3 {
+                x: 0.5,
+                y: 1.0,
+                z: 0.5,
+            },
+            gps_min_satellites: 6,
+            gps_max_hdop: 5.0,
             gyro_noise_std_dev: Vector3 {
                 x: 1e-2,
                 y: 1e-2,
                 z: 1e-2,
             },
+            magnetometer_hard_iron_offset: Vector3 {
+                x: 0.0,
+                y: 0.0,
+                z: 0.0,
+            },
+            magnetometer_soft_iron_matrix: ColumnMatrix3 {
+                x: Vector3 {
+                    x: 1.0,
+                    y: 0.0,
+                    z: 0.0,
+                },
+                y: Vector3 {
+                    x: 0.0,
+                    y: 1.0,
+                    z: 0.0,
+                },
+                z: Vector3 {
+                    x: 0.0,
+                    y: 0.0,
+                    z: 1.0,
+                },
+            },
+            magnetometer_heading_noise_std_dev: 5e-2,
+            magnetometer_disabled_during_boost: true,
+            drag_coefficient: 0.5,
+            drag_reference_area_m2: 8e-3,
+            dry_mass_kg: 10.0,
+            recovery_config: None,
         }
     }
 }
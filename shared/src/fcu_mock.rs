@@ -2,16 +2,16 @@ use core::any::Any;
 
 use crate::{
     comms_hal::{NetworkAddress, Packet},
-    fcu_hal::{FcuDriver, FcuHardwareData, OutputChannel, PwmChannel},
+    fcu_hal::{FcuDriver, FcuHardwareData, OutputChannel, PwmChannel, OUTPUT_CHANNEL_COUNT},
 };
 use strum::EnumCount;
 
 #[derive(Debug)]
 pub struct FcuDriverMock {
     start_timestamp: f64,
-    outputs: [bool; OutputChannel::COUNT],
+    outputs: [bool; OUTPUT_CHANNEL_COUNT],
     pwm: [f32; PwmChannel::COUNT],
-    continuities: [bool; OutputChannel::COUNT],
+    continuities: [bool; OUTPUT_CHANNEL_COUNT],
 }
 
 impl FcuDriver for FcuDriverMock {
@@ -20,7 +20,7 @@ impl FcuDriver for FcuDriverMock {
     }
 
     fn set_output_channel(&mut self, channel: OutputChannel, state: bool) {
-        self.outputs[0] = state;
+        self.outputs[channel.index()] = state;
     }
 
     fn set_pwm_channel(&mut self, channel: PwmChannel, duty_cycle: f32) {
@@ -28,11 +28,11 @@ impl FcuDriver for FcuDriverMock {
     }
 
     fn get_output_channel(&self, channel: OutputChannel) -> bool {
-        self.outputs[0]
+        self.outputs[channel.index()]
     }
 
     fn get_output_channel_continuity(&self, channel: OutputChannel) -> bool {
-        self.continuities[0]
+        self.continuities[channel.index()]
     }
 
     fn get_pwm_channel(&self, channel: PwmChannel) -> f32 {
@@ -76,9 +76,9 @@ impl FcuDriverMock {
     pub fn new() -> Self {
         Self {
             start_timestamp: get_timestamp(),
-            outputs: [false; OutputChannel::COUNT],
+            outputs: [false; OUTPUT_CHANNEL_COUNT],
             pwm: [0.0; PwmChannel::COUNT],
-            continuities: [false; OutputChannel::COUNT],
+            continuities: [false; OUTPUT_CHANNEL_COUNT],
         }
     }
 }
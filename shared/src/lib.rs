@@ -82,6 +82,12 @@ where
         }
     }
 
+    /// Transitions to a new state from outside of the FSM, e.g. to safe the system
+    pub fn force_state(&mut self, controller: &mut C, new_state: F) {
+        let old_state = self.fsm_state.take();
+        self.transition_state(controller, old_state, new_state);
+    }
+
     fn transition_state(&mut self, controller: &mut C, old_state: Option<F>, mut new_state: F) {
         if let Some(mut old_state) = old_state {
             old_state.to_controller_state().exit_state(controller);
@@ -117,8 +123,16 @@ pub struct SensorConfig {
     pub postmin: f32,
     pub postmax: f32,
     pub calibration: Option<SensorCalibration>,
+    /// Largest plausible change per second, in calibrated units
+    pub max_rate_per_s: Option<f32>,
+    /// The sensor is faulted if it doesn't report for this long
+    pub stale_timeout_s: Option<f32>,
 }
 
+// Fraction of the calibrated span a reading can be outside of postmin and postmax
+// before it's treated as a wiring fault
+pub const SENSOR_RANGE_MARGIN: f32 = 0.05;
+
 impl SensorConfig {
     pub const fn default() -> Self {
         Self {
@@ -127,9 +141,26 @@ impl SensorConfig {
             postmin: 0.0,
             postmax: 1.0,
             calibration: None,
+            max_rate_per_s: None,
+            stale_timeout_s: None,
         }
     }
 
+    pub fn in_range(&self, value: f32) -> bool {
+        let min = self.postmin.min(self.postmax);
+        let max = self.postmin.max(self.postmax);
+        let margin = (max - min) * SENSOR_RANGE_MARGIN;
+
+        value >= min - margin && value <= max + margin
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.premin != self.premax
+            && self.postmin != self.postmax
+            && self.max_rate_per_s.is_none_or(|max_rate| max_rate > 0.0)
+            && self.stale_timeout_s.is_none_or(|timeout_s| timeout_s > 0.0)
+    }
+
     pub fn apply(&self, val: f32) -> f32 {
         let lerp = (val - self.premin) / (self.premax - self.premin);
         let mut value = lerp * (self.postmax - self.postmin) + self.postmin;
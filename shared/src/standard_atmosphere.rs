@@ -1,5 +1,9 @@
 use libm::powf;
 
+const AIR_GAS_CONSTANT: f32 = 287.05; // J/(kg K)
+const SEA_LEVEL_TEMPERATURE_K: f32 = 288.15;
+const TEMPERATURE_LAPSE_RATE: f32 = 0.0065; // K/m
+
 pub fn convert_pressure_to_altitude(pressure_pa: f32, temperature_c: f32) -> f32 {
     let pressure_mbar = pressure_pa / 100.0;
     let temperature_k = temperature_c + 273.15;
@@ -27,6 +31,14 @@ pub fn convert_altitude_to_pressure(altitude_m: f32, temperature_c: f32) -> f32
     pressure_pa
 }
 
+/// International standard atmosphere, which holds up to the tropopause at 11 km
+pub fn air_density(altitude_m: f32) -> f32 {
+    let temperature_k = SEA_LEVEL_TEMPERATURE_K - TEMPERATURE_LAPSE_RATE * altitude_m;
+    let pressure_pa = convert_altitude_to_pressure(altitude_m, SEA_LEVEL_TEMPERATURE_K - 273.15);
+
+    pressure_pa / (AIR_GAS_CONSTANT * temperature_k)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -65,4 +77,10 @@ mod tests {
             assert!((altitude_m2 - altitude_m as f32).abs() < epsilon);
         }
     }
+
+    #[test]
+    fn test_air_density() {
+        assert!((air_density(0.0) - 1.225).abs() < 1e-3);
+        assert!((air_density(5000.0) - 0.736).abs() < 1e-2);
+    }
 }
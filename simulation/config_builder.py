@@ -103,6 +103,9 @@ def build_fuel_pump(config: dict, tank_outlet, pump_outlet) -> sil.SilPumpDynami
         tank_outlet,
         pump_outlet,
         (config['fuelPumpConfig']['setPointPsi'] - config['pressConfig']['setPointPsi']) * 6894.76, # Psi -> Pa
+        config['fuelPumpConfig'].get('inletSuctionPsi', 0.0) * 6894.76, # Psi -> Pa
+        config['fuelPumpConfig'].get('inducerPressureRisePsi', 0.0) * 6894.76, # Psi -> Pa
+        config['fuelConfig']['propellantLiquid']['vaporPressurePa'],
     )
 
 def build_oxidizer_pump(config: dict, tank_outlet, pump_outlet) -> sil.SilPumpDynamics:
@@ -110,6 +113,9 @@ def build_oxidizer_pump(config: dict, tank_outlet, pump_outlet) -> sil.SilPumpDy
         tank_outlet,
         pump_outlet,
         (config['oxidizerPumpConfig']['setPointPsi'] - config['pressConfig']['setPointPsi']) * 6894.76, # Psi -> Pa
+        config['oxidizerPumpConfig'].get('inletSuctionPsi', 0.0) * 6894.76, # Psi -> Pa
+        config['oxidizerPumpConfig'].get('inducerPressureRisePsi', 0.0) * 6894.76, # Psi -> Pa
+        config['oxidizerConfig']['propellantLiquid']['vaporPressurePa'],
     )
 
 def _propellant_definition(prop_config: dict) -> sil.LiquidDefinition:
@@ -80,7 +80,8 @@ class EngineSimulation(SimulationBase):
         self.logger = sil.Logger([self.eth_network])
         self.logger.dt = self.sim_config["sim_update_rate"]
 
-        self.ecu.update_ecu_config(self.project_config["softwareConfig"]["ecu0"])
+        self.ecu_config = self.project_config["softwareConfig"]["ecu0"]
+        self.ecu.update_ecu_config(self.ecu_config)
 
     def advance_timestep(self):
         self.dynamics_manager.update(self.t, self.dt)
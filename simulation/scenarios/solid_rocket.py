@@ -25,17 +25,20 @@ class SolidRocketSimulation(SimulationBase):
         self.mission_ctrl_radio_iface = sil.SilNetworkIface(self.mission_ctrl_radio_phy)
 
         self.fcu = sil.FcuSil([self.fcu_radio_iface])
+        self.fcu.gps = sil.SilGpsSensor(32.990254, -106.974998, 1401.0) # Spaceport America
+        self.fcu.magnetometer = sil.SilMagnetometerSensor([3.0, -42.1, 23.1]) # Spaceport America, microtesla
         self.mission_ctrl = sil.MissionControl([self.mission_ctrl_radio_iface])
 
         self.dynamics = sil.SilVehicleDynamics()
         self.logger = sil.Logger([self.radio_network])
         self.config = config
-        self.vehicle_components = VehicleComponents(self.fcu, self.dynamics, self.config)
         self.logger.dt = self.config.sim_update_rate
         self.dt = self.config.sim_update_rate
         self.t = 0.0
 
+        # Before the vehicle components, which wire up whatever recovery hardware it configures
         self.fcu.update_fcu_config(self.config.fcu_config)
+        self.vehicle_components = VehicleComponents(self.fcu, self.dynamics, self.config)
 
     # Meant as an easy way for tests to simulate until in Idle state,
     # leaving one place that has this logic instead of every test
@@ -84,7 +87,10 @@ class SolidRocketSimulation(SimulationBase):
             self.fcu.update_angular_velocity(angular_velocity)
 
         if math.fmod(self.t, self.config.gps_data_rate) <= self.dt:
-            self.fcu.update_gps(gps_noise(self.dynamics.position, self.config))
+            self.fcu.update_gps(self.dynamics.position, self.dynamics.velocity)
+
+        if math.fmod(self.t, self.config.mag_data_rate) <= self.dt:
+            self.fcu.update_magnetometer(self.dynamics.orientation)
 
         # Apply random noise on ascent to simulate wind
         if math.fmod(self.t, self.config.fcu_update_rate) <= self.dt:
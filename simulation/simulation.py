@@ -21,6 +21,10 @@ def _empty_project_config():
                 "fuel_tank_config": None,
                 "oxidizer_tank_config": None,
                 "telemetry_rate_s": 0.02,
+                "comms_watchdog_config": None,
+                "safe_tank_action": "Vent",
+                "valve_monitor_config": None,
+                "pump_cavitation_config": None,
             },
         },
     }
@@ -19,7 +19,10 @@ pub use vehicle::SilVehicleDynamics;
 
 use pyo3::prelude::*;
 
-use self::fluid::LiquidDefinition;
+use self::{
+    combustion::{calc_chamber_pressure, CombustionData},
+    fluid::{GasDefinition, LiquidDefinition},
+};
 
 #[pyclass]
 #[derive(Debug, Clone)]
@@ -54,6 +57,47 @@ impl InjectorConfig {
     }
 }
 
+/// Inert gas blown through a chamber while the purge valve is open
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct SilPurgeConfig {
+    #[pyo3(get, set)]
+    pub purge_gas: GasDefinition,
+    #[pyo3(get, set)]
+    pub mass_flow_rate_kg_s: Scalar,
+}
+
+#[pymethods]
+impl SilPurgeConfig {
+    #[new]
+    pub fn new(purge_gas: GasDefinition, mass_flow_rate_kg_s: Scalar) -> Self {
+        Self {
+            purge_gas,
+            mass_flow_rate_kg_s,
+        }
+    }
+}
+
+impl SilPurgeConfig {
+    /// The purge gas is cold, so it holds the chamber at a much lower pressure than combustion
+    fn chamber_pressure_pa(&self, throat_area_m2: Scalar) -> Scalar {
+        let purge_gas = CombustionData::new(
+            0.0,
+            self.purge_gas.molecular_weight_kg,
+            self.purge_gas.specific_heat_ratio,
+            ROOM_TEMP_K,
+        );
+
+        calc_chamber_pressure(
+            self.mass_flow_rate_kg_s,
+            throat_area_m2,
+            &purge_gas,
+            ATMOSPHERIC_PRESSURE_PA,
+        )
+        .max(ATMOSPHERIC_PRESSURE_PA)
+    }
+}
+
 #[pyclass]
 pub struct DynamicsManager {
     components: Vec<PyObject>,
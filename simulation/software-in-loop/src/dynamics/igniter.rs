@@ -3,7 +3,7 @@ use pyo3::prelude::*;
 use super::{
     combustion::{calc_chamber_pressure, CombustionData},
     pipe::FluidConnection,
-    InjectorConfig, Scalar, ATMOSPHERIC_PRESSURE_PA,
+    InjectorConfig, Scalar, SilPurgeConfig, ATMOSPHERIC_PRESSURE_PA,
 };
 
 pub const MINIMUM_SUSTAINABLE_CHAMBER_PRESSURE_PA: Scalar = 206843.0; // 30 PSI
@@ -15,6 +15,8 @@ pub struct IgniterState {
     pub chamber_pressure_pa: Scalar,
     #[pyo3(get, set)]
     pub has_ignition_source: bool,
+    #[pyo3(get, set)]
+    pub purge_valve_open: bool,
 }
 
 #[pyclass]
@@ -33,6 +35,10 @@ pub struct SilIgniterDynamics {
     #[pyo3(get, set)]
     pub allow_ignition: bool,
 
+    /// None leaves the chamber without a purge supply
+    #[pyo3(get, set)]
+    pub purge: Option<SilPurgeConfig>,
+
     pub fuel_injector: InjectorConfig,
     pub oxidizer_injector: InjectorConfig,
     pub combustion_data: CombustionData,
@@ -61,6 +67,7 @@ impl SilIgniterDynamics {
             fuel_injector: fuel_injector.clone(),
             oxidizer_injector: oxidizer_injector.clone(),
             allow_ignition: true,
+            purge: None,
             combustion_data: combustion_data.clone(),
             combustion_pressure_modifier: py.None(),
             throat_area_m2: throat_diameter_m.powi(2) * std::f64::consts::PI / 4.0,
@@ -101,6 +108,11 @@ impl SilIgniterDynamics {
             }
         }
 
+        if let Some(purge) = self.purge.as_ref().filter(|_| self.state.purge_valve_open) {
+            target_combustion_pressure_pa =
+                target_combustion_pressure_pa.max(purge.chamber_pressure_pa(self.throat_area_m2));
+        }
+
         let delta = target_combustion_pressure_pa - self.state.chamber_pressure_pa;
 
         self.new_state.chamber_pressure_pa += delta * 10.0 * dt;
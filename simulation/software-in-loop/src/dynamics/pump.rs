@@ -7,6 +7,10 @@ use super::{pipe::FluidConnection, Scalar};
 pub struct PumpState {
     #[pyo3(get)]
     pub pressure_pa: Scalar,
+    #[pyo3(get)]
+    pub inlet_pressure_pa: Scalar,
+    #[pyo3(get)]
+    pub inducer_pressure_pa: Scalar,
     #[pyo3(get, set)]
     pub motor_duty_cycle: f64,
 }
@@ -25,6 +29,13 @@ pub struct SilPumpDynamics {
     pub outlet: Py<FluidConnection>,
     #[pyo3(get)]
     pub maximum_pressure_rise_pa: Scalar,
+    /// Drop from the feed pressure to the pump inlet at full duty, from accelerating the flow
+    #[pyo3(get)]
+    pub maximum_inlet_suction_pa: Scalar,
+    #[pyo3(get)]
+    pub maximum_inducer_pressure_rise_pa: Scalar,
+    #[pyo3(get)]
+    pub vapor_pressure_pa: Scalar,
 
     pub pressure_velocity: Scalar,
 }
@@ -32,10 +43,21 @@ pub struct SilPumpDynamics {
 #[pymethods]
 impl SilPumpDynamics {
     #[new]
+    #[pyo3(signature = (
+        inlet,
+        outlet,
+        maximum_pressure_rise_pa,
+        maximum_inlet_suction_pa = 0.0,
+        maximum_inducer_pressure_rise_pa = 0.0,
+        vapor_pressure_pa = 0.0,
+    ))]
     pub fn new(
         inlet: Py<FluidConnection>,
         outlet: Py<FluidConnection>,
         maximum_pressure_rise_pa: Scalar,
+        maximum_inlet_suction_pa: Scalar,
+        maximum_inducer_pressure_rise_pa: Scalar,
+        vapor_pressure_pa: Scalar,
     ) -> Self {
         Self {
             state: PumpState::default(),
@@ -43,6 +65,9 @@ impl SilPumpDynamics {
             inlet,
             outlet,
             maximum_pressure_rise_pa,
+            maximum_inlet_suction_pa,
+            maximum_inducer_pressure_rise_pa,
+            vapor_pressure_pa,
             pressure_velocity: 2.0,
         }
     }
@@ -50,10 +75,26 @@ impl SilPumpDynamics {
     pub fn update(&mut self, py: Python, dt: f64) {
         let dt = dt as Scalar;
 
-        let inlet_pressure = self.inlet.borrow(py).outlet_pressure_pa();
+        let feed_pressure_pa = self.inlet.borrow(py).outlet_pressure_pa();
+        let suction_pa = self.calc_target_inlet_suction_pa();
+
+        // The propellant boils rather than dropping below its vapor pressure, and the pump loses
+        // head with the share of the flow that's vapor
+        let npsh_available_pa = (feed_pressure_pa - self.vapor_pressure_pa).max(0.0);
+        let liquid_fraction = if suction_pa > npsh_available_pa {
+            npsh_available_pa / suction_pa
+        } else {
+            1.0
+        };
 
-        let target_pressure_rise_pa = self.calc_target_pressure_rise_pa();
-        let delta = target_pressure_rise_pa - self.state.pressure_pa + inlet_pressure;
+        self.new_state.inlet_pressure_pa =
+            (feed_pressure_pa - suction_pa).max(self.vapor_pressure_pa.min(feed_pressure_pa));
+        self.new_state.inducer_pressure_pa = self.new_state.inlet_pressure_pa
+            + self.calc_target_inducer_pressure_rise_pa() * liquid_fraction;
+
+        let target_pressure_rise_pa = self.calc_target_pressure_rise_pa() * liquid_fraction;
+        let delta =
+            target_pressure_rise_pa - self.state.pressure_pa + self.new_state.inducer_pressure_pa;
 
         self.new_state.pressure_pa += delta * self.pressure_velocity * dt;
         self.outlet
@@ -74,11 +115,16 @@ impl SilPumpDynamics {
         duty_cycle * self.maximum_pressure_rise_pa
     }
 
+    /// Dynamic pressure goes with the square of the flow, which follows the duty cycle
     fn calc_target_inlet_suction_pa(&self) -> Scalar {
-        0.0
+        let duty_cycle = self.state.motor_duty_cycle as Scalar;
+
+        duty_cycle * duty_cycle * self.maximum_inlet_suction_pa
     }
 
     fn calc_target_inducer_pressure_rise_pa(&self) -> Scalar {
-        0.0
+        let duty_cycle = self.state.motor_duty_cycle as Scalar;
+
+        duty_cycle * self.maximum_inducer_pressure_rise_pa
     }
 }
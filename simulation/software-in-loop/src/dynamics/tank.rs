@@ -15,6 +15,8 @@ pub struct SilTankPressConfig {
     pub press_pressure_pa: Scalar,
     #[pyo3(get, set)]
     pub press_setpoint_pa: Scalar,
+    #[pyo3(get)]
+    pub press_gas: GasDefinition,
     #[pyo3(get, set)]
     pub press_orifice_diameter_m: Scalar,
     #[pyo3(get, set)]
@@ -235,7 +237,7 @@ impl SilTankDynamics {
                 return 0.0;
             }
 
-            let upstream_gas_density = self.ullage_gas.molecular_weight_kg
+            let upstream_gas_density = press_config.press_gas.molecular_weight_kg
                 * press_config.press_pressure_pa
                 / (GAS_CONSTANT * press_config.press_gas_temp_k);
 
@@ -333,6 +335,7 @@ impl SilTankPressConfig {
     pub fn new(
         press_pressure_pa: Scalar,
         press_setpoint_pa: Scalar,
+        press_gas: GasDefinition,
         press_orifice_diameter_m: Scalar,
         press_orifice_cd: Scalar,
         press_gas_temp_k: Scalar,
@@ -340,6 +343,7 @@ impl SilTankPressConfig {
         Self {
             press_pressure_pa,
             press_setpoint_pa,
+            press_gas,
             press_orifice_diameter_m,
             press_orifice_cd,
             press_gas_temp_k,
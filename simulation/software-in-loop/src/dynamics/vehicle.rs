@@ -19,6 +19,11 @@ pub struct SilVehicleDynamics {
     pub angular_forces: Vector3<Scalar>, // Body frame
     #[pyo3(get, set)]
     pub landed: bool,
+    #[pyo3(get, set)]
+    pub mass_kg: Scalar,
+    // Drag coefficient * reference area, swapped out as each parachute opens
+    #[pyo3(get, set)]
+    pub drag_area_m2: Scalar,
 }
 
 #[pymethods]
@@ -29,7 +34,9 @@ impl SilVehicleDynamics {
         let gravity = Vector3::new(0.0, G, 0.0);
         let gravity_accel_body_frame = self.orientation.inverse() * gravity;
 
-        self.acceleration_body_frame = self.motor_thrust;
+        let drag_accel_body_frame = self.orientation.inverse() * self.drag_acceleration();
+
+        self.acceleration_body_frame = self.motor_thrust + drag_accel_body_frame;
         let acceleration_body_frame_minus_gravity = self.acceleration_body_frame;
         self.acceleration_body_frame += gravity_accel_body_frame;
 
@@ -63,6 +70,8 @@ impl SilVehicleDynamics {
             motor_thrust: Vector3::new(0.0, 0.0, 0.0),
             angular_forces: Vector3::new(0.0, 0.0, 0.0),
             landed: true,
+            mass_kg: 1.0,
+            drag_area_m2: 0.0,
         }
     }
 
@@ -187,6 +196,20 @@ impl SilVehicleDynamics {
     }
 }
 
+impl SilVehicleDynamics {
+    /// World frame, opposing the velocity
+    fn drag_acceleration(&self) -> Vector3<Scalar> {
+        if self.landed {
+            return Vector3::zeros();
+        }
+
+        let air_density =
+            shared::standard_atmosphere::air_density(self.position.y as f32) as Scalar;
+
+        -0.5 * air_density * self.drag_area_m2 * self.velocity.norm() * self.velocity / self.mass_kg
+    }
+}
+
 fn integrate_angular_velocity_rk4(
     quat: UnitQuaternion<Scalar>,
     ang_vel: Vector3<Scalar>,
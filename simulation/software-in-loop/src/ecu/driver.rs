@@ -7,6 +7,7 @@ pub struct EcuDriverSil {
     start_timestamp: f64,
     sparking: bool,
     binary_valves: [bool; EcuBinaryOutput::COUNT],
+    stuck_valves: [Option<bool>; EcuBinaryOutput::COUNT],
     linear_outputs: [f32; EcuLinearOutput::COUNT],
     current_sim_timestamp: f32,
     last_sim_timestamp_update_timestamp: f64,
@@ -29,6 +30,10 @@ impl EcuDriver for EcuDriverSil {
         self.binary_valves[valve.index()]
     }
 
+    fn get_binary_valve_feedback(&self, valve: EcuBinaryOutput) -> Option<bool> {
+        Some(self.stuck_valves[valve.index()].unwrap_or(self.binary_valves[valve.index()]))
+    }
+
     fn set_linear_output(&mut self, output: EcuLinearOutput, value: f32) {
         self.linear_outputs[output.index()] = value;
     }
@@ -52,12 +57,19 @@ impl EcuDriverSil {
             start_timestamp: get_timestamp(),
             sparking: false,
             binary_valves: [false; EcuBinaryOutput::COUNT],
+            stuck_valves: [None; EcuBinaryOutput::COUNT],
             linear_outputs: [0.0; EcuLinearOutput::COUNT],
             current_sim_timestamp: 0.0,
             last_sim_timestamp_update_timestamp: get_timestamp(),
         }
     }
 
+    /// Holds the valve at the given position whatever it's commanded to, or frees it with None.
+    /// Only the feedback is stuck, but that's what the SIL plumbing dynamics follow
+    pub fn set_stuck_valve(&mut self, valve: EcuBinaryOutput, position: Option<bool>) {
+        self.stuck_valves[valve.index()] = position;
+    }
+
     pub fn update_timestamp(&mut self, sim_time: f32) {
         self.current_sim_timestamp = sim_time;
         self.last_sim_timestamp_update_timestamp = get_timestamp();
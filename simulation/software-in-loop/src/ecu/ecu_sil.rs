@@ -137,6 +137,24 @@ impl EcuSil {
         self.ecu.configure_ecu(config);
     }
 
+    /// Valve names as in EcuBinaryOutput, e.g. "FuelVentValve". None frees the valve again
+    pub fn set_stuck_valve(&mut self, valve: &str, position: Option<bool>) -> PyResult<()> {
+        let valve = EcuBinaryOutput::iter()
+            .find(|variant| format!("{:?}", variant) == valve)
+            .ok_or_else(|| {
+                PyErr::new::<pyo3::exceptions::PyValueError, _>(format!("Unknown {}", valve))
+            })?;
+
+        self.ecu
+            .driver
+            .as_mut_any()
+            .downcast_mut::<EcuDriverSil>()
+            .expect("Failed to retrieve driver from ECU object")
+            .set_stuck_valve(valve, position);
+
+        Ok(())
+    }
+
     pub fn update_timestamp(&mut self, sim_time: f32) {
         self.ecu
             .driver
@@ -152,14 +170,13 @@ impl EcuSil {
 
         if let Some(engine) = self.engine.as_ref() {
             let mut engine = engine.borrow_mut(py);
-            engine.fuel_inlet.borrow_mut(py).new_state.closed = !self
-                .ecu
-                .driver
-                .get_binary_valve(EcuBinaryOutput::EngineFuelValve);
-            engine.oxidizer_inlet.borrow_mut(py).new_state.closed = !self
-                .ecu
-                .driver
-                .get_binary_valve(EcuBinaryOutput::EngineOxidizerValve);
+            engine.fuel_inlet.borrow_mut(py).new_state.closed =
+                !self.valve_position(EcuBinaryOutput::EngineFuelValve);
+            engine.oxidizer_inlet.borrow_mut(py).new_state.closed =
+                !self.valve_position(EcuBinaryOutput::EngineOxidizerValve);
+
+            engine.new_state.purge_valve_open =
+                self.valve_position(EcuBinaryOutput::FuelPurgeValve);
 
             if let Some(igniter) = self.igniter.as_ref() {
                 let igniter = igniter.borrow_mut(py);
@@ -171,38 +188,28 @@ impl EcuSil {
         if let Some(igniter) = self.igniter.as_ref() {
             let mut igniter = igniter.borrow_mut(py);
             igniter.new_state.has_ignition_source = self.ecu.driver.get_sparking();
-            igniter.fuel_inlet.borrow_mut(py).new_state.closed = !self
-                .ecu
-                .driver
-                .get_binary_valve(EcuBinaryOutput::IgniterFuelValve);
-            igniter.oxidizer_inlet.borrow_mut(py).new_state.closed = !self
-                .ecu
-                .driver
-                .get_binary_valve(EcuBinaryOutput::IgniterOxidizerValve);
+            igniter.new_state.purge_valve_open =
+                self.valve_position(EcuBinaryOutput::FuelPurgeValve);
+            igniter.fuel_inlet.borrow_mut(py).new_state.closed =
+                !self.valve_position(EcuBinaryOutput::IgniterFuelValve);
+            igniter.oxidizer_inlet.borrow_mut(py).new_state.closed =
+                !self.valve_position(EcuBinaryOutput::IgniterOxidizerValve);
         }
 
         if let Some(fuel_tank) = self.fuel_tank.as_ref() {
             let mut fuel_tank = fuel_tank.borrow_mut(py);
-            fuel_tank.new_state.press_valve_open = self
-                .ecu
-                .driver
-                .get_binary_valve(EcuBinaryOutput::FuelPressValve);
-            fuel_tank.new_state.vent_valve_open = self
-                .ecu
-                .driver
-                .get_binary_valve(EcuBinaryOutput::FuelVentValve);
+            fuel_tank.new_state.press_valve_open =
+                self.valve_position(EcuBinaryOutput::FuelPressValve);
+            fuel_tank.new_state.vent_valve_open =
+                self.valve_position(EcuBinaryOutput::FuelVentValve);
         }
 
         if let Some(oxidizer_tank) = self.oxidizer_tank.as_ref() {
             let mut oxidizer_tank = oxidizer_tank.borrow_mut(py);
-            oxidizer_tank.new_state.press_valve_open = self
-                .ecu
-                .driver
-                .get_binary_valve(EcuBinaryOutput::OxidizerPressValve);
-            oxidizer_tank.new_state.vent_valve_open = self
-                .ecu
-                .driver
-                .get_binary_valve(EcuBinaryOutput::OxidizerVentValve);
+            oxidizer_tank.new_state.press_valve_open =
+                self.valve_position(EcuBinaryOutput::OxidizerPressValve);
+            oxidizer_tank.new_state.vent_valve_open =
+                self.valve_position(EcuBinaryOutput::OxidizerVentValve);
         }
 
         if let Some(fuel_pump) = self.fuel_pump.as_ref() {
@@ -235,14 +242,27 @@ impl EcuSil {
         self.ecu
             .generate_debug_info_all_variants(debug_info_callback);
 
+        // Built from the same output state the ECU puts in its telemetry
+        let outputs = self.ecu.output_state();
+
         let binary_valves = PyDict::new(py);
         for valve in EcuBinaryOutput::iter() {
             binary_valves.set_item(
                 format!("{:?}", valve),
-                self.ecu.driver.get_binary_valve(valve),
+                outputs.binary_valves.contains(valve),
             )?;
         }
         dict.set_item("binary_valves", binary_valves)?;
+        dict.set_item("sparking", outputs.sparking)?;
+
+        let linear_outputs = PyDict::new(py);
+        for output in EcuLinearOutput::iter() {
+            linear_outputs.set_item(
+                format!("{:?}", output),
+                outputs.linear_outputs[output.index()],
+            )?;
+        }
+        dict.set_item("linear_outputs", linear_outputs)?;
 
         let sensors = PyDict::new(py);
         for sensor in EcuSensor::iter() {
@@ -253,12 +273,31 @@ impl EcuSil {
         }
         dict.set_item("sensors", sensors)?;
 
+        // Engine state and performance as mission control would see them
+        let telemetry = self.ecu.generate_telemetry_frame();
+        dict.set_item("engine_state", format!("{:?}", telemetry.engine_state))?;
+        match telemetry.engine_performance {
+            Some(performance) => {
+                dict.set_item("engine_performance", dict_from_obj(py, performance))?
+            }
+            None => dict.set_item("engine_performance", py.None())?,
+        }
+
         dict.get_item_with_error(key)
             .map(|value| value.to_object(py))
     }
 }
 
 impl EcuSil {
+    /// Where the valve actually is going by its feedback, which the dynamics follow rather than
+    /// the commanded state so a stuck valve acts on the tanks and engine too
+    fn valve_position(&self, valve: EcuBinaryOutput) -> bool {
+        self.ecu
+            .driver
+            .get_binary_valve_feedback(valve)
+            .unwrap_or_else(|| self.ecu.driver.get_binary_valve(valve))
+    }
+
     pub fn get_direct_sensor_value(&self, py: Python, sensor: EcuSensor) -> f64 {
         match sensor {
             EcuSensor::FuelTankPressure => self
@@ -326,15 +365,31 @@ impl EcuSil {
                 .as_ref()
                 .map(|pump| pump.borrow(py).state.pressure_pa as f64)
                 .unwrap_or(0.0),
-            EcuSensor::FuelPumpInletPressure => 0.0,
-            EcuSensor::FuelPumpInducerPressure => 0.0,
+            EcuSensor::FuelPumpInletPressure => self
+                .fuel_pump
+                .as_ref()
+                .map(|pump| pump.borrow(py).state.inlet_pressure_pa)
+                .unwrap_or(0.0),
+            EcuSensor::FuelPumpInducerPressure => self
+                .fuel_pump
+                .as_ref()
+                .map(|pump| pump.borrow(py).state.inducer_pressure_pa)
+                .unwrap_or(0.0),
             EcuSensor::OxidizerPumpOutletPressure => self
                 .oxidizer_pump
                 .as_ref()
                 .map(|pump| pump.borrow(py).state.pressure_pa as f64)
                 .unwrap_or(0.0),
-            EcuSensor::OxidizerPumpInletPressure => 0.0,
-            EcuSensor::OxidizerPumpInducerPressure => 0.0,
+            EcuSensor::OxidizerPumpInletPressure => self
+                .oxidizer_pump
+                .as_ref()
+                .map(|pump| pump.borrow(py).state.inlet_pressure_pa)
+                .unwrap_or(0.0),
+            EcuSensor::OxidizerPumpInducerPressure => self
+                .oxidizer_pump
+                .as_ref()
+                .map(|pump| pump.borrow(py).state.inducer_pressure_pa)
+                .unwrap_or(0.0),
         }
     }
 }
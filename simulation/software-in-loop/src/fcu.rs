@@ -1,5 +1,9 @@
 pub mod driver;
+pub mod gps;
+pub mod magnetometer;
 pub mod sil_fcu;
 
 pub use driver::FcuDriverSim;
+pub use gps::SilGpsSensor;
+pub use magnetometer::SilMagnetometerSensor;
 pub use sil_fcu::{convert_altitude_to_pressure, convert_pressure_to_altitude, FcuSil};
@@ -1,13 +1,15 @@
 use std::any::Any;
 
-use shared::fcu_hal::{FcuDriver, FcuHardwareData, OutputChannel, PwmChannel};
+use shared::fcu_hal::{
+    FcuDriver, FcuHardwareData, OutputChannel, PwmChannel, OUTPUT_CHANNEL_COUNT,
+};
 use strum::EnumCount;
 
 #[derive(Debug)]
 pub struct FcuDriverSim {
-    outputs: [bool; OutputChannel::COUNT],
+    outputs: [bool; OUTPUT_CHANNEL_COUNT],
     pwm: [f32; PwmChannel::COUNT],
-    continuities: [bool; OutputChannel::COUNT],
+    continuities: [bool; OUTPUT_CHANNEL_COUNT],
     pub current_sim_timestamp: f32,
     pub last_sim_timestamp_update_timestamp: f64,
 }
@@ -71,9 +73,9 @@ impl FcuDriver for FcuDriverSim {
 impl FcuDriverSim {
     pub fn new() -> Self {
         Self {
-            outputs: [false; OutputChannel::COUNT],
+            outputs: [false; OUTPUT_CHANNEL_COUNT],
             pwm: [0.0; PwmChannel::COUNT],
-            continuities: [false; OutputChannel::COUNT],
+            continuities: [false; OUTPUT_CHANNEL_COUNT],
             current_sim_timestamp: 0.0,
             last_sim_timestamp_update_timestamp: get_timestamp(),
         }
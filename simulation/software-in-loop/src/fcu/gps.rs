@@ -0,0 +1,102 @@
+use std::str::FromStr;
+
+use fcu_rs::state_vector::gps::{launch_frame_to_geodetic, launch_frame_to_ned, GeodeticPosition};
+use nalgebra::Vector3;
+use pyo3::{exceptions::PyValueError, prelude::*};
+use rand_distr::{Distribution, Normal};
+use shared::fcu_hal::{FcuSensorData, GpsFixType};
+
+/// Receiver fed the vehicle's true launch frame position and velocity, reporting them as a
+/// noisy fix around the launch site
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct SilGpsSensor {
+    #[pyo3(get, set)]
+    pub latitude: f64, // Launch site, degrees
+    #[pyo3(get, set)]
+    pub longitude: f64, // Launch site, degrees
+    #[pyo3(get, set)]
+    pub altitude: f32, // Launch site, meters above mean sea level
+    #[pyo3(get, set)]
+    pub position_noise_std_dev: f32,
+    #[pyo3(get, set)]
+    pub velocity_noise_std_dev: f32,
+    /// Name of a GpsFixType, e.g. "Fix3D"
+    #[pyo3(get, set)]
+    pub fix_type: String,
+    #[pyo3(get, set)]
+    pub satellites: u8,
+    #[pyo3(get, set)]
+    pub hdop: f32,
+}
+
+#[pymethods]
+impl SilGpsSensor {
+    #[new]
+    #[pyo3(signature = (
+        latitude,
+        longitude,
+        altitude,
+        position_noise_std_dev = 1.5,
+        velocity_noise_std_dev = 0.2,
+    ))]
+    pub fn new(
+        latitude: f64,
+        longitude: f64,
+        altitude: f32,
+        position_noise_std_dev: f32,
+        velocity_noise_std_dev: f32,
+    ) -> Self {
+        Self {
+            latitude,
+            longitude,
+            altitude,
+            position_noise_std_dev,
+            velocity_noise_std_dev,
+            fix_type: "Fix3D".to_string(),
+            satellites: 10,
+            hdop: 1.0,
+        }
+    }
+}
+
+impl SilGpsSensor {
+    pub fn measure(
+        &self,
+        position: Vector3<f32>,
+        velocity: Vector3<f32>,
+    ) -> PyResult<FcuSensorData> {
+        let fix_type = GpsFixType::from_str(&self.fix_type)
+            .map_err(|_| PyValueError::new_err(format!("Unknown fix type {}", self.fix_type)))?;
+
+        let origin = GeodeticPosition {
+            latitude: self.latitude,
+            longitude: self.longitude,
+            altitude: self.altitude,
+        };
+        let fix =
+            launch_frame_to_geodetic(&origin, &(position + noise(self.position_noise_std_dev)));
+        let velocity_ned = launch_frame_to_ned(&(velocity + noise(self.velocity_noise_std_dev)));
+
+        Ok(FcuSensorData::Gps {
+            latitude: fix.latitude,
+            longitude: fix.longitude,
+            altitude: fix.altitude,
+            velocity_ned: velocity_ned.into(),
+            fix_type,
+            satellites: self.satellites,
+            hdop: self.hdop,
+        })
+    }
+}
+
+pub(crate) fn noise(std_dev: f32) -> Vector3<f32> {
+    let normal_distr = Normal::new(0.0, std_dev).expect("Invalid noise standard deviation");
+    let mut rng = rand::thread_rng();
+
+    Vector3::new(
+        normal_distr.sample(&mut rng),
+        normal_distr.sample(&mut rng),
+        normal_distr.sample(&mut rng),
+    )
+}
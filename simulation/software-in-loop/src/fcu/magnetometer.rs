@@ -0,0 +1,48 @@
+use nalgebra::{UnitQuaternion, Vector3};
+use pyo3::prelude::*;
+use shared::fcu_hal::FcuSensorData;
+
+use super::gps::noise;
+
+/// Magnetometer fed the vehicle's true orientation, reporting the launch site's field in the
+/// body frame along with the vehicle's own hard iron offset
+#[pyclass]
+#[derive(Debug, Clone)]
+pub struct SilMagnetometerSensor {
+    #[pyo3(get, set)]
+    pub field: [f32; 3], // Launch frame, microtesla
+    #[pyo3(get, set)]
+    pub hard_iron_offset: [f32; 3], // Body frame, microtesla
+    #[pyo3(get, set)]
+    pub noise_std_dev: f32,
+}
+
+#[pymethods]
+impl SilMagnetometerSensor {
+    #[new]
+    #[pyo3(signature = (field, hard_iron_offset = [0.0; 3], noise_std_dev = 0.3))]
+    pub fn new(field: [f32; 3], hard_iron_offset: [f32; 3], noise_std_dev: f32) -> Self {
+        Self {
+            field,
+            hard_iron_offset,
+            noise_std_dev,
+        }
+    }
+}
+
+impl SilMagnetometerSensor {
+    pub fn measure(&self, orientation: UnitQuaternion<f32>) -> FcuSensorData {
+        let magnetic_field = orientation.inverse_transform_vector(&Vector3::from(self.field))
+            + Vector3::from(self.hard_iron_offset)
+            + noise(self.noise_std_dev);
+
+        FcuSensorData::Magnetometer {
+            magnetic_field: magnetic_field.into(),
+            raw_data: mint::Vector3 {
+                x: 42,
+                y: 42,
+                z: 42,
+            },
+        }
+    }
+}
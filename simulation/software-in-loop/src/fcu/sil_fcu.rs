@@ -1,8 +1,7 @@
 use std::cell::RefCell;
 use std::rc::Rc;
-use std::str::FromStr;
 
-use super::FcuDriverSim;
+use super::{FcuDriverSim, SilGpsSensor, SilMagnetometerSensor};
 use crate::network::SilNetworkIface;
 use crate::ser::{dict_from_obj, obj_from_dict};
 use big_brother::big_brother::MAX_INTERFACE_COUNT;
@@ -10,13 +9,13 @@ use big_brother::interface::mock_interface::MockInterface;
 use big_brother::interface::BigBrotherInterface;
 use fcu_rs::{Fcu, FcuBigBrother};
 use mint::Vector3;
+use nalgebra::{Quaternion, UnitQuaternion};
 use pyo3::exceptions::PyTypeError;
 use pyo3::prelude::*;
 use pyo3::types::{PyDict, PyList};
 use shared::comms_hal::NetworkAddress;
 use shared::fcu_hal::{FcuSensorData, OutputChannel};
 use shared::logger::DataPointLoggerMock;
-use strum::IntoEnumIterator;
 
 #[pyclass(unsendable)]
 pub struct FcuSil {
@@ -27,6 +26,12 @@ pub struct FcuSil {
     pub(crate) _big_brother: Rc<RefCell<FcuBigBrother<'static>>>,
     pub(crate) _data_point_logger: Rc<RefCell<DataPointLoggerMock>>,
     pub(crate) fcu: Fcu<'static>,
+    /// None leaves the FCU without a GPS receiver
+    #[pyo3(get, set)]
+    pub gps: Option<Py<SilGpsSensor>>,
+    /// None leaves the FCU without a magnetometer
+    #[pyo3(get, set)]
+    pub magnetometer: Option<Py<SilMagnetometerSensor>>,
 }
 
 #[pymethods]
@@ -79,6 +84,8 @@ impl FcuSil {
             _big_brother: big_brother,
             _data_point_logger: data_point_logger,
             fcu,
+            gps: None,
+            magnetometer: None,
         }
     }
 
@@ -117,8 +124,33 @@ impl FcuSil {
         });
     }
 
-    pub fn update_gps(&mut self, _gps: &PyList) {
-        // TOOD
+    /// Takes the vehicle's true launch frame position and velocity
+    pub fn update_gps(&mut self, py: Python, position: &PyList, velocity: &PyList) -> PyResult<()> {
+        let gps_data = match &self.gps {
+            Some(gps) => gps
+                .borrow(py)
+                .measure(list_to_vec3(position).into(), list_to_vec3(velocity).into())?,
+            None => return Ok(()),
+        };
+
+        self.fcu.update_sensor_data(gps_data);
+
+        Ok(())
+    }
+
+    /// Takes the vehicle's true orientation as [i, j, k, w], like SilVehicleDynamics.orientation
+    pub fn update_magnetometer(&mut self, py: Python, orientation: &PyList) -> PyResult<()> {
+        let orientation: [f32; 4] = orientation.extract()?;
+        let orientation = UnitQuaternion::from_quaternion(Quaternion::from(orientation));
+
+        let magnetometer_data = match &self.magnetometer {
+            Some(magnetometer) => magnetometer.borrow(py).measure(orientation),
+            None => return Ok(()),
+        };
+
+        self.fcu.update_sensor_data(magnetometer_data);
+
+        Ok(())
     }
 
     pub fn update_fcu_config(&mut self, dict: &PyDict) {
@@ -148,8 +180,7 @@ impl FcuSil {
     }
 
     pub fn set_output(&mut self, channel: &str, state: bool) -> PyResult<()> {
-        let channel = OutputChannel::from_str(channel)
-            .map_err(|_| PyTypeError::new_err("Failed to parse output channel string"))?;
+        let channel = parse_output_channel(channel)?;
 
         self.fcu.driver.set_output_channel(channel, state);
 
@@ -157,8 +188,7 @@ impl FcuSil {
     }
 
     pub fn set_output_continuity(&mut self, channel: &str, state: bool) -> PyResult<()> {
-        let channel = OutputChannel::from_str(channel)
-            .map_err(|_| PyTypeError::new_err("Failed to parse output channel string"))?;
+        let channel = parse_output_channel(channel)?;
 
         self.fcu
             .driver
@@ -190,7 +220,7 @@ impl FcuSil {
             .generate_debug_info_all_variants(debug_info_callback);
 
         let output_channels = PyDict::new(py);
-        for channel in OutputChannel::iter() {
+        for channel in OutputChannel::all() {
             output_channels.set_item(
                 format!("{:?}", channel),
                 self.fcu.driver.get_output_channel(channel),
@@ -199,7 +229,7 @@ impl FcuSil {
         dict.set_item("outputs", output_channels)?;
 
         let output_channel_continuities = PyDict::new(py);
-        for channel in OutputChannel::iter() {
+        for channel in OutputChannel::all() {
             output_channel_continuities.set_item(
                 format!("{:?}", channel),
                 self.fcu.driver.get_output_channel_continuity(channel),
@@ -219,6 +249,15 @@ impl FcuSil {
     }
 }
 
+/// Channels go by their debug names, the same keys as the outputs dict
+fn parse_output_channel(name: &str) -> PyResult<OutputChannel> {
+    OutputChannel::all()
+        .find(|channel| format!("{:?}", channel) == name)
+        .ok_or(PyTypeError::new_err(
+            "Failed to parse output channel string",
+        ))
+}
+
 #[pyfunction]
 pub fn convert_altitude_to_pressure(altitude: f32, temperature: f32) -> f32 {
     shared::standard_atmosphere::convert_altitude_to_pressure(altitude, temperature)
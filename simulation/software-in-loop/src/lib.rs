@@ -16,6 +16,8 @@ fn software_in_loop(_py: Python, m: &PyModule) -> PyResult<()> {
     // m.add_class::<glue::SilGlue>()?;
     m.add_class::<ecu::EcuSil>()?;
     m.add_class::<fcu::FcuSil>()?;
+    m.add_class::<fcu::SilGpsSensor>()?;
+    m.add_class::<fcu::SilMagnetometerSensor>()?;
     m.add_class::<mission_ctrl::MissionControl>()?;
 
     m.add_class::<dynamics::DynamicsManager>()?;
@@ -23,6 +25,7 @@ fn software_in_loop(_py: Python, m: &PyModule) -> PyResult<()> {
 
     m.add_class::<dynamics::SilTankDynamics>()?;
     m.add_class::<dynamics::SilTankPressConfig>()?;
+    m.add_class::<dynamics::SilPurgeConfig>()?;
     m.add_class::<dynamics::SilVehicleDynamics>()?;
     m.add_class::<dynamics::combustion::CombustionData>()?;
     m.add_class::<dynamics::igniter::SilIgniterDynamics>()?;
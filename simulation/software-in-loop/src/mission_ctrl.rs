@@ -10,8 +10,10 @@ use fcu_rs::FcuBigBrother;
 use mission_ctrl_api::CommandHandler;
 use pyo3::{prelude::*, types::PyList};
 use shared::{
-    comms_hal::NetworkAddress, ecu_hal::TankType, REALTIME_SIMULATION_CTRL_PORT,
-    REALTIME_SIMULATION_SIM_PORT,
+    comms_hal::NetworkAddress,
+    ecu_hal::{PumpType, TankType},
+    fcu_hal::{self, VehicleCommand},
+    REALTIME_SIMULATION_CTRL_PORT, REALTIME_SIMULATION_SIM_PORT,
 };
 
 use crate::network::SilNetworkIface;
@@ -31,6 +33,10 @@ pub struct MissionControl {
     #[pyo3(get)]
     pub oxidizer_tank: Py<mission_ctrl_api::tank::Tank>,
     #[pyo3(get)]
+    pub fuel_pump: Py<mission_ctrl_api::pump::Pump>,
+    #[pyo3(get)]
+    pub oxidizer_pump: Py<mission_ctrl_api::pump::Pump>,
+    #[pyo3(get)]
     pub igniter: Py<mission_ctrl_api::igniter::Igniter>,
     #[pyo3(get)]
     pub engine: Py<mission_ctrl_api::engine::Engine>,
@@ -124,6 +130,24 @@ impl MissionControl {
                 ),
             )
             .unwrap(),
+            fuel_pump: Py::new(
+                py,
+                mission_ctrl_api::pump::Pump::new(
+                    format!("{:?}", PumpType::FuelMain),
+                    0,
+                    command_handler.clone(),
+                ),
+            )
+            .unwrap(),
+            oxidizer_pump: Py::new(
+                py,
+                mission_ctrl_api::pump::Pump::new(
+                    format!("{:?}", PumpType::OxidizerMain),
+                    0,
+                    command_handler.clone(),
+                ),
+            )
+            .unwrap(),
             igniter: Py::new(
                 py,
                 mission_ctrl_api::igniter::Igniter::new(0, command_handler.clone()),
@@ -148,16 +172,29 @@ impl MissionControl {
         self.timestamp += dt;
         self.time_since_last_1ms += dt;
 
-        loop {
-            if let Ok(packet) = comms.recv_packet_raw() {
-                if packet.is_none() {
-                    break;
-                }
-            } else {
-                break;
+        Python::with_gil(|py| {
+            let command_handler = self.command_handler.borrow(py);
+            while let Ok(Some((packet, _remote))) = comms.recv_packet() {
+                command_handler.record_packet(&packet);
             }
-        }
+        });
     }
 
     pub fn post_update(&mut self) {}
+
+    pub fn send_arm_vehicle_packet(&self, py: Python) -> PyResult<u16> {
+        self.command_handler
+            .borrow(py)
+            .send_vehicle_command(VehicleCommand::Arm {
+                magic_number: fcu_hal::ARMING_MAGIC_NUMBER,
+            })
+    }
+
+    pub fn send_ignite_solid_motor_packet(&self, py: Python) -> PyResult<u16> {
+        self.command_handler
+            .borrow(py)
+            .send_vehicle_command(VehicleCommand::IgniteSolidMotor {
+                magic_number: fcu_hal::IGNITION_MAGIC_NUMBER,
+            })
+    }
 }
@@ -1,4 +1,7 @@
+import random
+
 import pytest
+import software_in_loop as sil
 from simulation.simulation import build_config
 
 ENDREGA_CONFIG = build_config(
@@ -9,6 +12,10 @@ ENDREGA_CONFIG = build_config(
 def endrega_config():
     return ENDREGA_CONFIG.copy()
 
+@pytest.fixture
+def fcu_flight_sim():
+    return FcuFlightSimulation
+
 @pytest.fixture
 def generic_ecu_sensor_config():
     sensor_config = {
@@ -99,3 +106,178 @@ def generic_ecu_sensor_config():
     }
 
     return sensor_config
+
+SOLID_MOTOR_IGNITER = 'SolidMotorIgniter'
+
+class FcuFlightSimulation:
+    """An FCU and mission control on a SIL network, flying a solid motor through the vehicle
+    dynamics. Sits on the pad with its sensors at rest until it's ignited"""
+
+    def __init__(
+            self,
+            thrust: float = 0.0, # m/s^2
+            burn_time: float = 0.0,
+            mass_kg: float = 1.0,
+            drag_area_m2: float = 0.0,
+            parachutes: dict = None, # Output channel to the drag area once it's fired
+            streamer_drag_area_m2: float = None, # Deployed at the true apogee
+            accelerometer_std_dev: float = 0.0, # m/s^2
+            barometer_std_dev: float = 0.0, # m
+            gps: sil.SilGpsSensor = None,
+            gps_rate: float = 0.1,
+            magnetometer: sil.SilMagnetometerSensor = None,
+            magnetometer_rate: float = 0.05,
+            fcu_config: dict = None,
+        ):
+        self.network = sil.SilNetwork([10, 0, 0, 0])
+
+        self.fcu_phy = sil.SilNetworkPhy(self.network)
+        self.fcu_iface = sil.SilNetworkIface(self.fcu_phy)
+        self.mission_ctrl_phy = sil.SilNetworkPhy(self.network)
+        self.mission_ctrl_iface = sil.SilNetworkIface(self.mission_ctrl_phy)
+
+        self.fcu = sil.FcuSil([self.fcu_iface])
+        self.mission_ctrl = sil.MissionControl([self.mission_ctrl_iface])
+        self.fcu.gps = gps
+        self.fcu.magnetometer = magnetometer
+        self.update_fcu_config(**(fcu_config or {}))
+
+        self.parachutes = parachutes or {}
+        for channel in [SOLID_MOTOR_IGNITER, *self.parachutes]:
+            self.fcu.set_output_continuity(channel, True)
+
+        self.dynamics = sil.SilVehicleDynamics()
+        self.dynamics.mass_kg = mass_kg
+        self.dynamics.drag_area_m2 = drag_area_m2
+        self.streamer_drag_area_m2 = streamer_drag_area_m2
+
+        self.random = random.Random(25)
+        self.accelerometer_std_dev = accelerometer_std_dev
+        self.barometer_std_dev = barometer_std_dev
+        self.accelerometer_offset = [0.0, 0.0, 0.0]
+        self.gyro_bias = [0.0, 0.0, 0.0]
+
+        self.thrust = thrust
+        self.burn_time = burn_time
+        self.ignition_time = None
+        self.apogee_time = None
+        self.touchdown_time = None
+
+        self.t = 0.0
+        self.dt = 0.01
+        self.gps_rate = gps_rate
+        self.time_since_gps = 0.0
+        self.magnetometer_rate = magnetometer_rate
+        self.time_since_magnetometer = 0.0
+
+    def update_fcu_config(self, **changes):
+        config = self.fcu.fcu_config()
+        config.update(changes)
+        self.fcu.update_fcu_config(config)
+
+    def simulate_until_idle(self):
+        self.simulate_for(self.fcu.fcu_config()['calibration_duration'] + 0.1)
+        assert self.fcu['vehicle_state'] == 'Idle'
+
+    def simulate_until_ignition(self):
+        self.simulate_until_idle()
+
+        self.mission_ctrl.send_arm_vehicle_packet()
+        self.simulate_for(0.5)
+        self.mission_ctrl.send_ignite_solid_motor_packet()
+
+        self.simulate_until_state('Ignition', 1.0)
+
+    def simulate_until_ascent(self):
+        self.simulate_until_ignition()
+        self.simulate_until_state('Ascent', 2.0)
+
+    def simulate_until_apogee(self):
+        while self.dynamics.velocity[1] > 0.0:
+            self.advance_timestep()
+
+        return self.dynamics.position[1]
+
+    def simulate_until_state(self, state, timeout_s):
+        start_time = self.t
+
+        while self.fcu['vehicle_state'] != state:
+            assert self.t - start_time < timeout_s, f"Timed out waiting for {state} in {self.fcu['vehicle_state']}"
+            self.advance_timestep()
+
+    def simulate_for(self, duration_s):
+        start_time = self.t
+
+        while self.t - start_time < duration_s:
+            self.advance_timestep()
+
+    def advance_timestep(self):
+        self.fcu.update_timestamp(self.t)
+        self.mission_ctrl.update(self.dt)
+
+        outputs = self.fcu['outputs']
+        if self.ignition_time is None and outputs[SOLID_MOTOR_IGNITER]:
+            self.ignition_time = self.t
+
+        burning = self.ignition_time is not None and self.t - self.ignition_time < self.burn_time
+        if burning and self.thrust > 0.0:
+            self.dynamics.motor_thrust = [0.0, self.thrust, 0.0]
+            self.dynamics.landed = False
+        else:
+            self.dynamics.motor_thrust = [0.0, 0.0, 0.0]
+
+        # Parachutes only ever add drag, so the main isn't undone by a late drogue
+        for channel, drag_area_m2 in self.parachutes.items():
+            if outputs[channel] and self.dynamics.drag_area_m2 < drag_area_m2:
+                self.dynamics.drag_area_m2 = drag_area_m2
+
+        self.dynamics.update(self.dt)
+        self.update_flight_events()
+        self.update_sensors()
+
+        self.fcu.update(self.dt)
+        self.t += self.dt
+
+    def update_flight_events(self):
+        if self.dynamics.landed:
+            # The landed dynamics still sink by a sliver of a g each step, so hold it on the ground
+            x, _, z = self.dynamics.position
+            self.dynamics.position = [x, 0.0, z]
+            return
+
+        if self.apogee_time is None and self.dynamics.velocity[1] < 0.0:
+            self.apogee_time = self.t
+
+            if self.streamer_drag_area_m2 is not None:
+                self.dynamics.drag_area_m2 = self.streamer_drag_area_m2
+
+        # Stopped dead by the ground
+        if self.apogee_time is not None and self.dynamics.position[1] <= 0.0:
+            self.touchdown_time = self.t
+            self.dynamics.landed = True
+            self.dynamics.velocity = [0.0, 0.0, 0.0]
+            self.dynamics.position = [0.0, 0.0, 0.0]
+
+    def update_sensors(self):
+        acceleration = self.dynamics.acceleration_body_frame
+        self.fcu.update_acceleration([
+            a + offset + self.random.gauss(0.0, self.accelerometer_std_dev)
+            for a, offset in zip(acceleration, self.accelerometer_offset)
+        ])
+        self.fcu.update_angular_velocity([
+            w + bias for w, bias in zip(self.dynamics.angular_velocity, self.gyro_bias)
+        ])
+        self.fcu.update_barometric_altitude(
+            self.dynamics.position[1] + self.random.gauss(0.0, self.barometer_std_dev)
+        )
+
+        # Both are left out if the FCU doesn't have the sensor
+        self.time_since_gps += self.dt
+        if self.time_since_gps >= self.gps_rate:
+            self.time_since_gps = 0.0
+            self.fcu.update_gps(self.dynamics.position, self.dynamics.velocity)
+
+        self.time_since_magnetometer += self.dt
+        if self.time_since_magnetometer >= self.magnetometer_rate:
+            self.time_since_magnetometer = 0.0
+            self.fcu.update_magnetometer(self.dynamics.orientation)
@@ -0,0 +1,73 @@
+import copy
+import math
+import pytest
+
+from simulation.scenarios.engine_pressurefed import EngineSimulation
+from simulation.tests.test_ecu_tank_selfpress import tanks_pressurized
+
+def fire_engine(engine_sim: EngineSimulation):
+    engine_sim.advance_timestep()
+
+    engine_sim.mission_ctrl.fuel_tank.press()
+    engine_sim.mission_ctrl.oxidizer_tank.press()
+    assert engine_sim.simulate_until(lambda s: tanks_pressurized(s), 5.0)
+    assert engine_sim.simulate_until(
+        lambda s: s.ecu['fuel_tank_state'] == 'Pressurized' and s.ecu['oxidizer_tank_state'] == 'Pressurized',
+        1.0,
+    )
+
+    engine_sim.mission_ctrl.engine.fire()
+    assert engine_sim.simulate_until(lambda s: s.ecu['engine_state'] == 'Firing', 3.0)
+
+def test_performance_matches_engine_dynamics(engine_sim):
+    fire_engine(engine_sim)
+    engine_sim.simulate_for(1.0)
+
+    performance = engine_sim.ecu['engine_performance']
+    fuel_mass_flow_kg_s = engine_sim.engine_dynamics.fuel_inlet.state.mass_flow_rate_kg_s
+    oxidizer_mass_flow_kg_s = engine_sim.engine_dynamics.oxidizer_inlet.state.mass_flow_rate_kg_s
+
+    assert fuel_mass_flow_kg_s > 0.0
+    assert abs(performance['fuel_mass_flow_kg_s'] / fuel_mass_flow_kg_s - 1.0) < 0.05
+    assert abs(performance['oxidizer_mass_flow_kg_s'] / oxidizer_mass_flow_kg_s - 1.0) < 0.05
+    assert abs(performance['mixture_ratio'] / (oxidizer_mass_flow_kg_s / fuel_mass_flow_kg_s) - 1.0) < 0.05
+    assert performance['thrust_n'] > 0.0
+
+def test_mixture_ratio_redline_shuts_down_engine(engine_sim):
+    engine_sim.advance_timestep()
+
+    # Well below anything the engine will run at
+    engine_sim.mission_ctrl.engine.set_redlines([
+        ("MixtureRatio", 0.0, 0.1, 0.05, ["Firing"], "ShutdownEngine"),
+    ])
+
+    fire_engine(engine_sim)
+    assert engine_sim.simulate_until(lambda s: s.ecu['engine_state'] != 'Firing', 0.5)
+
+@pytest.fixture
+def engine_sim(endrega_config):
+    sim_config = {
+        "ecu_update_rate": 0.001,
+        "sim_update_rate": 0.0005,
+    }
+
+    project_config = copy.deepcopy(endrega_config)
+    hardware_config = project_config["hardwareConfig"]
+    engine_config = hardware_config["engineConfig"]
+
+    def injector_cda_m2(diameter_m, cd):
+        return cd * math.pi * diameter_m ** 2 / 4.0
+
+    project_config["softwareConfig"]["ecu0"]["engine_config"]["performance"] = {
+        "fuel_injector_cda_m2": injector_cda_m2(engine_config["fuelInjectorDiameterMeters"], engine_config["fuelInjectorCd"]),
+        "oxidizer_injector_cda_m2": injector_cda_m2(engine_config["oxidizerInjectorDiameterMeters"], engine_config["oxidizerInjectorCd"]),
+        "fuel_density_kg_m3": hardware_config["fuelConfig"]["propellantLiquid"]["densityKgPerM3"],
+        "oxidizer_density_kg_m3": hardware_config["oxidizerConfig"]["propellantLiquid"]["densityKgPerM3"],
+        "throat_area_m2": math.pi * engine_config["throatDiameterMeters"] ** 2 / 4.0,
+        "thrust_coefficient": 1.4,
+    }
+
+    simulation = EngineSimulation(sim_config)
+    simulation.initialize(project_config, False) # False for no realtime
+
+    return simulation
@@ -1,3 +1,4 @@
+import copy
 import software_in_loop as sil
 import pytest
 
@@ -24,8 +25,10 @@ def test_igniter_init_state(igniter_sim):
 def test_no_startup_with_no_pressurized_tanks(igniter_sim):
     igniter_sim.advance_timestep()
 
-    igniter_sim.mission_ctrl.igniter.fire()
-    igniter_sim.advance_timestep()
+    command_handler = igniter_sim.mission_ctrl.command_handler
+    command_id = igniter_sim.mission_ctrl.igniter.fire()
+    assert igniter_sim.simulate_until(lambda s: command_handler.command_result(command_id) is not None, 0.1)
+    assert command_handler.command_result(command_id) == 'Rejected(TanksNotPressurized)'
 
     assert not igniter_sim.simulate_until(lambda s: s.ecu['igniter_state'] != 'Idle', 3.0)
 
@@ -54,8 +57,10 @@ def test_ignition(igniter_sim):
     igniter_sim.mission_ctrl.oxidizer_tank.press()
     assert igniter_sim.simulate_until(lambda s: tanks_pressurized(s), 5.0)
 
-    igniter_sim.mission_ctrl.igniter.fire()
+    command_handler = igniter_sim.mission_ctrl.command_handler
+    command_id = igniter_sim.mission_ctrl.igniter.fire()
     assert igniter_sim.simulate_until(lambda s: s.ecu['igniter_state'] == 'Startup', 1.0)
+    assert command_handler.command_result(command_id) == 'Accepted'
     assert igniter_sim.simulate_until(lambda s: s.ecu['igniter_state'] == 'Firing', 2.0)
     assert igniter_sim.simulate_until(lambda s: s.ecu['igniter_state'] == 'Shutdown', 2.0)
     assert igniter_sim.simulate_until(lambda s: s.ecu['igniter_state'] == 'Idle', 3.0)
@@ -84,3 +89,33 @@ def test_unstable_pressure_no_ignition(igniter_sim):
         assert_fn=no_ignition_assert,
         timeout_s=5.0,
     )
+
+def test_purge_after_shutdown(igniter_sim):
+    ecu_config = copy.deepcopy(igniter_sim.ecu_config)
+    ecu_config["igniter_config"]["purge"] = {
+        "lead_s": None,
+        "delay_s": 0.05,
+        "duration_s": 1.0,
+    }
+    igniter_sim.ecu.update_ecu_config(ecu_config)
+    igniter_sim.igniter_dynamics.purge = sil.SilPurgeConfig(sil.GasDefinition("N2", 28.0, 1.4), 0.002)
+    igniter_sim.advance_timestep()
+
+    igniter_sim.mission_ctrl.fuel_tank.press()
+    igniter_sim.mission_ctrl.oxidizer_tank.press()
+    assert igniter_sim.simulate_until(lambda s: tanks_pressurized(s), 5.0)
+
+    igniter_sim.mission_ctrl.igniter.fire()
+    assert igniter_sim.simulate_until(lambda s: s.ecu['igniter_state'] == 'Firing', 3.0)
+    firing_pressure_pa = igniter_sim.igniter_dynamics.chamber_pressure_pa
+
+    assert igniter_sim.simulate_until(lambda s: s.ecu['binary_valves']['FuelPurgeValve'], 2.0)
+    assert igniter_sim.ecu['igniter_state'] == 'Shutdown'
+
+    # Chamber pressure decays from combustion down to the cold purge flow, then to ambient
+    assert igniter_sim.simulate_until(lambda s: s.igniter_dynamics.chamber_pressure_pa < firing_pressure_pa * 0.5, 0.5)
+    assert igniter_sim.igniter_dynamics.chamber_pressure_pa > sil.ATMOSPHERIC_PRESSURE_PA * 1.1
+
+    assert igniter_sim.simulate_until(lambda s: s.ecu['igniter_state'] == 'Idle', 2.0)
+    assert not igniter_sim.ecu['binary_valves']['FuelPurgeValve']
+    assert igniter_sim.simulate_until(lambda s: s.igniter_dynamics.chamber_pressure_pa < sil.ATMOSPHERIC_PRESSURE_PA * 1.05, 1.0)
@@ -0,0 +1,247 @@
+import software_in_loop as sil
+import pytest
+
+from simulation.simulation import SimulationBase
+import simulation.config_builder as cb
+
+def test_pump_inlet_and_inducer_pressure(pump_sim):
+    pump_sim.advance_timestep()
+
+    pump_sim.mission_ctrl.fuel_pump.set_duty(0.8)
+    assert pump_sim.simulate_until(lambda s: s.ecu['fuel_pump_state'] == 'Pumping', 1.0)
+    pump_sim.simulate_for(1.0)
+
+    feed_pressure_pa = pump_sim.fuel_tank_dynamics.tank_pressure_pa
+    inlet_pressure_pa = pump_sim.fuel_pump.state.inlet_pressure_pa
+    inducer_pressure_pa = pump_sim.fuel_pump.state.inducer_pressure_pa
+
+    assert inlet_pressure_pa < feed_pressure_pa * 0.7
+    assert inducer_pressure_pa > inlet_pressure_pa
+    assert pump_sim.fuel_pump.state.pressure_pa > inducer_pressure_pa
+    assert abs(pump_sim.ecu['sensors']['FuelPumpInletPressure'] - inlet_pressure_pa) < 1.0
+
+def test_fuel_pump_stops_when_starved(pump_sim):
+    pump_sim.advance_timestep()
+
+    pump_sim.mission_ctrl.fuel_pump.set_duty(0.8)
+    assert pump_sim.simulate_until(lambda s: s.ecu['fuel_pump_state'] == 'Pumping', 1.0)
+
+    def assert_pumping(pump_sim: PumpSimulation):
+        assert pump_sim.ecu['fuel_pump_state'] == 'Pumping'
+
+    pump_sim.simulate_assert(assert_pumping, 1.0)
+
+    # Venting the tank starves the pump until the inlet boils
+    pump_sim.mission_ctrl.fuel_tank.vent()
+    assert pump_sim.simulate_until(lambda s: s.ecu['fuel_pump_state'] == 'Idle', 10.0)
+
+    assert pump_sim.ecu['linear_outputs']['FuelPump'] == 0.0
+    assert pump_sim.ecu['oxidizer_pump_state'] == 'Idle'
+
+@pytest.fixture
+def pump_sim(project_config):
+    sim_config = {
+        "ecu_update_rate": 0.001,
+        "sim_update_rate": 0.0005,
+    }
+
+    simulation = PumpSimulation(sim_config)
+    simulation.initialize(project_config, False) # False for no realtime
+
+    return simulation
+
+class PumpSimulation(SimulationBase):
+    def __init__(self, sim_config: dict):
+        super().__init__(sim_config)
+
+    def initialize(self, project_config: dict, realtime: bool):
+        self.project_config = project_config
+        self.realtime = realtime
+
+        self.eth_network = sil.SilNetwork([10, 0, 0, 0])
+
+        self.ecu_eth_phy = sil.SilNetworkPhy(self.eth_network)
+        self.ecu_eth_iface = sil.SilNetworkIface(self.ecu_eth_phy)
+
+        self.mission_ctrl_eth_phy = sil.SilNetworkPhy(self.eth_network)
+        self.mission_ctrl_eth_iface = sil.SilNetworkIface(self.mission_ctrl_eth_phy)
+
+        self.mission_ctrl = sil.MissionControl([self.mission_ctrl_eth_iface], self.realtime)
+
+        self.tank_fuel_pipe = sil.FluidConnection()
+        self.tank_oxidizer_pipe = sil.FluidConnection()
+        self.ox_to_fuel_press_pipe = sil.FluidConnection()
+        self.fuel_pump_outlet_pipe = sil.FluidConnection()
+
+        hardware_config = self.project_config["hardwareConfig"]
+        N2O_VAPOR_PRESSURE_PA = hardware_config["oxidizerConfig"]["propellantLiquid"]["vaporPressurePa"]
+        self.fuel_tank_dynamics = cb.build_fuel_tank(hardware_config, self.tank_fuel_pipe, N2O_VAPOR_PRESSURE_PA, sil.ROOM_TEMP_K)
+        self.oxidizer_tank_dynamics = cb.build_oxidizer_tank(hardware_config, self.tank_oxidizer_pipe, N2O_VAPOR_PRESSURE_PA, sil.ROOM_TEMP_K)
+
+        # Fed straight from the tank, as there's no press config for build_fuel_pump to work from
+        self.fuel_pump = sil.SilPumpDynamics(
+            self.tank_fuel_pipe,
+            self.fuel_pump_outlet_pipe,
+            hardware_config["fuelPumpConfig"]["pressureRisePsi"] * 6894.76, # Psi -> Pa
+            hardware_config["fuelPumpConfig"]["inletSuctionPsi"] * 6894.76, # Psi -> Pa
+            hardware_config["fuelPumpConfig"]["inducerPressureRisePsi"] * 6894.76, # Psi -> Pa
+            hardware_config["fuelConfig"]["propellantLiquid"]["vaporPressurePa"],
+        )
+
+        self.ecu = sil.EcuSil(
+            [self.ecu_eth_iface],
+            0, # ECU index
+            hardware_config["ecuSensorConfig"],
+            self.sim_config["ecu_update_rate"],
+            self.fuel_tank_dynamics,
+            self.oxidizer_tank_dynamics,
+            None, # self.engine_dynamics,
+            None, # self.igniter_dynamics,
+            self.fuel_pump,
+            None, # self.oxidizer_pump,
+        )
+
+        self.fuel_tank_dynamics.ullage_inlet = self.ox_to_fuel_press_pipe
+        self.oxidizer_tank_dynamics.ullage_outlet = self.ox_to_fuel_press_pipe
+
+        self.dynamics_manager = sil.DynamicsManager()
+
+        self.dynamics_manager.add_dynamics_component(self.ecu)
+        self.dynamics_manager.add_dynamics_component(self.mission_ctrl)
+
+        self.dynamics_manager.add_dynamics_component(self.fuel_tank_dynamics)
+        self.dynamics_manager.add_dynamics_component(self.oxidizer_tank_dynamics)
+        self.dynamics_manager.add_dynamics_component(self.fuel_pump)
+        self.dynamics_manager.add_dynamics_component(self.tank_fuel_pipe)
+        self.dynamics_manager.add_dynamics_component(self.tank_oxidizer_pipe)
+        self.dynamics_manager.add_dynamics_component(self.ox_to_fuel_press_pipe)
+        self.dynamics_manager.add_dynamics_component(self.fuel_pump_outlet_pipe)
+
+        self.logger = sil.Logger([self.eth_network])
+        self.logger.dt = self.sim_config["sim_update_rate"]
+
+        self.ecu_config = self.project_config["softwareConfig"]["ecu0"]
+        self.ecu.update_ecu_config(self.ecu_config)
+
+    def advance_timestep(self):
+        self.dynamics_manager.update(self.t, self.dt)
+
+        if not self.realtime:
+            self.logger.log_common_data()
+            self.logger.log_ecu_data(self.ecu)
+
+        self.t += self.dt
+
+        return True
+
+@pytest.fixture
+def project_config(generic_ecu_sensor_config):
+    config = {}
+
+    config["hardwareConfig"] = {
+        "pressConfig": None,
+        "fuelConfig": {
+            "ventDiameterMeters": 0.01,
+            "ventCd": 0.65,
+            "tankVolumeMeters3": 0.005,
+            "propellantMassKg": 4.0,
+            "propellantLiquid": {
+                "name": "75% IPA",
+                "densityKgPerM3": 846.0,
+                "vaporPressurePa": 4400.0,
+            },
+            "ullageGas": {
+                "name": "N2O",
+                "molecularWeightKg": 0.04401,
+                "specificHeatRatio": 0.875,
+            },
+        },
+        "oxidizerConfig": {
+            "ventDiameterMeters": 0.1,
+            "ventCd": 0.65,
+            "tankVolumeMeters3": 0.03,
+            "propellantMassKg": 10.0,
+            "propellantLiquid": {
+                "name": "N2O",
+                "densityKgPerM3": 1220.0,
+                "vaporPressurePa": 5137000.0,
+            },
+            "ullageGas": {
+                "name": "N2O",
+                "molecularWeightKg": 0.04401,
+                "specificHeatRatio": 0.875,
+            },
+        },
+        "igniterConfig": None,
+        "fuelPumpConfig": {
+            "pressureRisePsi": 500.0,
+            "inletSuctionPsi": 400.0,
+            "inducerPressureRisePsi": 50.0,
+        },
+        "oxidizerPumpConfig": None,
+        "engineConfig": None,
+        "ecuSensorConfig": generic_ecu_sensor_config,
+    }
+
+    config["softwareConfig"] = {
+        "ecu0": {
+            "engine_config": {
+                "use_pumps": True,
+                "fuel_injector_pressure_setpoint_pa": 500.0 * 6894.76, # PSI to Pascals
+                "fuel_injector_startup_pressure_tolerance_pa": 25.0 * 6894.76, # PSI to Pascals
+                "fuel_injector_running_pressure_tolerance_pa": 100.0 * 6894.76, # PSI to Pascals
+                "oxidizer_injector_pressure_setpoint_pa": 500.0 * 6894.76, # PSI to Pascals
+                "oxidizer_injector_startup_pressure_tolerance_pa": 25.0 * 6894.76, # PSI to Pascals
+                "oxidizer_injector_running_pressure_tolerance_pa": 100.0 * 6894.76, # PSI to Pascals
+                "engine_target_combustion_pressure_pa": 300.0 * 6894.76, # PSI to Pascals
+                "engine_combustion_pressure_tolerance_pa": 200.0 * 6894.76, # PSI to Pascals
+                "pump_startup_timeout_s": 1.0,
+                "igniter_startup_timeout_s": 1.0,
+                "engine_startup_timeout_s": 1.0,
+                "engine_firing_duration_s": None,
+                "engine_shutdown_duration_s": 0.5,
+                "pump_pressure_pid": {
+                    "kp": 1.0 / (500.0 * 6894.76), # Full duty for 500 PSI of error
+                    "ki": 0.5 / (500.0 * 6894.76),
+                    "kd": 0.0,
+                    "output_min": 0.0,
+                    "output_max": 1.0,
+                    "max_output_rate_per_s": 2.0,
+                },
+                "purge": None,
+                "performance": None,
+            },
+            "igniter_config": None,
+            "fuel_tank_config": {
+                'press_valve': None,
+                'vent_valve': "FuelVentValve",
+                'fill_valve': "FuelFillValve",
+                'press_min_threshold_pa': 500.0 * 6894.76, # PSI to Pascals
+                'press_max_threshold_pa': 900.0 * 6894.76, # PSI to Pascals
+                'press_relief_threshold_pa': 1000.0 * 6894.76, # PSI to Pascals
+                'press_valve_min_cycle_time_s': 0.1,
+            },
+            "oxidizer_tank_config": {
+                'press_valve': None,
+                'vent_valve': "OxidizerVentValve",
+                'fill_valve': "OxidizerFillValve",
+                'press_min_threshold_pa': 500.0 * 6894.76, # PSI to Pascals
+                'press_max_threshold_pa': 900.0 * 6894.76, # PSI to Pascals
+                'press_relief_threshold_pa': 1000.0 * 6894.76, # PSI to Pascals
+                'press_valve_min_cycle_time_s': 0.1,
+            },
+            "telemetry_rate_s": 0.02,
+            "comms_watchdog_config": None,
+            "safe_tank_action": "Vent",
+            "valve_monitor_config": None,
+            "pump_cavitation_config": {
+                "fuel_vapor_pressure_pa": 4400.0,
+                "oxidizer_vapor_pressure_pa": 5137000.0,
+                "min_npsh_margin_pa": 100.0 * 6894.76, # PSI to Pascals
+                "persistence_s": 0.1,
+                "action": "ShutdownEngine",
+            },
+        }
+    }
+
+    return config
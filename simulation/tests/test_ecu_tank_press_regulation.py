@@ -0,0 +1,193 @@
+import software_in_loop as sil
+import pytest
+
+from simulation.simulation import SimulationBase
+import simulation.config_builder as cb
+
+PSI_TO_PA = 6894.76
+
+def test_fuel_tank_regulates_within_band(press_sim):
+    press_sim.advance_timestep()
+    tank_config = press_sim.ecu_config["fuel_tank_config"]
+    min_pressure_pa = tank_config["press_min_threshold_pa"]
+    max_pressure_pa = tank_config["press_max_threshold_pa"]
+
+    press_sim.mission_ctrl.fuel_tank.press()
+    assert press_sim.simulate_until(lambda s: s.fuel_tank_dynamics.tank_pressure_pa > min_pressure_pa, 10.0)
+
+    # Overshoot from the valve cycle time and sensor noise
+    margin_pa = 25.0 * PSI_TO_PA
+    press_valve_cycles = 0
+    last_press_valve_open = press_sim.ecu['binary_valves']['FuelPressValve']
+
+    def assert_in_band(press_sim: PressRegulationSimulation):
+        nonlocal press_valve_cycles, last_press_valve_open
+
+        assert press_sim.ecu['fuel_tank_state'] == 'Pressurized'
+        assert press_sim.ecu['binary_valves']['FuelVentValve'] == False
+        assert press_sim.fuel_tank_dynamics.tank_pressure_pa > min_pressure_pa - margin_pa
+        assert press_sim.fuel_tank_dynamics.tank_pressure_pa < max_pressure_pa + margin_pa
+
+        press_valve_open = press_sim.ecu['binary_valves']['FuelPressValve']
+        if press_valve_open != last_press_valve_open:
+            press_valve_cycles += 1
+        last_press_valve_open = press_valve_open
+
+    press_sim.simulate_assert(assert_in_band, 5.0)
+
+    # Left alone the regulator would take the tank well past the band
+    assert press_valve_cycles >= 1
+    assert press_sim.ecu['binary_valves']['FuelPressValve'] == False
+
+def test_fuel_tank_relieves_overpressure(press_sim):
+    press_sim.advance_timestep()
+    tank_config = press_sim.ecu_config["fuel_tank_config"]
+
+    # Max threshold above the relief limit, so the press valve never closes by itself
+    press_sim.ecu_config["fuel_tank_config"]["press_max_threshold_pa"] = 2000.0 * PSI_TO_PA
+    press_sim.ecu.update_ecu_config(press_sim.ecu_config)
+    press_sim.advance_timestep()
+
+    press_sim.mission_ctrl.fuel_tank.press()
+
+    relief_pressure_pa = tank_config["press_relief_threshold_pa"]
+    assert press_sim.simulate_until(lambda s: s.ecu['binary_valves']['FuelVentValve'], 10.0)
+    assert press_sim.fuel_tank_dynamics.tank_pressure_pa > relief_pressure_pa * 0.95
+    assert press_sim.ecu['binary_valves']['FuelPressValve'] == False
+
+@pytest.fixture
+def press_sim(project_config):
+    sim_config = {
+        "ecu_update_rate": 0.001,
+        "sim_update_rate": 0.0005,
+    }
+
+    simulation = PressRegulationSimulation(sim_config)
+    simulation.initialize(project_config, False) # False for no realtime
+
+    return simulation
+
+class PressRegulationSimulation(SimulationBase):
+    def __init__(self, sim_config: dict):
+        super().__init__(sim_config)
+
+    def initialize(self, project_config: dict, realtime: bool):
+        self.project_config = project_config
+        self.realtime = realtime
+
+        self.eth_network = sil.SilNetwork([10, 0, 0, 0])
+
+        self.ecu_eth_phy = sil.SilNetworkPhy(self.eth_network)
+        self.ecu_eth_iface = sil.SilNetworkIface(self.ecu_eth_phy)
+
+        self.mission_ctrl_eth_phy = sil.SilNetworkPhy(self.eth_network)
+        self.mission_ctrl_eth_iface = sil.SilNetworkIface(self.mission_ctrl_eth_phy)
+
+        self.mission_ctrl = sil.MissionControl([self.mission_ctrl_eth_iface], self.realtime)
+
+        self.tank_fuel_pipe = sil.FluidConnection()
+        self.fuel_tank_dynamics = cb.build_fuel_tank(self.project_config["hardwareConfig"], self.tank_fuel_pipe, sil.ATMOSPHERIC_PRESSURE_PA, sil.ROOM_TEMP_K)
+
+        self.ecu = sil.EcuSil(
+            [self.ecu_eth_iface],
+            0, # ECU index
+            self.project_config["hardwareConfig"]["ecuSensorConfig"],
+            self.sim_config["ecu_update_rate"],
+            self.fuel_tank_dynamics,
+            None, # self.oxidizer_tank_dynamics,
+            None, # self.engine_dynamics,
+            None, # self.igniter_dynamics,
+            None, # self.fuel_pump,
+            None, # self.oxidizer_pump,
+        )
+
+        self.dynamics_manager = sil.DynamicsManager()
+
+        self.dynamics_manager.add_dynamics_component(self.ecu)
+        self.dynamics_manager.add_dynamics_component(self.mission_ctrl)
+
+        self.dynamics_manager.add_dynamics_component(self.fuel_tank_dynamics)
+        self.dynamics_manager.add_dynamics_component(self.tank_fuel_pipe)
+
+        self.logger = sil.Logger([self.eth_network])
+        self.logger.dt = self.sim_config["sim_update_rate"]
+
+        self.ecu_config = self.project_config["softwareConfig"]["ecu0"]
+        self.ecu.update_ecu_config(self.ecu_config)
+
+    def advance_timestep(self):
+        self.dynamics_manager.update(self.t, self.dt)
+
+        if not self.realtime:
+            self.logger.log_common_data()
+            self.logger.log_ecu_data(self.ecu)
+
+        self.t += self.dt
+
+        return True
+
+@pytest.fixture
+def project_config(generic_ecu_sensor_config):
+    config = {}
+
+    config["hardwareConfig"] = {
+        "pressConfig": {
+            "pressurePa": 3000.0 * PSI_TO_PA,
+            # Regulator set above the ECU band so the press valve does the regulating
+            "setPointPa": 900.0 * PSI_TO_PA,
+            "pressGas": {
+                "name": "N2",
+                "molecularWeightKg": 0.028014,
+                "specificHeatRatio": 1.4,
+            },
+            "orificeDiameterMeters": 0.0015,
+            "orificeCd": 0.65,
+            "temperatureKelvin": sil.ROOM_TEMP_K,
+        },
+        "fuelConfig": {
+            "ventDiameterMeters": 0.01,
+            "ventCd": 0.65,
+            "tankVolumeMeters3": 0.005,
+            "propellantMassKg": 2.0,
+            "propellantLiquid": {
+                "name": "75% IPA",
+                "densityKgPerM3": 846.0,
+                "vaporPressurePa": 4.1,
+            },
+            "ullageGas": {
+                "name": "N2",
+                "molecularWeightKg": 0.028014,
+                "specificHeatRatio": 1.4,
+            },
+        },
+        "oxidizerConfig": None,
+        "igniterConfig": None,
+        "fuelPumpConfig": None,
+        "oxidizerPumpConfig": None,
+        "engineConfig": None,
+        "ecuSensorConfig": generic_ecu_sensor_config,
+    }
+
+    config["softwareConfig"] = {
+        "ecu0": {
+            "engine_config": None,
+            "igniter_config": None,
+            "fuel_tank_config": {
+                'press_valve': "FuelPressValve",
+                'vent_valve': "FuelVentValve",
+                'fill_valve': "FuelFillValve",
+                'press_min_threshold_pa': 400.0 * PSI_TO_PA,
+                'press_max_threshold_pa': 500.0 * PSI_TO_PA,
+                'press_relief_threshold_pa': 700.0 * PSI_TO_PA,
+                'press_valve_min_cycle_time_s': 0.1,
+            },
+            "oxidizer_tank_config": None,
+            "telemetry_rate_s": 0.02,
+            "comms_watchdog_config": None,
+            "safe_tank_action": "Vent",
+            "valve_monitor_config": None,
+            "pump_cavitation_config": None,
+        }
+    }
+
+    return config
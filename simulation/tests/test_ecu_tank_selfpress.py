@@ -68,6 +68,25 @@ def test_fuel_tank_depress_and_repress(tank_sim):
     assert tank_sim.ecu['oxidizer_tank_state'] == 'Idle'
     assert tank_sim.ecu['binary_valves']['FuelVentValve'] == False
 
+def test_stuck_vent_valve_holds_pressure(tank_sim):
+    tank_sim.advance_timestep()
+
+    tank_sim.ecu.set_stuck_valve('FuelVentValve', False)
+    tank_sim.mission_ctrl.fuel_tank.vent()
+
+    start_pressure_pa = tank_sim.fuel_tank_dynamics.tank_pressure_pa
+    def assert_pressure_held(tank_sim: TankOnlySimulation):
+        assert tank_sim.fuel_tank_dynamics.tank_pressure_pa > start_pressure_pa * 0.99
+
+    tank_sim.simulate_assert(assert_pressure_held, 1.0)
+
+    # The ECU still commands the valve open, the dynamics follow where it actually is
+    assert tank_sim.ecu['fuel_tank_state'] == 'Venting'
+    assert tank_sim.ecu['binary_valves']['FuelVentValve'] == True
+
+    tank_sim.ecu.set_stuck_valve('FuelVentValve', None)
+    assert tank_sim.simulate_until(lambda s: s.fuel_tank_dynamics.tank_pressure_pa < start_pressure_pa * 0.99, 1.0)
+
 def test_oxidizer_tank_depress_and_repress(tank_sim):
     tank_sim.advance_timestep()
 
@@ -238,6 +257,8 @@ def project_config(generic_ecu_sensor_config):
                 'fill_valve': "FuelFillValve",
                 'press_min_threshold_pa': 500.0 * 6894.76, # PSI to Pascals
                 'press_max_threshold_pa': 900.0 * 6894.76, # PSI to Pascals
+                'press_relief_threshold_pa': 1000.0 * 6894.76, # PSI to Pascals
+                'press_valve_min_cycle_time_s': 0.1,
             },
             "oxidizer_tank_config": {
                 'press_valve': None,
@@ -245,8 +266,14 @@ def project_config(generic_ecu_sensor_config):
                 'fill_valve': "OxidizerFillValve",
                 'press_min_threshold_pa': 500.0 * 6894.76, # PSI to Pascals
                 'press_max_threshold_pa': 900.0 * 6894.76, # PSI to Pascals
+                'press_relief_threshold_pa': 1000.0 * 6894.76, # PSI to Pascals
+                'press_valve_min_cycle_time_s': 0.1,
             },
             "telemetry_rate_s": 0.02,
+            "comms_watchdog_config": None,
+            "safe_tank_action": "Vent",
+            "valve_monitor_config": None,
+            "pump_cavitation_config": None,
         }
     }
 
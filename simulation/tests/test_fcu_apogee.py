@@ -0,0 +1,37 @@
+import pytest
+
+def test_predicted_apogee_during_coast(fcu_sim):
+    fcu_sim.simulate_until_ascent()
+    fcu_sim.simulate_for(fcu_sim.burn_time + 1.0)
+
+    # The estimated velocity wanders as it slows towards apogee, which the prediction inherits
+    predictions = []
+    while fcu_sim.dynamics.velocity[1] > 75.0:
+        fcu_sim.simulate_for(1.0)
+        predictions.append(fcu_sim.fcu['predicted_apogee'])
+
+    apogee = fcu_sim.simulate_until_apogee()
+    assert apogee > 1000.0
+
+    for predicted_apogee in predictions:
+        assert abs(predicted_apogee - apogee) < 0.02 * apogee
+
+def test_drag_free_prediction_overshoots(fcu_sim):
+    fcu_sim.update_fcu_config(drag_coefficient=0.0)
+
+    fcu_sim.simulate_until_ascent()
+    fcu_sim.simulate_for(fcu_sim.burn_time + 1.0)
+    drag_free_prediction = fcu_sim.fcu['predicted_apogee']
+
+    assert drag_free_prediction > 1.2 * fcu_sim.simulate_until_apogee()
+
+@pytest.fixture
+def fcu_sim(fcu_flight_sim):
+    """A single stage flight, with the dynamics' drag matched to the FCU's ballistic model"""
+    fcu_sim = fcu_flight_sim(thrust=100.0, burn_time=2.5)
+
+    config = fcu_sim.fcu.fcu_config()
+    fcu_sim.dynamics.mass_kg = config['dry_mass_kg']
+    fcu_sim.dynamics.drag_area_m2 = config['drag_coefficient'] * config['drag_reference_area_m2']
+
+    return fcu_sim
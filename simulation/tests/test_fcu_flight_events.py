@@ -0,0 +1,63 @@
+import pytest
+
+ACCELEROMETER_STD_DEV = 0.1 # m/s^2
+BAROMETER_STD_DEV = 0.5 # m
+
+def test_knock_on_pad_is_not_a_launch(fcu_sim):
+    fcu_sim.thrust = 0.0
+    fcu_sim.simulate_until_ignition()
+
+    # Something bumps the pad while the igniter fails to light the motor
+    fcu_sim.accelerometer_offset = [0.0, 30.0, 0.0]
+    fcu_sim.simulate_for(0.05)
+    fcu_sim.accelerometer_offset = [0.0, 0.0, 0.0]
+
+    timeout = fcu_sim.fcu.fcu_config()['startup_acceleration_timeout']
+    while fcu_sim.fcu['vehicle_state'] == 'Ignition':
+        assert fcu_sim.t - fcu_sim.ignition_time < timeout + 1.0
+        fcu_sim.advance_timestep()
+
+    assert fcu_sim.fcu['vehicle_state'] == 'Idle'
+
+def test_launch_needs_altitude_gain(fcu_sim):
+    fcu_sim.simulate_until_ignition()
+    fcu_sim.simulate_until_state('Ascent', 2.0)
+
+    config = fcu_sim.fcu.fcu_config()
+    assert fcu_sim.dynamics.position[1] > config['launch_altitude_gain_m']
+    assert fcu_sim.t - fcu_sim.ignition_time < 1.0
+
+def test_apogee_confirmed_after_true_apogee(fcu_sim):
+    fcu_sim.simulate_until_ignition()
+    fcu_sim.simulate_until_state('Ascent', 2.0)
+    fcu_sim.simulate_until_state('Descent', 20.0)
+
+    # Late enough for the barometer to have seen the drop, but not by much
+    assert fcu_sim.apogee_time is not None
+    assert fcu_sim.t - fcu_sim.apogee_time < 1.5
+
+def test_landing_after_touchdown(fcu_sim):
+    fcu_sim.simulate_until_ignition()
+    fcu_sim.simulate_until_state('Ascent', 2.0)
+    fcu_sim.simulate_until_state('Descent', 20.0)
+
+    # A slow descent under the streamer shouldn't look like it's landed
+    while fcu_sim.touchdown_time is None:
+        assert fcu_sim.t - fcu_sim.apogee_time < 30.0
+        fcu_sim.advance_timestep()
+        assert fcu_sim.fcu['vehicle_state'] == 'Descent'
+
+    config = fcu_sim.fcu.fcu_config()
+    fcu_sim.simulate_until_state('Landed', config['landing_stable_duration_s'] + 1.5)
+    assert fcu_sim.t - fcu_sim.touchdown_time >= config['landing_stable_duration_s']
+
+@pytest.fixture
+def fcu_sim(fcu_flight_sim):
+    """A short hop on noisy sensors, coming down under a streamer onto the ground"""
+    return fcu_flight_sim(
+        thrust=40.0,
+        burn_time=0.8,
+        streamer_drag_area_m2=0.5,
+        accelerometer_std_dev=ACCELEROMETER_STD_DEV,
+        barometer_std_dev=BAROMETER_STD_DEV,
+    )
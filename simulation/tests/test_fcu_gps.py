@@ -0,0 +1,35 @@
+import software_in_loop as sil
+import pytest
+
+def test_gps_origin_captured_at_calibration(fcu_sim):
+    fcu_sim.simulate_until_idle()
+
+    calibration = fcu_sim.fcu.state_vector()['sensor_calibration']
+    assert abs(calibration['gps_origin']['latitude'] - fcu_sim.fcu.gps.latitude) < 1e-4
+    assert abs(calibration['gps_origin']['altitude'] - fcu_sim.fcu.gps.altitude) < 1.0
+
+def test_gps_fix_pulls_position(fcu_sim):
+    fcu_sim.simulate_until_idle()
+
+    # Carried 50 m north, which only the GPS can see
+    fcu_sim.dynamics.position = [0.0, 0.0, 50.0]
+    fcu_sim.simulate_for(10.0)
+
+    position = fcu_sim.fcu.state_vector()['kalman']['position']
+    assert abs(position[2] - 50.0) < 10.0
+    assert abs(position[0]) < 10.0
+
+def test_poor_fix_is_ignored(fcu_sim):
+    fcu_sim.simulate_until_idle()
+
+    fcu_sim.fcu.gps.hdop = 25.0
+    fcu_sim.dynamics.position = [0.0, 0.0, 50.0]
+    fcu_sim.simulate_for(5.0)
+
+    assert fcu_sim.fcu.state_vector()['sensor_data'].get('gps_fix') is None
+    assert abs(fcu_sim.fcu.state_vector()['kalman']['position'][2]) < 5.0
+
+@pytest.fixture
+def fcu_sim(fcu_flight_sim):
+    """A stationary FCU fed by the SIL GPS model, with the rest of its sensors at rest"""
+    return fcu_flight_sim(gps=sil.SilGpsSensor(32.990254, -106.974998, 1401.0))
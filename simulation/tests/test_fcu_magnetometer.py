@@ -0,0 +1,47 @@
+import math
+
+import software_in_loop as sil
+import pytest
+
+# Spaceport America, [east, up, north] in microtesla
+LAUNCH_SITE_FIELD = [3.0, -42.1, 23.1]
+HARD_IRON_OFFSET = [12.0, -3.0, 5.0]
+
+def test_reference_field_captured_at_calibration(fcu_sim):
+    fcu_sim.simulate_until_idle()
+
+    reference = fcu_sim.fcu.state_vector()['sensor_calibration']['magnetic_field_reference']
+    for axis in range(3):
+        assert abs(reference[axis] - LAUNCH_SITE_FIELD[axis]) < 0.5
+
+def test_magnetometer_limits_heading_drift(fcu_sim, fcu_flight_sim):
+    gyro_only_sim = fcu_flight_sim()
+
+    for sim in [fcu_sim, gyro_only_sim]:
+        sim.simulate_until_idle()
+
+        # Bias that crept in after calibration, like a gyro warming up on the pad
+        sim.gyro_bias = [0.0, 0.05, 0.0]
+        sim.simulate_for(20.0)
+
+    assert abs(heading(gyro_only_sim.fcu)) > 0.5
+    assert abs(heading(fcu_sim.fcu)) < 0.1
+
+def heading(fcu: sil.FcuSil):
+    """Heading of the body z axis, which starts out pointing north"""
+    i, j, k, w = fcu.state_vector()['kalman']['orientation']
+
+    # Body z axis rotated into the launch frame
+    east = 2.0 * (i * k + w * j)
+    north = 1.0 - 2.0 * (i * i + j * j)
+
+    return math.atan2(east, north)
+
+@pytest.fixture
+def fcu_sim(fcu_flight_sim):
+    """A stationary FCU fed by the SIL magnetometer model, with the rest of its sensors at rest"""
+    return fcu_flight_sim(
+        magnetometer=sil.SilMagnetometerSensor(LAUNCH_SITE_FIELD, HARD_IRON_OFFSET),
+        # The hard iron offset is the vehicle's own, which the config takes back out
+        fcu_config={'magnetometer_hard_iron_offset': HARD_IRON_OFFSET},
+    )
@@ -0,0 +1,84 @@
+import pytest
+
+DROGUE = 'Extra { index: 0 }'
+MAIN = 'Extra { index: 1 }'
+
+DROGUE_DRAG_AREA_M2 = 0.3
+MAIN_DRAG_AREA_M2 = 2.5
+
+RECOVERY_CONFIG = {
+    'drogue_channel': {'Extra': {'index': 0}},
+    'drogue_delay_s': 1.0,
+    'drogue_backup_time_s': 30.0,
+    'main_channel': {'Extra': {'index': 1}},
+    'main_deploy_altitude_m': 150.0,
+    'pyro_fire_duration_s': 0.5,
+}
+
+def test_arming_needs_recovery_continuity(fcu_sim):
+    fcu_sim.fcu.set_output_continuity(MAIN, False)
+    fcu_sim.simulate_until_idle()
+
+    fcu_sim.mission_ctrl.send_arm_vehicle_packet()
+    fcu_sim.simulate_for(0.5)
+    assert fcu_sim.fcu['vehicle_state'] == 'Idle'
+
+    fcu_sim.fcu.set_output_continuity(MAIN, True)
+    fcu_sim.mission_ctrl.send_arm_vehicle_packet()
+    fcu_sim.simulate_for(0.5)
+    assert fcu_sim.fcu['vehicle_state'] == 'Armed'
+
+def test_drogue_at_apogee_then_main(fcu_sim):
+    fcu_sim.simulate_until_ascent()
+
+    fcu_sim.simulate_until_state('Descent', 20.0)
+    apogee_time = fcu_sim.t
+    assert fcu_sim.dynamics.velocity[1] < 5.0
+    assert fcu_sim.dynamics.drag_area_m2 == 0.0
+
+    fcu_sim.simulate_until_state('DescentDrogueParachute', 5.0)
+    assert abs(fcu_sim.t - apogee_time - RECOVERY_CONFIG['drogue_delay_s']) < 0.1
+    assert fcu_sim.fcu['outputs'][DROGUE]
+
+    # Opens on the next step, once the harness sees the output
+    fcu_sim.simulate_for(fcu_sim.dt)
+    assert fcu_sim.dynamics.drag_area_m2 == DROGUE_DRAG_AREA_M2
+
+    # Only a pulse, the e-match is gone once it's fired
+    fcu_sim.simulate_for(RECOVERY_CONFIG['pyro_fire_duration_s'] + 0.1)
+    assert not fcu_sim.fcu['outputs'][DROGUE]
+
+    fcu_sim.simulate_until_state('DescentMainParachute', 60.0)
+    assert abs(fcu_sim.dynamics.position[1] - RECOVERY_CONFIG['main_deploy_altitude_m']) < 15.0
+    assert fcu_sim.fcu['outputs'][MAIN]
+
+    fcu_sim.simulate_for(fcu_sim.dt)
+    assert fcu_sim.dynamics.drag_area_m2 == MAIN_DRAG_AREA_M2
+
+    drogue_descent_rate = -fcu_sim.dynamics.velocity[1]
+    fcu_sim.simulate_for(3.0)
+    assert -fcu_sim.dynamics.velocity[1] < drogue_descent_rate / 2.0
+    assert not fcu_sim.fcu['outputs'][MAIN]
+
+def test_drogue_backup_timer(fcu_sim):
+    fcu_sim.update_fcu_config(
+        recovery_config=dict(RECOVERY_CONFIG, drogue_backup_time_s=4.0)
+    )
+
+    fcu_sim.simulate_until_ascent()
+    fcu_sim.simulate_until_state('DescentDrogueParachute', 5.0)
+
+    # Still climbing hard, so this was the timer and not apogee
+    assert fcu_sim.dynamics.velocity[1] > 20.0
+    assert fcu_sim.fcu['outputs'][DROGUE]
+
+@pytest.fixture
+def fcu_sim(fcu_flight_sim):
+    """A small dual-deploy flight, with the drag of each parachute added to the dynamics as it fires"""
+    return fcu_flight_sim(
+        thrust=50.0,
+        burn_time=2.0,
+        mass_kg=5.0,
+        parachutes={DROGUE: DROGUE_DRAG_AREA_M2, MAIN: MAIN_DRAG_AREA_M2},
+        fcu_config={'recovery_config': RECOVERY_CONFIG},
+    )
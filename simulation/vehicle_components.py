@@ -5,18 +5,30 @@ from pysim.config import SimConfig
 
 SOLID_MOTOR_IGNITER_NAME = 'SolidMotorIgniter'
 
+# Drag coefficient * reference area once each parachute is out
+DROGUE_DRAG_AREA_M2 = 0.3
+MAIN_DRAG_AREA_M2 = 2.5
+
 class VehicleComponents:
     def __init__(self, fcu: FcuSil, dynamics: SilVehicleDynamics, config: SimConfig):
         self.fcu = fcu
         self.dynamics = dynamics
         self.config = config
 
+        self.dynamics.mass_kg = self.config.vehicle_mass
         self.set_solid_motor_igniter_continuity(True)
 
+        self.recovery_config = self.fcu.fcu_config().get('recovery_config')
+        if self.recovery_config is not None:
+            self.fcu.set_output_continuity(output_channel_name(self.recovery_config['drogue_channel']), True)
+            self.fcu.set_output_continuity(output_channel_name(self.recovery_config['main_channel']), True)
+
         self.auto_ignite_ignition_packet_sent = False
         self.solid_motor_ignited = False
         self.solid_motor_burning = False
         self.ignition_time = 0.0
+        self.drogue_deployed = False
+        self.main_deployed = False
 
     def update(self, t: float, dt: float):
         if self.config.auto_ignite_solid_motor and self.fcu['vehicle_state'] == 'Armed' and not self.auto_ignite_ignition_packet_sent:
@@ -38,6 +50,22 @@ class VehicleComponents:
                 self.dynamics.motor_thrust = [0.0, thrust, 0.0]
                 self.dynamics.landed = False # TODO Have dynamics figure this out on its own
 
+        if self.recovery_config is not None:
+            self.update_parachutes(t)
+
+    def update_parachutes(self, t: float):
+        outputs = self.fcu['outputs']
+
+        if not self.drogue_deployed and outputs[output_channel_name(self.recovery_config['drogue_channel'])]:
+            print(f'Drogue deployed at {t}')
+            self.drogue_deployed = True
+            self.dynamics.drag_area_m2 = DROGUE_DRAG_AREA_M2
+
+        if not self.main_deployed and outputs[output_channel_name(self.recovery_config['main_channel'])]:
+            print(f'Main deployed at {t}')
+            self.main_deployed = True
+            self.dynamics.drag_area_m2 = MAIN_DRAG_AREA_M2
+
     def set_solid_motor_igniter_continuity(self, state: bool):
         self.fcu.set_output_continuity(SOLID_MOTOR_IGNITER_NAME, state)
 
@@ -49,3 +77,10 @@ class VehicleComponents:
             self.ignition_time = t
 
             self.set_solid_motor_igniter_continuity(False)
+
+def output_channel_name(channel) -> str:
+    """Name the FCU outputs dict uses for a channel from the FCU config"""
+    if channel == SOLID_MOTOR_IGNITER_NAME:
+        return SOLID_MOTOR_IGNITER_NAME
+
+    return f"Extra {{ index: {channel['Extra']['index']} }}"